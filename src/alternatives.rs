@@ -0,0 +1,255 @@
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+
+use crate::model::{LpModel, format_constraint};
+use crate::presolve::INFEASIBLE;
+use crate::simplexef64::{
+    self, Basis, BranchAndBoundOptions, BranchAndBoundResult, NO_INTEGER_SOLUTION, PRECISION,
+    REFACTOR_FREQUENCY, Start, Status, get_solution, logical_columns, parse_lp_two_phases,
+    two_phases,
+};
+
+// Garde-fou contre l'explosion des bases dégénérées d'un même sommet
+const MAX_BASES: usize = 10_000;
+
+// Sommets optimaux d'un programme linéaire
+#[derive(Debug, Clone, Serialize)]
+pub struct Alternatives {
+    pub vertices: Vec<Vec<(String, f64)>>,
+    // Une colonne de coût réduit nul sans ligne bloquante : la face optimale est non bornée
+    pub unbounded_face: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolSolution {
+    pub variables: Vec<(String, f64)>,
+    pub z: f64,
+}
+
+// Enumère les sommets optimaux du programme (relaxation continue), au plus limit. Depuis
+// la base optimale des deux phases, chaque colonne hors base de coût réduit nul entre dans
+// la base par un pivot du simplexe primal : la nouvelle base est encore optimale et son
+// sommet est gardé s'il n'a pas déjà été vu. Les bases sont parcourues en largeur.
+pub fn optimal_vertices(model: &LpModel, limit: usize) -> Result<Alternatives, String> {
+    let mut relaxation = model.clone();
+    relaxation.integers.fill(false);
+    let (matrix, variables, is_min, vars_hash_map, original_cost) =
+        parse_lp_two_phases(&relaxation.to_lp_string())?;
+    let width = matrix.columns();
+    let logicals = logical_columns(&variables, matrix.rows);
+    let mut costs = vec![0.0; width];
+    let mut is_artificial = vec![false; width];
+    for (name, index) in &vars_hash_map {
+        let var = &variables[*index];
+        costs[var.column] = original_cost[name];
+        is_artificial[var.column] = var.is_artificial;
+    }
+    let (status, basis) = two_phases(&matrix, &variables, &logicals, &costs, Start::Logical);
+    if status != Status::Optimal {
+        return Err("Le problème n'a pas de solution optimale".to_string());
+    }
+    let mut alternatives = Alternatives {
+        vertices: vec![],
+        unbounded_face: false,
+    };
+    let mut points: Vec<Vec<f64>> = vec![];
+    let mut visited = HashSet::new();
+    let key = |basis: &Basis| {
+        let mut heads = basis.heads.clone();
+        heads.sort_unstable();
+        heads
+    };
+    visited.insert(key(&basis));
+    let mut queue = VecDeque::from([basis]);
+    while let Some(mut basis) = queue.pop_front() {
+        if basis.inverse.len() >= REFACTOR_FREQUENCY {
+            basis = Basis::factor(&matrix, &basis.heads, &logicals);
+        }
+        let values = basis.column_values(width);
+        let scale = values.iter().fold(1.0_f64, |max, x| max.max(x.abs()));
+        if !points.iter().any(|point| {
+            point
+                .iter()
+                .zip(&values)
+                .all(|(a, b)| (a - b).abs() <= PRECISION * scale)
+        }) {
+            let (vertex, _) = get_solution(&values, &variables, &vars_hash_map, is_min)?;
+            alternatives.vertices.push(
+                model
+                    .names
+                    .iter()
+                    .cloned()
+                    .zip(model.values(&vertex))
+                    .collect(),
+            );
+            points.push(values);
+            if alternatives.vertices.len() == limit {
+                break;
+            }
+        }
+        if visited.len() >= MAX_BASES {
+            continue;
+        }
+        let mut in_base = vec![false; width];
+        for j in &basis.heads {
+            in_base[*j] = true;
+        }
+        let y = basis.duals(&costs);
+        for j in (1..width).filter(|j| !in_base[*j] && !is_artificial[*j]) {
+            if (matrix.dot_column(j, &y) - costs[j]).abs() > PRECISION {
+                continue;
+            }
+            let alpha = basis.column(&matrix, j);
+            // Règle de Bland sur les ex aequo : la plus petite colonne sortante
+            let leaving = (0..alpha.len())
+                .filter(|p| alpha[*p] > PRECISION)
+                .map(|p| (p, basis.values[p].max(0.0) / alpha[p]))
+                .min_by(|a, b| {
+                    a.1.total_cmp(&b.1)
+                        .then(basis.heads[a.0].cmp(&basis.heads[b.0]))
+                });
+            let Some((p, _)) = leaving else {
+                alternatives.unbounded_face = true;
+                continue;
+            };
+            let mut next = basis.clone();
+            next.pivot(p, j, &alpha);
+            if visited.insert(key(&next)) {
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(alternatives)
+}
+
+// Pool des size meilleures solutions entières distinctes, par partition de l'espace des
+// solutions (Lawler). Une région est le programme plus des bornes sur les variables
+// entières, son optimum est trouvé par branch and bound. L'optimum de la meilleure région
+// ouverte entre dans le pool, puis la région privée de ce point x* est partagée en
+//   x_1 <= x*_1 - 1,  x_1 >= x*_1 + 1,
+//   x_1 = x*_1, x_2 <= x*_2 - 1,  x_1 = x*_1, x_2 >= x*_2 + 1,  ...
+// régions disjointes qui couvrent toutes les solutions entières différentes de x*, que les
+// variables soient binaires ou entières générales. declarations est ajouté au programme
+// (priorités).
+pub fn solution_pool(
+    model: &LpModel,
+    declarations: &str,
+    options: &BranchAndBoundOptions,
+) -> Result<BranchAndBoundResult, String> {
+    // Sans déclaration int, toutes les variables sont entières
+    let mut integer_model = model.clone();
+    if !integer_model.integers.contains(&true) {
+        integer_model.integers.fill(true);
+    }
+    let upper = integer_model.implied_upper_bounds();
+    let size = options.pool_size;
+    let options = BranchAndBoundOptions {
+        pool_size: 1,
+        ..*options
+    };
+    let lp = model.to_lp_string() + declarations;
+    // Une région sans solution entière est abandonnée, les autres erreurs sont rendues
+    let solve = |region: &str| match simplexef64::branch_and_bound(&(lp.clone() + region), &options)
+    {
+        Ok(result) => Ok(Some(result)),
+        Err(e) if e == NO_INTEGER_SOLUTION || e == INFEASIBLE => Ok(None),
+        Err(e) => Err(e),
+    };
+    let mut open = vec![(simplexef64::branch_and_bound(&lp, &options)?, String::new())];
+    let mut best: Option<BranchAndBoundResult> = None;
+    let mut pool = vec![];
+    while pool.len() < size
+        && let Some(k) = (0..open.len()).max_by(|a, b| {
+            let score = |k: usize| open[k].0.z * open[k].0.is_min;
+            score(*a).total_cmp(&score(*b))
+        })
+    {
+        let (result, region) = open.swap_remove(k);
+        let values = model.values(&result.variables);
+        let mut fixed = region;
+        for (j, _) in integer_model
+            .integers
+            .iter()
+            .enumerate()
+            .filter(|(_, integer)| **integer)
+        {
+            let name = &model.names[j];
+            let v = values[j].round();
+            // Les variables sont positives
+            let mut bounds = vec![];
+            if v >= 1.0 {
+                bounds.push(("<=", v - 1.0));
+            }
+            if v + 1.0 <= upper[j] + PRECISION {
+                bounds.push((">=", v + 1.0));
+            }
+            for (op, rhs) in bounds {
+                let child = format!(
+                    "{}\n{}",
+                    fixed,
+                    format_constraint(&[(name.clone(), 1.0)], op, rhs)
+                );
+                if let Some(child_result) = solve(&child)? {
+                    open.push((child_result, child));
+                }
+            }
+            fixed.push('\n');
+            fixed.push_str(&format_constraint(&[(name.clone(), 1.0)], "=", v));
+        }
+        pool.push(PoolSolution {
+            variables: result.variables.clone(),
+            z: result.z,
+        });
+        best.get_or_insert(result);
+    }
+    let mut result = best.unwrap();
+    result.pool = pool;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simplexef64::{self, BranchAndBoundOptions, PRECISION};
+
+    // Le pool d'un petit programme en entiers généraux est comparé aux k meilleures
+    // solutions trouvées en énumérant tous les points entiers
+    #[test]
+    fn pool_matches_enumeration() {
+        let file = "max: 3x + y + 2z;\nx <= 2;\ny <= 3;\nz <= 2;\nx + y + z <= 4;\n\
+                    x + 2z <= 4;\nint x, y, z;\n";
+        let size = 8;
+        let options = BranchAndBoundOptions {
+            pool_size: size,
+            ..Default::default()
+        };
+        let result = simplexef64::branch_and_bound(file, &options).unwrap();
+        let mut enumerated = vec![];
+        for x in 0..=2 {
+            for y in 0..=3 {
+                for z in 0..=2 {
+                    if x + y + z <= 4 && x + 2 * z <= 4 {
+                        enumerated.push(3 * x + y + 2 * z);
+                    }
+                }
+            }
+        }
+        enumerated.sort_by(|a, b| b.cmp(a));
+        let pool = result.pool.iter().map(|s| s.z).collect::<Vec<_>>();
+        assert_eq!(pool.len(), size);
+        for (z, expected) in pool.iter().zip(&enumerated) {
+            assert!((z - *expected as f64).abs() < PRECISION, "{:?}", pool);
+        }
+        // Solutions distinctes, entières et réalisables
+        let points = result
+            .pool
+            .iter()
+            .map(|s| s.variables.iter().map(|(_, v)| v.round() as i64).collect())
+            .collect::<Vec<Vec<i64>>>();
+        for (k, p) in points.iter().enumerate() {
+            assert!(!points[..k].contains(p));
+            let (x, y, z) = (p[0], p[1], p[2]);
+            assert!(x <= 2 && y <= 3 && z <= 2 && x + y + z <= 4 && x + 2 * z <= 4);
+            assert_eq!(3 * x + y + 2 * z, pool[k].round() as i64);
+        }
+    }
+}
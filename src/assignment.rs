@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::model::{LpModel, Sense};
+
+// Problème d'affectation : coût de chaque couple (agent, tâche), None pour un couple interdit.
+// La matrice peut être rectangulaire, le plus petit côté est alors entièrement affecté.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssignmentProblem {
+    pub costs: Vec<Vec<Option<f64>>>,
+    #[serde(default)]
+    pub maximize: bool,
+    #[serde(default)]
+    pub agents: Vec<String>,
+    #[serde(default)]
+    pub tasks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssignmentSolution {
+    // x_agent_tâche = 1 pour chaque couple affecté, les autres valent 0
+    pub variables: Vec<(String, f64)>,
+    pub z: f64,
+    // Agents ou tâches du plus grand côté restés sans affectation
+    pub unassigned: Vec<String>,
+}
+
+// Affectation de coût minimal rendue par l'algorithme hongrois, avec les potentiels
+// u_i + v_j <= c_ij (égalité sur les couples affectés), duales du programme linéaire
+struct Hungarian {
+    column_of_row: Vec<Option<usize>>,
+    u: Vec<f64>,
+    v: Vec<f64>,
+}
+
+// Lit un problème en JSON ou sous forme de matrice, une ligne par agent, "-" pour un couple
+// interdit, précédée de "max" pour maximiser :
+//   max
+//   7 3 -
+//   2 8 5
+pub fn parse_assignment(input: &str) -> Result<AssignmentProblem, String> {
+    let problem = if input.trim_start().starts_with('{') {
+        serde_json::from_str(input).map_err(|e| format!("JSON invalide : {}", e))?
+    } else {
+        let mut problem = AssignmentProblem {
+            costs: vec![],
+            maximize: false,
+            agents: vec![],
+            tasks: vec![],
+        };
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            match line {
+                "max" => problem.maximize = true,
+                "min" => problem.maximize = false,
+                _ => problem.costs.push(
+                    line.split_whitespace()
+                        .map(|a| match a {
+                            "-" => Ok(None),
+                            _ => a
+                                .parse::<f64>()
+                                .map(Some)
+                                .map_err(|_| format!("Nombre invalide : {}", a)),
+                        })
+                        .collect::<Result<_, _>>()?,
+                ),
+            }
+        }
+        problem
+    };
+    check(&problem)?;
+    Ok(problem)
+}
+
+fn check(problem: &AssignmentProblem) -> Result<(), String> {
+    let rows = problem.costs.len();
+    let columns = problem.costs.first().map_or(0, |row| row.len());
+    if rows == 0 || columns == 0 {
+        return Err("La matrice des coûts est vide".to_string());
+    }
+    if problem.costs.iter().any(|row| row.len() != columns) {
+        return Err("Toutes les lignes de la matrice doivent avoir la même longueur".to_string());
+    }
+    if problem
+        .costs
+        .iter()
+        .flatten()
+        .flatten()
+        .any(|c| !c.is_finite())
+    {
+        return Err("Les coûts doivent être finis".to_string());
+    }
+    if !problem.agents.is_empty() && problem.agents.len() != rows
+        || !problem.tasks.is_empty() && problem.tasks.len() != columns
+    {
+        return Err("Il faut un nom par agent et par tâche".to_string());
+    }
+    Ok(())
+}
+
+// Algorithme hongrois par plus courts chemins augmentants (Jonker-Volgenant), O(n² m) pour
+// n lignes et m >= n colonnes. Les couples interdits ont un coût infini. Une matrice plus
+// haute que large est transposée.
+fn hungarian(costs: &[Vec<f64>]) -> Result<Hungarian, String> {
+    let (n, m) = (costs.len(), costs[0].len());
+    if n > m {
+        let transposed = (0..m)
+            .map(|j| costs.iter().map(|row| row[j]).collect())
+            .collect::<Vec<Vec<f64>>>();
+        let solution = hungarian(&transposed)?;
+        let mut column_of_row = vec![None; n];
+        for (j, i) in solution.column_of_row.iter().enumerate() {
+            if let Some(i) = i {
+                column_of_row[*i] = Some(j);
+            }
+        }
+        return Ok(Hungarian {
+            column_of_row,
+            u: solution.v,
+            v: solution.u,
+        });
+    }
+    // Indices à partir de 1, la colonne 0 est la racine des chemins augmentants
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; m + 1];
+    let mut row_of_column = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+    for i in 1..=n {
+        row_of_column[0] = i;
+        let mut j0 = 0;
+        let mut min_slack = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = row_of_column[j0];
+            let (mut delta, mut j1) = (f64::INFINITY, 0);
+            for j in (1..=m).filter(|j| !used[*j]) {
+                let slack = costs[i0 - 1][j - 1] - u[i0] - v[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = j0;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    j1 = j;
+                }
+            }
+            if !delta.is_finite() {
+                return Err("Aucune affectation complète n'est possible".to_string());
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[row_of_column[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_of_column[j0] == 0 {
+                break;
+            }
+        }
+        // Inversion du chemin augmentant
+        while j0 != 0 {
+            let j1 = way[j0];
+            row_of_column[j0] = row_of_column[j1];
+            j0 = j1;
+        }
+    }
+    let mut column_of_row = vec![None; n];
+    for j in 1..=m {
+        if row_of_column[j] != 0 {
+            column_of_row[row_of_column[j] - 1] = Some(j - 1);
+        }
+    }
+    Ok(Hungarian {
+        column_of_row,
+        u: u[1..].to_vec(),
+        v: v[1..].to_vec(),
+    })
+}
+
+pub fn solve_assignment(problem: &AssignmentProblem) -> Result<AssignmentSolution, String> {
+    check(problem)?;
+    let sign = if problem.maximize { -1.0 } else { 1.0 };
+    let costs = problem
+        .costs
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|c| c.map_or(f64::INFINITY, |c| sign * c))
+                .collect()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    let solution = hungarian(&costs)?;
+    let name =
+        |names: &[String], k: usize| names.get(k).cloned().unwrap_or_else(|| (k + 1).to_string());
+    let mut variables = vec![];
+    let mut z = 0.0;
+    let mut assigned_tasks = vec![false; costs[0].len()];
+    let mut unassigned = vec![];
+    for (i, j) in solution.column_of_row.iter().enumerate() {
+        match j {
+            Some(j) => {
+                let (agent, task) = (name(&problem.agents, i), name(&problem.tasks, *j));
+                variables.push((format!("x_{}_{}", agent, task), 1.0));
+                z += sign * costs[i][*j];
+                assigned_tasks[*j] = true;
+            }
+            None => unassigned.push(name(&problem.agents, i)),
+        }
+    }
+    for (j, assigned) in assigned_tasks.iter().enumerate() {
+        if !assigned {
+            unassigned.push(name(&problem.tasks, j));
+        }
+    }
+    Ok(AssignmentSolution {
+        variables,
+        z,
+        unassigned,
+    })
+}
+
+// Reconnaît un programme d'affectation : lignes de second membre 1 à coefficients 1, chaque
+// variable dans exactement deux lignes de côtés opposés d'un graphe biparti connexe, le plus
+// petit côté en égalités et le plus grand en <= (ou en égalités s'ils ont la même taille).
+// Rend les deux côtés, lignes des agents puis lignes des tâches.
+fn detect(model: &LpModel) -> Option<(Vec<usize>, Vec<usize>)> {
+    if model.rows.is_empty()
+        || model.rows.iter().any(|row| {
+            row.rhs != 1.0 || row.sense == Sense::Geq || row.coeffs.iter().any(|(_, a)| *a != 1.0)
+        })
+    {
+        return None;
+    }
+    let mut rows_of = vec![vec![]; model.names.len()];
+    for (r, row) in model.rows.iter().enumerate() {
+        for (j, _) in &row.coeffs {
+            rows_of[*j].push(r);
+        }
+    }
+    if rows_of.iter().any(|rows| rows.len() != 2) {
+        return None;
+    }
+    let mut neighbours = vec![vec![]; model.rows.len()];
+    for rows in &rows_of {
+        neighbours[rows[0]].push(rows[1]);
+        neighbours[rows[1]].push(rows[0]);
+    }
+    let mut side = vec![usize::MAX; model.rows.len()];
+    side[0] = 0;
+    let mut queue = VecDeque::from([0]);
+    while let Some(r) = queue.pop_front() {
+        for s in &neighbours[r] {
+            if side[*s] == usize::MAX {
+                side[*s] = 1 - side[r];
+                queue.push_back(*s);
+            } else if side[*s] == side[r] {
+                return None;
+            }
+        }
+    }
+    if side.contains(&usize::MAX) {
+        return None;
+    }
+    let mut sides = [vec![], vec![]];
+    for (r, s) in side.iter().enumerate() {
+        sides[*s].push(r);
+    }
+    let [mut agents, mut tasks] = sides;
+    let all_equal = |rows: &[usize]| rows.iter().all(|r| model.rows[*r].sense == Sense::Eq);
+    if agents.len() > tasks.len() || agents.len() == tasks.len() && !all_equal(&agents) {
+        std::mem::swap(&mut agents, &mut tasks);
+    }
+    all_equal(&agents).then_some((agents, tasks))
+}
+
+// Résout un programme d'affectation par l'algorithme hongrois. Rend les valeurs des
+// variables et les duales des lignes, ou None si le modèle n'est pas une affectation.
+pub fn solve_model(model: &LpModel) -> Option<(Vec<f64>, Vec<f64>)> {
+    let (agents, tasks) = detect(model)?;
+    let mut position = vec![0; model.rows.len()];
+    for (k, r) in agents.iter().chain(&tasks).enumerate() {
+        position[*r] = if k < agents.len() {
+            k
+        } else {
+            k - agents.len()
+        };
+    }
+    let is_agent = {
+        let mut is_agent = vec![false; model.rows.len()];
+        for r in &agents {
+            is_agent[*r] = true;
+        }
+        is_agent
+    };
+    // Coût à minimiser de chaque couple, la moins chère des variables parallèles
+    let mut costs = vec![vec![f64::INFINITY; tasks.len()]; agents.len()];
+    let mut variable = vec![vec![usize::MAX; tasks.len()]; agents.len()];
+    let mut rows_of = vec![vec![]; model.names.len()];
+    for (r, row) in model.rows.iter().enumerate() {
+        for (j, _) in &row.coeffs {
+            rows_of[*j].push(r);
+        }
+    }
+    for (j, rows) in rows_of.iter().enumerate() {
+        let (a, t) = if is_agent[rows[0]] {
+            (position[rows[0]], position[rows[1]])
+        } else {
+            (position[rows[1]], position[rows[0]])
+        };
+        let cost = -model.is_min * model.objective[j];
+        if cost < costs[a][t] {
+            costs[a][t] = cost;
+            variable[a][t] = j;
+        }
+    }
+    let solution = hungarian(&costs).ok()?;
+    log::info!(
+        "Assignment: {} x {} solved with the Hungarian algorithm",
+        agents.len(),
+        tasks.len()
+    );
+    let mut values = vec![0.0; model.names.len()];
+    for (a, t) in solution.column_of_row.iter().enumerate() {
+        values[variable[a][t.unwrap()]] = 1.0;
+    }
+    let mut duals = vec![0.0; model.rows.len()];
+    for (a, r) in agents.iter().enumerate() {
+        duals[*r] = -model.is_min * solution.u[a];
+    }
+    for (t, r) in tasks.iter().enumerate() {
+        duals[*r] = -model.is_min * solution.v[t];
+    }
+    Some((values, duals))
+}
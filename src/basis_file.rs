@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{LpModel, Sense};
+use crate::simplexef64::Variable;
+use crate::sparse::CscMatrix;
+
+// Statut d'une variable ou de l'écart d'une ligne. Une ligne hors base est à sa borne
+// supérieure (a x = b d'une ligne <=) ou inférieure, comme dans le format BAS.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BasisStatus {
+    Basic,
+    Lower,
+    Upper,
+}
+
+// Base enregistrée par noms : une variable absente est hors base, une ligne absente est
+// de base. Les lignes sont nommées R1, R2, ... dans l'ordre du fichier.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoredBasis {
+    pub variables: Vec<(String, BasisStatus)>,
+    pub rows: Vec<(String, BasisStatus)>,
+}
+
+fn row_name(i: usize) -> String {
+    format!("R{}", i + 1)
+}
+
+// Ligne de l'unique coefficient des colonnes d'écart et artificielles
+fn logical_rows<'a>(
+    matrix: &'a CscMatrix,
+    variables: &'a [Variable],
+) -> impl Iterator<Item = (usize, &'a Variable)> + 'a {
+    variables
+        .iter()
+        .filter(|var| var.is_slack || var.is_artificial)
+        .filter_map(|var| matrix.column(var.column).next().map(|(i, _)| (i, var)))
+}
+
+impl StoredBasis {
+    // Base des colonnes heads des deux phases du programme model
+    pub(crate) fn from_heads(
+        heads: &[usize],
+        matrix: &CscMatrix,
+        variables: &[Variable],
+        vars_hash_map: &HashMap<String, usize>,
+        model: &LpModel,
+    ) -> StoredBasis {
+        let mut in_base = vec![false; matrix.columns()];
+        for j in heads {
+            in_base[*j] = true;
+        }
+        let mut row_basic = vec![false; matrix.rows];
+        for (i, var) in logical_rows(matrix, variables) {
+            row_basic[i] |= in_base[var.column];
+        }
+        let variables = model
+            .names
+            .iter()
+            .map(|name| {
+                let basic = vars_hash_map
+                    .get(name)
+                    .is_some_and(|index| in_base[variables[*index].column]);
+                let status = if basic {
+                    BasisStatus::Basic
+                } else {
+                    BasisStatus::Lower
+                };
+                (name.clone(), status)
+            })
+            .collect();
+        let rows = model
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let status = match row.sense {
+                    _ if row_basic[i] => BasisStatus::Basic,
+                    Sense::Leq => BasisStatus::Upper,
+                    Sense::Geq | Sense::Eq => BasisStatus::Lower,
+                };
+                (row_name(i), status)
+            })
+            .collect();
+        StoredBasis { variables, rows }
+    }
+
+    // Colonnes de départ des deux phases : les variables de base et, pour chaque ligne de
+    // base, son écart (son artificielle pour une égalité). Les noms inconnus sont ignorés.
+    pub(crate) fn heads(
+        &self,
+        matrix: &CscMatrix,
+        variables: &[Variable],
+        vars_hash_map: &HashMap<String, usize>,
+    ) -> Vec<usize> {
+        let mut heads = self
+            .variables
+            .iter()
+            .filter(|(_, status)| *status == BasisStatus::Basic)
+            .filter_map(|(name, _)| vars_hash_map.get(name))
+            .map(|index| &variables[*index])
+            .filter(|var| !var.is_slack && !var.is_artificial)
+            .map(|var| var.column)
+            .collect::<Vec<_>>();
+        let nonbasic = self
+            .rows
+            .iter()
+            .filter(|(_, status)| *status != BasisStatus::Basic)
+            .map(|(name, _)| name.as_str())
+            .collect::<HashSet<_>>();
+        let mut logicals = vec![None; matrix.rows];
+        for (i, var) in logical_rows(matrix, variables) {
+            if logicals[i].is_none() || var.is_slack {
+                logicals[i] = Some(var.column);
+            }
+        }
+        heads.extend(
+            logicals
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !nonbasic.contains(row_name(*i).as_str()))
+                .filter_map(|(_, j)| *j),
+        );
+        heads
+    }
+
+    // Format BAS (MPS) : XU/XL associe une variable de base à une ligne hors base à sa
+    // borne supérieure/inférieure, les autres statuts sont ceux par défaut
+    pub fn to_bas(&self, name: &str) -> String {
+        let mut bas = format!("NAME          {}\n", name);
+        let basic = self
+            .variables
+            .iter()
+            .filter(|(_, status)| *status == BasisStatus::Basic);
+        let nonbasic = self
+            .rows
+            .iter()
+            .filter(|(_, status)| *status != BasisStatus::Basic);
+        // Une ligne hors base de plus que de variables de base (deux colonnes logiques de
+        // la même ligne dans la base) est écrite de base, ce qui décrit la même base
+        for ((variable, _), (row, status)) in basic.zip(nonbasic) {
+            let code = if *status == BasisStatus::Upper {
+                "XU"
+            } else {
+                "XL"
+            };
+            bas.push_str(&format!(" {} {} {}\n", code, variable, row));
+        }
+        for (variable, status) in &self.variables {
+            if *status == BasisStatus::Upper {
+                bas.push_str(&format!(" UL {}\n", variable));
+            }
+        }
+        bas.push_str("ENDATA\n");
+        bas
+    }
+
+    fn parse_bas(text: &str) -> Result<StoredBasis, String> {
+        let mut basis = StoredBasis::default();
+        for line in text.lines() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields.as_slice() {
+                [] => {}
+                [comment, ..] if comment.starts_with('*') => {}
+                ["NAME", ..] => {}
+                ["ENDATA"] => break,
+                [code @ ("XU" | "XL"), variable, row] => {
+                    let status = if *code == "XU" {
+                        BasisStatus::Upper
+                    } else {
+                        BasisStatus::Lower
+                    };
+                    basis
+                        .variables
+                        .push((variable.to_string(), BasisStatus::Basic));
+                    basis.rows.push((row.to_string(), status));
+                }
+                ["UL", variable] => basis
+                    .variables
+                    .push((variable.to_string(), BasisStatus::Upper)),
+                ["LL", variable] => basis
+                    .variables
+                    .push((variable.to_string(), BasisStatus::Lower)),
+                _ => return Err(format!("Ligne de base invalide : {}", line)),
+            }
+        }
+        Ok(basis)
+    }
+
+    // Lit une base au format JSON ou BAS
+    pub fn parse(text: &str) -> Result<StoredBasis, String> {
+        if text.trim_start().starts_with('{') {
+            serde_json::from_str(text).map_err(|e| format!("Base JSON invalide : {}", e))
+        } else {
+            StoredBasis::parse_bas(text)
+        }
+    }
+}
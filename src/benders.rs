@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::incremental::Session;
+use crate::model::{Row, Sense, name_matches, parse_lp_model};
+use crate::simplexef64::{self, BranchAndBoundOptions, PRECISION, SimplexOptions};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BendersOptions {
+    // Variables du maître séparées par des virgules, "w*" pour toutes celles qui commencent
+    // par w. Les autres variables forment le sous-problème.
+    pub master: String,
+    pub max_iterations: usize,
+    // Écart relatif entre les bornes qui arrête les itérations
+    pub gap: f64,
+}
+
+impl Default for BendersOptions {
+    fn default() -> Self {
+        BendersOptions {
+            master: String::new(),
+            max_iterations: 100,
+            gap: 1e-6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BendersResult {
+    pub variables: Vec<(String, f64)>,
+    pub z: f64,
+    // Borne donnée par le dernier maître
+    pub bound: f64,
+    pub iterations: usize,
+    pub optimality_cuts: usize,
+    pub feasibility_cuts: usize,
+}
+
+// Décomposition de Benders de min c x + d y sous A x + B y (sens) b, x les variables du
+// maître et y celles du sous-problème. Le maître min c x + theta garde les lignes en x
+// seul et reçoit les coupes ; pour x fixé, le sous-problème min d y sous B y (sens) b - A x
+// est résolu depuis sa base précédente. Ses duales u donnent la coupe d'optimalité
+// theta >= u (b - A x), un rayon de Farkas w la coupe de faisabilité w (b - A x) <= 0.
+// Les variables entières du maître sont gardées, le maître est alors résolu par le branch
+// and bound. theta >= 0 demande des coûts d positifs (en minimisation).
+pub fn benders(file: &str, options: &BendersOptions) -> Result<BendersResult, String> {
+    let model = parse_lp_model(file)?;
+    if !model.quadratic.is_empty() || !model.objectives.is_empty() || !model.goals.is_empty() {
+        return Err("Benders demande un programme linéaire à un objectif".to_string());
+    }
+    let spec = options
+        .master
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .collect::<Vec<_>>();
+    let (master_vars, sub_vars): (Vec<usize>, Vec<usize>) =
+        (0..model.names.len()).partition(|j| name_matches(&spec, &model.names[*j]));
+    if master_vars.is_empty() || sub_vars.is_empty() {
+        return Err("Le maître et le sous-problème doivent avoir des variables".to_string());
+    }
+    if sub_vars.iter().any(|j| model.integers[*j]) {
+        return Err("Les variables du sous-problème doivent être continues".to_string());
+    }
+    // Coûts en minimisation
+    let costs = model
+        .objective
+        .iter()
+        .map(|c| -model.is_min * c)
+        .collect::<Vec<_>>();
+    if sub_vars.iter().any(|j| costs[*j] < 0.0) {
+        return Err(
+            "Les coûts du sous-problème doivent être positifs (négatifs en maximisation)"
+                .to_string(),
+        );
+    }
+    let mut in_sub = vec![false; model.names.len()];
+    for j in &sub_vars {
+        in_sub[*j] = true;
+    }
+    let (sub_rows, master_rows): (Vec<usize>, Vec<usize>) =
+        (0..model.rows.len()).partition(|i| model.rows[*i].coeffs.iter().any(|(j, _)| in_sub[*j]));
+
+    let mut master = model.restrict(&master_vars, &master_rows, &costs);
+    let theta = master.fresh_name("theta".to_string());
+    master.index.insert(theta.clone(), master.names.len());
+    master.names.push(theta);
+    master.objective.push(1.0);
+    master.integers.push(false);
+    let theta = master.names.len() - 1;
+    // Le format LP demande au moins une ligne
+    master.rows.push(Row {
+        coeffs: vec![(theta, 1.0)],
+        sense: Sense::Geq,
+        rhs: 0.0,
+    });
+    let mut sub = Session::from_model(model.restrict(&sub_vars, &sub_rows, &costs))?;
+    let position = master_vars
+        .iter()
+        .enumerate()
+        .map(|(k, j)| (*j, k))
+        .collect::<HashMap<_, _>>();
+    // Partie A x de chaque ligne du sous-problème, x indicé dans le maître
+    let links = sub_rows
+        .iter()
+        .map(|i| {
+            model.rows[*i]
+                .coeffs
+                .iter()
+                .filter_map(|(j, a)| position.get(j).map(|k| (*k, *a)))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let integer_master = master.integers.contains(&true);
+
+    let mut result = BendersResult {
+        variables: vec![],
+        z: f64::INFINITY,
+        bound: f64::NEG_INFINITY,
+        iterations: 0,
+        optimality_cuts: 0,
+        feasibility_cuts: 0,
+    };
+    while result.iterations < options.max_iterations {
+        result.iterations += 1;
+        let lp = master.to_lp_string();
+        let solution = if integer_master {
+            simplexef64::branch_and_bound(&lp, &BranchAndBoundOptions::default())?.variables
+        } else {
+            simplexef64::solve_lp(&lp, &SimplexOptions::default())?.variables
+        };
+        let x = master.values(&solution);
+        result.bound = master.objective_value(&x);
+        for (k, i) in sub_rows.iter().enumerate() {
+            let ax = links[k].iter().map(|(j, a)| a * x[*j]).sum::<f64>();
+            sub.set_rhs(k, model.rows[*i].rhs - ax)?;
+        }
+        // Coupe sum_x (w A)_x x + coeff_theta theta >= w b
+        let (weights, theta_coeff) = match sub.solve() {
+            Ok(sub_solution) => {
+                let z = result.bound - x[theta] + sub_solution.z;
+                if z < result.z {
+                    result.z = z;
+                    let mut values = vec![0.0; model.names.len()];
+                    for (k, j) in master_vars.iter().enumerate() {
+                        values[*j] = x[k];
+                    }
+                    for (j, (_, y)) in sub_vars.iter().zip(&sub_solution.variables) {
+                        values[*j] = *y;
+                    }
+                    result.variables = model.names.iter().cloned().zip(values).collect();
+                }
+                // theta atteint la valeur du sous-problème ou les bornes se rejoignent
+                let tolerance = PRECISION * sub_solution.z.abs().max(1.0);
+                if x[theta] >= sub_solution.z - tolerance
+                    || result.z - result.bound <= options.gap * result.z.abs().max(1.0)
+                {
+                    break;
+                }
+                result.optimality_cuts += 1;
+                (sub_solution.duals, 1.0)
+            }
+            Err(e) => {
+                let ray = sub.farkas_ray().ok_or(e)?.to_vec();
+                result.feasibility_cuts += 1;
+                (ray, 0.0)
+            }
+        };
+        let mut coeffs = vec![0.0; master.names.len()];
+        coeffs[theta] = theta_coeff;
+        let mut rhs = 0.0;
+        for (k, i) in sub_rows.iter().enumerate() {
+            rhs += weights[k] * model.rows[*i].rhs;
+            for (j, a) in &links[k] {
+                coeffs[*j] += weights[k] * a;
+            }
+        }
+        master.rows.push(Row {
+            coeffs: coeffs
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| a.abs() > PRECISION)
+                .map(|(j, a)| (j, *a))
+                .collect(),
+            sense: Sense::Geq,
+            rhs,
+        });
+    }
+    if result.variables.is_empty() {
+        return Err("Aucune solution réalisable trouvée par Benders".to_string());
+    }
+    // Valeurs dans le sens du fichier
+    result.z *= -model.is_min;
+    result.bound *= -model.is_min;
+    Ok(result)
+}
@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+use crate::incremental::Session;
+use crate::simplexef64::LpSolution;
+
+// Garde-fou contre un pricing qui proposerait sans fin des colonnes
+const MAX_ITERATIONS: usize = 10_000;
+
+// Colonne proposée par le pricing : coût dans l'objectif et coefficients (ligne, coefficient),
+// les lignes étant numérotées dans l'ordre du programme maître
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub cost: f64,
+    pub coeffs: Vec<(usize, f64)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnGeneration {
+    // Optimum du maître restreint, qui est celui du maître complet
+    pub solution: LpSolution,
+    pub iterations: usize,
+    pub columns: usize,
+}
+
+// Génération de colonnes sur le maître restreint master : pricing reçoit les duales de
+// l'optimum courant (une par ligne) et rend des colonnes. Celles qui améliorent l'optimum
+// sont ajoutées et le maître est résolu à nouveau depuis sa base, les autres sont ignorées.
+// S'arrête quand aucune colonne proposée n'améliore l'optimum.
+pub fn column_generation(
+    master: &mut Session,
+    mut pricing: impl FnMut(&[f64]) -> Vec<Column>,
+) -> Result<ColumnGeneration, String> {
+    let mut solution = master.solve()?;
+    let (mut iterations, mut columns) = (1, 0);
+    loop {
+        let priced = pricing(&solution.duals)
+            .into_iter()
+            .filter(|column| master.prices_out(&solution.duals, column.cost, &column.coeffs))
+            .collect::<Vec<_>>();
+        if priced.is_empty() {
+            break;
+        }
+        if iterations == MAX_ITERATIONS {
+            return Err(format!(
+                "Génération de colonnes arrêtée après {} itérations",
+                iterations
+            ));
+        }
+        for column in &priced {
+            master.add_column(&column.name, column.cost, &column.coeffs)?;
+        }
+        columns += priced.len();
+        solution = master.solve()?;
+        iterations += 1;
+    }
+    Ok(ColumnGeneration {
+        solution,
+        iterations,
+        columns,
+    })
+}
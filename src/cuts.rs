@@ -0,0 +1,309 @@
+use std::collections::HashSet;
+
+use crate::model::{LpModel, Sense, format_constraint};
+use crate::simplexef64::{PRECISION, Variable};
+use crate::sparse::CsrMatrix;
+
+// On ignore les lignes dont la valeur est trop proche d'un entier, les coupes y sont instables
+const MIN_FRACTION: f64 = 0.01;
+// Rapport maximal entre le plus grand et le plus petit coefficient d'une coupe
+const MAX_DYNAMISM: f64 = 1.0e6;
+// Violation minimale du point courant rapportée à la norme de la coupe
+const MIN_EFFICACY: f64 = 1.0e-3;
+// Au-delà, une ligne produirait trop d'arêtes dans le graphe de conflits
+const MAX_CONFLICT_ROW: usize = 200;
+
+// Coupes de Gomory mixtes entières tirées de la base finale de two_phases : tableau_row
+// calcule une ligne de B^-1 [b A] et values donne la valeur de chaque colonne.
+// Les écarts sont substitués grâce à la matrice d'origine pour que la coupe
+// ne porte que sur les variables du fichier LP.
+pub fn gomory_cuts(
+    tableau_row: &dyn Fn(usize) -> Vec<f64>,
+    values: &[f64],
+    original_matrix: &CsrMatrix,
+    variables: &[Variable],
+    names: &[String],
+    is_integer: &dyn Fn(&str) -> bool,
+    max_cuts: usize,
+) -> Vec<String> {
+    let width = original_matrix.columns;
+    if original_matrix.rows() == 0 {
+        return vec![];
+    }
+    let mut by_column = vec![None; width];
+    for (index, var) in variables.iter().enumerate() {
+        by_column[var.column] = Some(index);
+    }
+    // Ligne d'origine et coefficient de chaque écart
+    let mut slack_rows = vec![None; width];
+    for i in 0..original_matrix.rows() {
+        for (column, a) in original_matrix.row(i) {
+            if by_column[column].is_some_and(|v| variables[v].is_slack) {
+                slack_rows[column] = Some((i, a));
+            }
+        }
+    }
+
+    let mut sources = variables
+        .iter()
+        .enumerate()
+        .filter(|(i, v)| v.in_base && !v.is_slack && !v.is_artificial && is_integer(&names[*i]))
+        .filter_map(|(_, v)| {
+            let f0 = values[v.column] - values[v.column].floor();
+            (MIN_FRACTION..=1.0 - MIN_FRACTION)
+                .contains(&f0)
+                .then_some((v.ligne, f0))
+        })
+        .collect::<Vec<_>>();
+    // Les lignes les plus fractionnaires d'abord
+    sources.sort_by(|a, b| {
+        (a.1 - 0.5)
+            .abs()
+            .total_cmp(&(b.1 - 0.5).abs())
+            .then(a.0.cmp(&b.0))
+    });
+
+    let mut cuts = vec![];
+    for (ligne, f0) in sources {
+        if cuts.len() >= max_cuts {
+            break;
+        }
+        let row = tableau_row(ligne);
+        let mut coeffs = vec![0.0; width];
+        let mut rhs = 1.0;
+        for column in 1..width {
+            let Some(v) = by_column[column] else {
+                continue;
+            };
+            let var = variables[v];
+            let a = row[column];
+            if var.in_base || var.is_artificial || a.abs() <= PRECISION {
+                continue;
+            }
+            let alpha = if !var.is_slack && is_integer(&names[v]) {
+                let f = a - a.floor();
+                if f <= f0 {
+                    f / f0
+                } else {
+                    (1.0 - f) / (1.0 - f0)
+                }
+            } else if a >= 0.0 {
+                a / f0
+            } else {
+                -a / (1.0 - f0)
+            };
+            match slack_rows[column] {
+                // s = (b - a.x) / sigma
+                Some((i, sigma)) => {
+                    for (k, a) in original_matrix.row(i) {
+                        let structural = by_column[k]
+                            .is_some_and(|x| !variables[x].is_slack && !variables[x].is_artificial);
+                        if k == 0 {
+                            rhs -= alpha * a / sigma;
+                        } else if structural {
+                            coeffs[k] -= alpha * a / sigma;
+                        }
+                    }
+                }
+                None => coeffs[column] += alpha,
+            }
+        }
+        let terms = coeffs
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.abs() > 1.0e-12)
+            .map(|(k, c)| (k, *c))
+            .collect::<Vec<_>>();
+        if terms.is_empty() {
+            continue;
+        }
+        let (min, max) = terms.iter().fold((f64::MAX, 0.0_f64), |acc, (_, c)| {
+            (acc.0.min(c.abs()), acc.1.max(c.abs()))
+        });
+        if max / min > MAX_DYNAMISM {
+            continue;
+        }
+        // La coupe doit séparer franchement le point courant
+        let activity = terms.iter().map(|(k, c)| c * values[*k]).sum::<f64>();
+        let norm = terms.iter().map(|(_, c)| c * c).sum::<f64>().sqrt();
+        if (rhs - activity) / norm < MIN_EFFICACY {
+            continue;
+        }
+        let terms = terms
+            .iter()
+            .map(|(k, c)| (names[by_column[*k].unwrap()].clone(), *c))
+            .collect::<Vec<_>>();
+        cuts.push(format_constraint(&terms, ">=", rhs));
+    }
+    cuts
+}
+
+// Graphe de conflits entre variables binaires : deux binaires sont reliées
+// si une ligne interdit qu'elles valent 1 en même temps (x + y <= 1 par exemple)
+pub struct ConflictGraph {
+    adjacency: Vec<HashSet<usize>>,
+}
+
+impl ConflictGraph {
+    pub fn new(model: &LpModel, binaries: &[bool]) -> Self {
+        let mut adjacency = vec![HashSet::new(); model.names.len()];
+        for row in &model.rows {
+            if row.sense == Sense::Geq
+                || row.coeffs.len() > MAX_CONFLICT_ROW
+                || row.coeffs.iter().any(|(j, a)| !binaries[*j] || *a < 0.0)
+            {
+                continue;
+            }
+            for (i, (j, a)) in row.coeffs.iter().enumerate() {
+                for (k, b) in &row.coeffs[i + 1..] {
+                    if a + b > row.rhs + PRECISION {
+                        adjacency[*j].insert(*k);
+                        adjacency[*k].insert(*j);
+                    }
+                }
+            }
+        }
+        ConflictGraph { adjacency }
+    }
+
+    pub fn edges(&self) -> usize {
+        self.adjacency.iter().map(|x| x.len()).sum::<usize>() / 2
+    }
+
+    // Cliques maximales violées par le point courant : sum x <= 1
+    pub fn clique_cuts(&self, model: &LpModel, values: &[f64], max_cuts: usize) -> Vec<String> {
+        let mut candidates = (0..values.len())
+            .filter(|j| values[*j] > PRECISION && !self.adjacency[*j].is_empty())
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| values[*b].total_cmp(&values[*a]).then(a.cmp(b)));
+        let mut seen = HashSet::new();
+        let mut cuts = vec![];
+        for start in &candidates {
+            if cuts.len() >= max_cuts {
+                break;
+            }
+            let mut clique = vec![*start];
+            let is_compatible =
+                |clique: &[usize], k: usize| clique.iter().all(|j| self.adjacency[*j].contains(&k));
+            // Les variables fractionnaires d'abord, puis n'importe quel voisin pour la maximalité
+            for k in &candidates {
+                if !clique.contains(k) && is_compatible(&clique, *k) {
+                    clique.push(*k);
+                }
+            }
+            if clique.iter().map(|j| values[*j]).sum::<f64>() <= 1.0 + MIN_EFFICACY {
+                continue;
+            }
+            let mut neighbours = self.adjacency[*start].iter().copied().collect::<Vec<_>>();
+            neighbours.sort();
+            for k in neighbours {
+                if !clique.contains(&k) && is_compatible(&clique, k) {
+                    clique.push(k);
+                }
+            }
+            clique.sort();
+            if !seen.insert(clique.clone()) {
+                continue;
+            }
+            let terms = clique
+                .iter()
+                .map(|j| (model.names[*j].clone(), 1.0))
+                .collect::<Vec<_>>();
+            cuts.push(format_constraint(&terms, "<=", 1.0));
+        }
+        cuts
+    }
+}
+
+// Couvertures étendues des lignes sac-à-dos sur des binaires. Les variables de
+// coefficient négatif sont complémentées (x' = 1 - x) pour se ramener à a >= 0.
+pub fn cover_cuts(
+    model: &LpModel,
+    binaries: &[bool],
+    values: &[f64],
+    max_cuts: usize,
+) -> Vec<String> {
+    let mut cuts = vec![];
+    for row in &model.rows {
+        if cuts.len() >= max_cuts {
+            break;
+        }
+        if row.sense != Sense::Leq
+            || row.coeffs.len() < 2
+            || row.coeffs.iter().any(|(j, _)| !binaries[*j])
+        {
+            continue;
+        }
+        // (variable, |a|, complémentée, valeur de x')
+        let mut items = row
+            .coeffs
+            .iter()
+            .filter(|(_, a)| *a != 0.0)
+            .map(|(j, a)| {
+                if *a < 0.0 {
+                    (*j, -a, true, 1.0 - values[*j])
+                } else {
+                    (*j, *a, false, values[*j])
+                }
+            })
+            .collect::<Vec<_>>();
+        let capacity = row.rhs
+            - row
+                .coeffs
+                .iter()
+                .filter(|(_, a)| *a < 0.0)
+                .map(|(_, a)| a)
+                .sum::<f64>();
+        if items.iter().map(|x| x.1).sum::<f64>() <= capacity + PRECISION {
+            continue;
+        }
+        // Couverture gloutonne : on prend d'abord les variables proches de 1
+        items.sort_by(|a, b| {
+            ((1.0 - a.3) / a.1)
+                .total_cmp(&((1.0 - b.3) / b.1))
+                .then(a.0.cmp(&b.0))
+        });
+        let mut cover = vec![];
+        let mut weight = 0.0;
+        for item in &items {
+            if weight > capacity + PRECISION {
+                break;
+            }
+            weight += item.1;
+            cover.push(*item);
+        }
+        // Couverture minimale : on retire les variables de plus petite valeur tant qu'elle reste une couverture
+        cover.sort_by(|a, b| a.3.total_cmp(&b.3).then(a.0.cmp(&b.0)));
+        let mut i = 0;
+        while i < cover.len() {
+            if weight - cover[i].1 > capacity + PRECISION {
+                weight -= cover[i].1;
+                cover.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        // Extension : toute variable plus lourde que la couverture peut y être ajoutée
+        let heaviest = cover.iter().map(|x| x.1).fold(0.0, f64::max);
+        let size = cover.len() as f64;
+        let extended = items
+            .iter()
+            .filter(|x| cover.iter().any(|c| c.0 == x.0) || x.1 >= heaviest)
+            .collect::<Vec<_>>();
+        if extended.iter().map(|x| x.3).sum::<f64>() <= size - 1.0 + MIN_EFFICACY {
+            continue;
+        }
+        let complemented = extended.iter().filter(|x| x.2).count() as f64;
+        let mut terms = extended
+            .iter()
+            .map(|x| (x.0, if x.2 { -1.0 } else { 1.0 }))
+            .collect::<Vec<_>>();
+        terms.sort_by_key(|x| x.0);
+        let terms = terms
+            .iter()
+            .map(|(j, a)| (model.names[*j].clone(), *a))
+            .collect::<Vec<_>>();
+        cuts.push(format_constraint(&terms, "<=", size - 1.0 - complemented));
+    }
+    cuts
+}
@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+
+use crate::column_generation::{Column, column_generation};
+use crate::incremental::Session;
+use crate::model::format_constraint;
+use crate::simplexef64::{self, BranchAndBoundOptions, PRECISION};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Item {
+    pub width: f64,
+    pub demand: f64,
+}
+
+// Découpe de rouleaux de largeur width en articles de largeurs entières
+#[derive(Debug, Clone, Deserialize)]
+pub struct CuttingStockProblem {
+    pub width: f64,
+    pub items: Vec<Item>,
+}
+
+// Découpage d'un rouleau : nombre d'articles de chaque largeur, et nombre de rouleaux
+// découpés ainsi
+#[derive(Debug, Clone, Serialize)]
+pub struct Pattern {
+    pub cuts: Vec<usize>,
+    pub rolls: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CuttingStockSolution {
+    // Nombre de rouleaux de la relaxation continue, borne inférieure
+    pub bound: f64,
+    pub rolls: f64,
+    pub patterns: Vec<Pattern>,
+    pub iterations: usize,
+    pub columns: usize,
+}
+
+// Format texte :
+//   width: 100
+//   45 97
+//   36 610
+// une ligne "largeur demande" par article
+pub fn parse_cutting_stock(input: &str) -> Result<CuttingStockProblem, String> {
+    let problem = if input.trim_start().starts_with('{') {
+        serde_json::from_str(input).map_err(|e| format!("JSON invalide : {}", e))?
+    } else {
+        parse_text(input)?
+    };
+    check(&problem)?;
+    Ok(problem)
+}
+
+fn parse_text(input: &str) -> Result<CuttingStockProblem, String> {
+    let mut problem = CuttingStockProblem {
+        width: 0.0,
+        items: vec![],
+    };
+    let number = |a: &str| {
+        a.parse::<f64>()
+            .map_err(|_| format!("Nombre invalide : {}", a))
+    };
+    for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(rest) = line.strip_prefix("width:") {
+            problem.width = number(rest.trim())?;
+            continue;
+        }
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [width, demand] => problem.items.push(Item {
+                width: number(width)?,
+                demand: number(demand)?,
+            }),
+            _ => return Err(format!("Ligne inattendue : {}", line)),
+        }
+    }
+    Ok(problem)
+}
+
+fn check(problem: &CuttingStockProblem) -> Result<(), String> {
+    if problem.items.is_empty() {
+        return Err("Il faut au moins un article".to_string());
+    }
+    let mut widths = std::iter::once(problem.width).chain(problem.items.iter().map(|i| i.width));
+    if widths.any(|w| w <= 0.0 || w.fract() != 0.0) {
+        return Err("Les largeurs doivent être des entiers positifs".to_string());
+    }
+    if let Some(item) = problem.items.iter().find(|i| i.width > problem.width) {
+        return Err(format!(
+            "L'article de largeur {} est plus large que le rouleau",
+            item.width
+        ));
+    }
+    if problem.items.iter().any(|i| i.demand < 0.0) {
+        return Err("Les demandes doivent être positives".to_string());
+    }
+    Ok(())
+}
+
+// Sac à dos entier non borné du pricing : découpage de plus grande valeur sum y_i a_i sous
+// sum w_i a_i <= capacity, par programmation dynamique sur la largeur
+fn best_pattern(widths: &[usize], capacity: usize, values: &[f64]) -> (f64, Vec<usize>) {
+    let mut best = vec![0.0; capacity + 1];
+    // Dernier article découpé à chaque largeur, None pour une chute d'une unité
+    let mut last = vec![None; capacity + 1];
+    for c in 1..=capacity {
+        best[c] = best[c - 1];
+        for (i, w) in widths.iter().enumerate().filter(|(_, w)| **w <= c) {
+            if best[c - w] + values[i] > best[c] {
+                best[c] = best[c - w] + values[i];
+                last[c] = Some(i);
+            }
+        }
+    }
+    let mut cuts = vec![0; widths.len()];
+    let mut c = capacity;
+    while c > 0 {
+        match last[c] {
+            Some(i) => {
+                cuts[i] += 1;
+                c -= widths[i];
+            }
+            None => c -= 1,
+        }
+    }
+    (best[capacity], cuts)
+}
+
+fn column(name: String, cuts: &[usize]) -> Column {
+    Column {
+        name,
+        cost: 1.0,
+        coeffs: cuts
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| **a > 0)
+            .map(|(i, a)| (i, *a as f64))
+            .collect(),
+    }
+}
+
+// Modèle de Gilmore-Gomory : min sum x_p sous sum_p a_ip x_p >= d_i, les découpages p étant
+// générés par le sac à dos du pricing depuis les découpages homogènes. Les découpages
+// générés sont ensuite repris en entiers par le branch and bound.
+pub fn solve_cutting_stock(problem: &CuttingStockProblem) -> Result<CuttingStockSolution, String> {
+    let capacity = problem.width as usize;
+    let widths = problem
+        .items
+        .iter()
+        .map(|item| item.width as usize)
+        .collect::<Vec<_>>();
+    let mut patterns = widths
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            let mut cuts = vec![0; widths.len()];
+            cuts[i] = capacity / w;
+            cuts
+        })
+        .collect::<Vec<_>>();
+    let mut lp = format!(
+        "min: {};\n",
+        (0..patterns.len())
+            .map(|k| format!("+1 p{}", k))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    for (i, item) in problem.items.iter().enumerate() {
+        let coeffs = [(format!("p{}", i), patterns[i][i] as f64)];
+        lp.push_str(&format_constraint(&coeffs, ">=", item.demand));
+        lp.push('\n');
+    }
+    let mut master = Session::new(&lp)?;
+    let generation = column_generation(&mut master, |duals| {
+        let (value, cuts) = best_pattern(&widths, capacity, duals);
+        // Coût réduit 1 - y a du découpage
+        if value <= 1.0 + PRECISION {
+            return vec![];
+        }
+        patterns.push(cuts);
+        vec![column(
+            format!("p{}", patterns.len() - 1),
+            &patterns[patterns.len() - 1],
+        )]
+    })?;
+    let integers = (0..patterns.len())
+        .map(|k| format!("p{}", k))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let lp = format!("{}\nint {};\n", master.to_lp_string(), integers);
+    let result = simplexef64::branch_and_bound(&lp, &BranchAndBoundOptions::default())?;
+    let patterns = result
+        .variables
+        .iter()
+        .filter(|(_, rolls)| *rolls > 0.5)
+        .filter_map(|(name, rolls)| {
+            let k = name.strip_prefix('p')?.parse::<usize>().ok()?;
+            Some(Pattern {
+                cuts: patterns[k].clone(),
+                rolls: rolls.round(),
+            })
+        })
+        .collect();
+    Ok(CuttingStockSolution {
+        bound: generation.solution.z,
+        rolls: result.z.round(),
+        patterns,
+        iterations: generation.iterations,
+        columns: generation.columns,
+    })
+}
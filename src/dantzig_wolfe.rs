@@ -0,0 +1,503 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::column_generation::{Column, column_generation};
+use crate::incremental::Session;
+use crate::model::{LpModel, Row, Sense, name_matches, parse_lp_model};
+use crate::simplexef64::PRECISION;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DantzigWolfeOptions {
+    // Blocs séparés par des points-virgules, chacun une liste de noms séparés par des
+    // virgules ("x_cl0*;x_cl1*"). Vide : les blocs sont détectés dans le programme.
+    pub blocks: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DantzigWolfeResult {
+    pub variables: Vec<(String, f64)>,
+    pub z: f64,
+    pub blocks: usize,
+    pub linking_rows: usize,
+    pub iterations: usize,
+    pub columns: usize,
+}
+
+// Sous-problème d'un bloc : ses variables dans l'ordre du modèle, la session de ses lignes
+// propres et les points extrêmes trouvés par le pricing
+struct Block {
+    vars: Vec<usize>,
+    session: Session,
+    points: Vec<Vec<f64>>,
+}
+
+// Bloc de chaque variable, None pour une variable qui reste dans le maître
+type Assignment = Vec<Option<usize>>;
+
+// Composantes connexes des variables reliées par les lignes non liantes. Une variable
+// d'aucune de ces lignes reste dans le maître.
+fn components(model: &LpModel, linking: &[bool]) -> (Assignment, usize) {
+    let mut parent = (0..model.names.len()).collect::<Vec<_>>();
+    fn find(parent: &mut [usize], mut j: usize) -> usize {
+        while parent[j] != j {
+            parent[j] = parent[parent[j]];
+            j = parent[j];
+        }
+        j
+    }
+    let mut in_row = vec![false; model.names.len()];
+    for (row, _) in model.rows.iter().zip(linking).filter(|(_, l)| !**l) {
+        for (j, _) in &row.coeffs {
+            in_row[*j] = true;
+            let (a, b) = (find(&mut parent, row.coeffs[0].0), find(&mut parent, *j));
+            parent[a] = b;
+        }
+    }
+    let mut numbers = HashMap::new();
+    let assignment = (0..model.names.len())
+        .map(|j| {
+            in_row[j].then(|| {
+                let root = find(&mut parent, j);
+                let count = numbers.len();
+                *numbers.entry(root).or_insert(count)
+            })
+        })
+        .collect();
+    (assignment, numbers.len())
+}
+
+// Nombre de morceaux contenant au moins une ligne que laisse la suppression de chaque ligne
+// non liante dans sa composante : 1 si la ligne ne relie pas deux parties séparées, 0 si
+// elle est seule. Les lignes qui séparent leur composante sont les points d'articulation
+// du graphe biparti variables-lignes, trouvés par un parcours en profondeur (Tarjan).
+fn split_counts(model: &LpModel, linking: &[bool]) -> Vec<usize> {
+    let n = model.names.len();
+    let mut var_rows = vec![vec![]; n];
+    for (i, row) in model.rows.iter().enumerate().filter(|(i, _)| !linking[*i]) {
+        for (j, _) in &row.coeffs {
+            var_rows[*j].push(n + i);
+        }
+    }
+    // Noeuds : variables 0..n puis lignes n..n + m
+    let neighbour = |v: usize, k: usize| {
+        if v < n {
+            var_rows[v].get(k).copied()
+        } else {
+            model.rows[v - n].coeffs.get(k).map(|(j, _)| *j)
+        }
+    };
+    let nodes = n + model.rows.len();
+    let mut discovery = vec![usize::MAX; nodes];
+    let mut low = vec![0; nodes];
+    let mut parent = vec![usize::MAX; nodes];
+    // Lignes du sous-arbre de chaque noeud, et des sous-arbres séparés par chaque ligne
+    let mut subtree_rows = vec![0; nodes];
+    let mut split_rows = vec![0; nodes];
+    let mut pieces = vec![0; model.rows.len()];
+    let mut time = 0;
+    for root in (0..model.rows.len())
+        .filter(|i| !linking[*i])
+        .map(|i| n + i)
+    {
+        if discovery[root] != usize::MAX {
+            continue;
+        }
+        let mut component = vec![];
+        let mut stack = vec![(root, 0)];
+        discovery[root] = time;
+        low[root] = time;
+        time += 1;
+        while let Some((v, k)) = stack.last_mut() {
+            let v = *v;
+            match neighbour(v, *k) {
+                Some(w) => {
+                    *k += 1;
+                    if discovery[w] == usize::MAX {
+                        parent[w] = v;
+                        discovery[w] = time;
+                        low[w] = time;
+                        time += 1;
+                        stack.push((w, 0));
+                    } else if w != parent[v] {
+                        low[v] = low[v].min(discovery[w]);
+                    }
+                }
+                None => {
+                    stack.pop();
+                    if v >= n {
+                        subtree_rows[v] += 1;
+                        component.push(v);
+                    }
+                    let p = parent[v];
+                    if p == usize::MAX {
+                        continue;
+                    }
+                    low[p] = low[p].min(low[v]);
+                    subtree_rows[p] += subtree_rows[v];
+                    if p >= n && low[v] >= discovery[p] && subtree_rows[v] > 0 {
+                        split_rows[p] += subtree_rows[v];
+                        pieces[p - n] += 1;
+                    }
+                }
+            }
+        }
+        // Reste de la composante, du côté du parent de la ligne
+        let rows = subtree_rows[root];
+        for v in component {
+            if rows - 1 - split_rows[v] > 0 {
+                pieces[v - n] += 1;
+            }
+        }
+    }
+    pieces
+}
+
+// Retire une à une comme lignes liantes celles qui relient des parties autrement séparées
+// (la ligne qui sépare sa composante en le plus de morceaux, la plus longue quand aucune ne
+// la sépare), jusqu'à ce qu'il reste au moins deux blocs
+fn greedy_linking(model: &LpModel) -> Option<Vec<bool>> {
+    let mut linking = vec![false; model.rows.len()];
+    loop {
+        if components(model, &linking).1 >= 2 {
+            return Some(linking);
+        }
+        let pieces = split_counts(model, &linking);
+        let row = (0..model.rows.len())
+            .filter(|i| !linking[*i])
+            .max_by_key(|i| (pieces[*i], model.rows[*i].coeffs.len()))?;
+        linking[row] = true;
+    }
+}
+
+// Lignes liantes candidates : celles du retrait glouton, aucune ligne, et chaque classe de
+// lignes de même nombre de coefficients (une famille de contraintes qui lie les blocs tous
+// ensemble, sans qu'aucune de ses lignes ne les sépare seule). Le découpage en le plus de
+// blocs est gardé, à égalité celui qui a le moins de lignes liantes.
+fn detect_blocks(model: &LpModel) -> Result<Assignment, String> {
+    let sizes = model
+        .rows
+        .iter()
+        .map(|row| row.coeffs.len())
+        .collect::<BTreeSet<_>>();
+    let classes = std::iter::once(None)
+        .chain(sizes.into_iter().map(Some))
+        .map(|size| {
+            model
+                .rows
+                .iter()
+                .map(|row| Some(row.coeffs.len()) == size)
+                .collect()
+        });
+    let mut best: Option<(usize, usize, Assignment)> = None;
+    for linking in greedy_linking(model).into_iter().chain(classes) {
+        let count = linking.iter().filter(|l| **l).count();
+        let (assignment, blocks) = components(model, &linking);
+        let better = match &best {
+            None => true,
+            Some((best_blocks, best_count, _)) => {
+                blocks > *best_blocks || (blocks == *best_blocks && count < *best_count)
+            }
+        };
+        if better {
+            best = Some((blocks, count, assignment));
+        }
+    }
+    match best {
+        Some((blocks, _, assignment)) if blocks >= 2 => Ok(assignment),
+        _ => Err("Aucune structure en blocs trouvée dans le programme".to_string()),
+    }
+}
+
+fn annotated_blocks(model: &LpModel, spec: &str) -> Result<Assignment, String> {
+    let groups = spec
+        .split(';')
+        .map(|group| {
+            group
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|group| !group.is_empty())
+        .collect::<Vec<_>>();
+    let assignment = model
+        .names
+        .iter()
+        .map(|name| groups.iter().position(|group| name_matches(group, name)))
+        .collect::<Vec<_>>();
+    if let Some(k) = (0..groups.len()).find(|k| !assignment.contains(&Some(*k))) {
+        return Err(format!(
+            "Le bloc {} ne contient aucune variable",
+            groups[k].join(",")
+        ));
+    }
+    Ok(assignment)
+}
+
+// Résout en parallèle le sous-problème de chaque bloc pour les coûts réduits c - pi A,
+// pi les duales des lignes liantes, et rend le point extrême optimal de chaque bloc
+fn price(
+    model: &LpModel,
+    blocks: &mut [Block],
+    costs: &[f64],
+    linking_columns: &[Vec<(usize, f64)>],
+    pi: &[f64],
+) -> Result<Vec<Vec<f64>>, String> {
+    blocks
+        .par_iter_mut()
+        .enumerate()
+        .map(|(k, block)| {
+            for j in &block.vars {
+                let reduced = costs[*j]
+                    - linking_columns[*j]
+                        .iter()
+                        .map(|(l, a)| pi[*l] * a)
+                        .sum::<f64>();
+                block.session.set_objective(&model.names[*j], reduced)?;
+            }
+            let solution = block
+                .session
+                .solve()
+                .map_err(|e| format!("Bloc {} : {}", k, e))?;
+            Ok(solution.variables.iter().map(|(_, x)| *x).collect())
+        })
+        .collect()
+}
+
+// Colonne du point extrême x du bloc k : son coût c x, ses coefficients A x sur les lignes
+// liantes et 1 sur la ligne de convexité du bloc
+fn point_column(
+    name: String,
+    block: &Block,
+    k: usize,
+    x: &[f64],
+    costs: &[f64],
+    linking_columns: &[Vec<(usize, f64)>],
+    linking_rows: usize,
+) -> Column {
+    let mut coeffs = vec![0.0; linking_rows];
+    let mut cost = 0.0;
+    for (j, value) in block
+        .vars
+        .iter()
+        .zip(x)
+        .filter(|(_, v)| v.abs() > PRECISION)
+    {
+        cost += costs[*j] * value;
+        for (l, a) in &linking_columns[*j] {
+            coeffs[*l] += a * value;
+        }
+    }
+    let mut coeffs = coeffs
+        .into_iter()
+        .enumerate()
+        .filter(|(_, a)| a.abs() > PRECISION)
+        .collect::<Vec<_>>();
+    coeffs.push((linking_rows + k, 1.0));
+    Column { name, cost, coeffs }
+}
+
+// Décomposition de Dantzig-Wolfe de la relaxation continue d'un programme à blocs liés par
+// quelques lignes : le maître choisit une combinaison convexe sum_p lambda_kp x_kp des
+// points extrêmes de chaque bloc sous les lignes liantes, et le pricing de chaque bloc,
+// min (c_k - pi A_k) x_k sous ses lignes propres, donne la colonne d'un nouveau point
+// extrême tant que son coût réduit moins la duale mu_k de la convexité est négatif. Des
+// colonnes d'écart de coût élevé rendent le maître réalisable dès le départ.
+pub fn dantzig_wolfe(
+    file: &str,
+    options: &DantzigWolfeOptions,
+) -> Result<DantzigWolfeResult, String> {
+    let model = parse_lp_model(file)?;
+    if !model.quadratic.is_empty() || !model.objectives.is_empty() || !model.goals.is_empty() {
+        return Err("Dantzig-Wolfe demande un programme linéaire à un objectif".to_string());
+    }
+    let assignment = if options.blocks.trim().is_empty() {
+        detect_blocks(&model)?
+    } else {
+        annotated_blocks(&model, &options.blocks)?
+    };
+    let count = assignment.iter().flatten().max().map_or(0, |k| k + 1);
+    // Une ligne dont toutes les variables sont du même bloc est une ligne propre de ce bloc
+    let row_block = model
+        .rows
+        .iter()
+        .map(|row| {
+            let mut blocks = row.coeffs.iter().map(|(j, _)| assignment[*j]);
+            let first = blocks.next().flatten();
+            blocks.all(|k| k == first).then_some(first).flatten()
+        })
+        .collect::<Vec<_>>();
+    let linking = (0..model.rows.len())
+        .filter(|i| row_block[*i].is_none())
+        .collect::<Vec<_>>();
+    // Coûts en minimisation
+    let costs = model
+        .objective
+        .iter()
+        .map(|c| -model.is_min * c)
+        .collect::<Vec<_>>();
+    let mut linking_columns = vec![vec![]; model.names.len()];
+    for (l, i) in linking.iter().enumerate() {
+        for (j, a) in &model.rows[*i].coeffs {
+            linking_columns[*j].push((l, *a));
+        }
+    }
+    let mut blocks = (0..count)
+        .map(|k| {
+            let vars = (0..model.names.len())
+                .filter(|j| assignment[*j] == Some(k))
+                .collect::<Vec<_>>();
+            let rows = (0..model.rows.len())
+                .filter(|i| row_block[*i] == Some(k))
+                .collect::<Vec<_>>();
+            if rows.is_empty() {
+                return Err(format!("Le bloc {} n'a aucune ligne propre", k));
+            }
+            Ok(Block {
+                session: Session::from_model(model.restrict(&vars, &rows, &costs))?,
+                vars,
+                points: vec![],
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    // Maître : variables hors blocs, lignes liantes puis lignes de convexité
+    let master_vars = (0..model.names.len())
+        .filter(|j| assignment[*j].is_none())
+        .collect::<Vec<_>>();
+    let mut master = model.restrict(&master_vars, &linking, &costs);
+    master.rows.extend((0..count).map(|_| Row {
+        coeffs: vec![],
+        sense: Sense::Eq,
+        rhs: 1.0,
+    }));
+    // Les colonnes des points et des écarts ont ce préfixe, absent des noms du modèle
+    let mut prefix = "dw".to_string();
+    while model.names.iter().any(|name| name.starts_with(&prefix)) {
+        prefix.insert(0, '_');
+    }
+    let penalty = 1e6 * costs.iter().fold(1.0, |m, c| f64::max(m, c.abs()));
+    let mut penalties = vec![];
+    for (l, i) in linking.iter().enumerate() {
+        let signs: &[f64] = match model.rows[*i].sense {
+            Sense::Leq => &[-1.0],
+            Sense::Geq => &[1.0],
+            Sense::Eq => &[1.0, -1.0],
+        };
+        for sign in signs {
+            let name = format!("{}_penalty{}", prefix, master.names.len());
+            master.index.insert(name.clone(), master.names.len());
+            master.names.push(name.clone());
+            master.objective.push(penalty);
+            master.integers.push(false);
+            master.rows[l].coeffs.push((master.names.len() - 1, *sign));
+            penalties.push(name);
+        }
+    }
+    let mut master = Session::from_model(master)?;
+
+    // Points de départ : optimum de chaque bloc pour les coûts d'origine
+    let mut points = HashMap::new();
+    let first = price(
+        &model,
+        &mut blocks,
+        &costs,
+        &linking_columns,
+        &vec![0.0; linking.len()],
+    )?;
+    for (k, x) in first.into_iter().enumerate() {
+        let name = format!("{}{}_0", prefix, k);
+        let column = point_column(
+            name.clone(),
+            &blocks[k],
+            k,
+            &x,
+            &costs,
+            &linking_columns,
+            linking.len(),
+        );
+        master.add_column(&name, column.cost, &column.coeffs)?;
+        points.insert(name, (k, 0));
+        blocks[k].points.push(x);
+    }
+
+    let mut error = None;
+    let generation = column_generation(&mut master, |duals| {
+        let x = match price(
+            &model,
+            &mut blocks,
+            &costs,
+            &linking_columns,
+            &duals[..linking.len()],
+        ) {
+            Ok(x) => x,
+            Err(e) => {
+                error = Some(e);
+                return vec![];
+            }
+        };
+        x.into_iter()
+            .enumerate()
+            .map(|(k, x)| {
+                let p = blocks[k].points.len();
+                let name = format!("{}{}_{}", prefix, k, p);
+                let column = point_column(
+                    name.clone(),
+                    &blocks[k],
+                    k,
+                    &x,
+                    &costs,
+                    &linking_columns,
+                    linking.len(),
+                );
+                points.insert(name, (k, p));
+                blocks[k].points.push(x);
+                column
+            })
+            .collect()
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+    let generation = generation?;
+
+    let mut values = vec![0.0; model.names.len()];
+    for (name, value) in &generation.solution.variables {
+        if penalties.contains(name) && *value > PRECISION {
+            return Err("Les lignes liantes ne peuvent pas être satisfaites".to_string());
+        }
+        if let Some((k, p)) = points.get(name) {
+            for (j, x) in blocks[*k].vars.iter().zip(&blocks[*k].points[*p]) {
+                values[*j] += value * x;
+            }
+        } else if let Some(j) = model.index.get(name) {
+            values[*j] = *value;
+        }
+    }
+    Ok(DantzigWolfeResult {
+        z: model.objective_value(&values),
+        variables: model.names.iter().cloned().zip(values).collect(),
+        blocks: count,
+        linking_rows: linking.len(),
+        iterations: generation.iterations,
+        columns: generation.columns + count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // La ligne liante x1 + 2 y1 a autant de coefficients que les lignes des blocs
+    #[test]
+    fn detects_linking_rows_of_block_row_length() {
+        let file = "max: 3x1 + 2x2 + 4y1 + y2;\nx1 + x2 + y1 + y2 <= 10;\nx1 + 2y1 <= 8;\n\
+                    x1 + x2 <= 6;\n2x1 + x2 <= 8;\ny1 + y2 <= 5;\ny1 - y2 <= 1;\n";
+        let result = dantzig_wolfe(file, &DantzigWolfeOptions::default()).unwrap();
+        assert_eq!(result.blocks, 2);
+        assert_eq!(result.linking_rows, 2);
+        assert!((result.z - 26.25).abs() < 1e-6);
+    }
+}
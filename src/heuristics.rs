@@ -0,0 +1,418 @@
+use crate::model::{LpModel, Sense, format_constraint};
+use crate::simplexef64::{BranchAndBoundOptions, PRECISION, Solver, branch_and_bound, solve_node};
+
+// Nombre maximal de déplacements de l'arrondi avec décalage
+const MAX_SHIFTS: usize = 1000;
+// Nombre maximal de variables fixées par une plongée
+const MAX_DIVE_DEPTH: usize = 100;
+const PUMP_ITERATIONS: usize = 30;
+// Variables basculées quand la pompe de faisabilité cycle
+const PUMP_FLIPS: usize = 10;
+// RINS n'est lancé que si au moins cette part des entières est fixée
+const RINS_MIN_FIXED: f64 = 0.3;
+
+// Activité de chaque ligne du modèle pour des valeurs données
+fn activities(model: &LpModel, values: &[f64]) -> Vec<f64> {
+    model
+        .rows
+        .iter()
+        .map(|row| row.coeffs.iter().map(|(j, a)| a * values[*j]).sum())
+        .collect()
+}
+
+// Violation d'une ligne, positive si l'activité dépasse le second membre
+#[inline(always)]
+fn violation(sense: Sense, activity: f64, rhs: f64) -> f64 {
+    let tolerance = PRECISION * (1.0 + rhs.abs());
+    let excess = match sense {
+        Sense::Leq => (activity - rhs).max(0.0),
+        Sense::Geq => (rhs - activity).max(0.0),
+        Sense::Eq => (activity - rhs).abs(),
+    };
+    if excess <= tolerance { 0.0 } else { excess }
+}
+
+#[inline(always)]
+fn is_integral(value: f64) -> bool {
+    (value - value.round()).abs() <= PRECISION
+}
+
+// Solution du modèle d'origine : lignes, positivité et intégralité
+pub fn is_feasible(model: &LpModel, values: &[f64]) -> bool {
+    values.iter().all(|x| *x >= -PRECISION)
+        && values
+            .iter()
+            .zip(&model.integers)
+            .all(|(x, integer)| !integer || is_integral(*x))
+        && model
+            .rows
+            .iter()
+            .zip(activities(model, values))
+            .all(|(row, activity)| violation(row.sense, activity, row.rhs) == 0.0)
+}
+
+// Arrondit les entières et remet les valeurs au format (nom, valeur) du branch and bound
+pub fn to_solution(model: &LpModel, values: &[f64]) -> Vec<(String, f64)> {
+    model
+        .names
+        .iter()
+        .zip(values)
+        .zip(&model.integers)
+        .map(|((name, x), integer)| (name.clone(), if *integer { x.round() } else { *x }))
+        .collect()
+}
+
+// Valeurs d'une solution LP, None si le programme n'a pas de solution
+fn solve_values(model: &LpModel, lp: &str) -> Option<Vec<f64>> {
+    solve_node(lp).map(|solution| model.values(&solution.vars_string))
+}
+
+// Remplace la fonction objectif d'un programme (tout ce qui précède le premier ';')
+fn with_objective(lp: &str, objective: &str) -> String {
+    match lp.find(';') {
+        Some(end) => format!("{}{}", objective, &lp[end + 1..]),
+        None => lp.to_string(),
+    }
+}
+
+// Arrondi simple : une variable fractionnaire est arrondie dans le sens
+// où aucune ligne ne peut être violée (verrous du modèle)
+pub fn simple_rounding(model: &LpModel, values: &[f64]) -> Option<Vec<f64>> {
+    let mut down_locks = vec![0; values.len()];
+    let mut up_locks = vec![0; values.len()];
+    for row in &model.rows {
+        for (j, a) in &row.coeffs {
+            match row.sense {
+                Sense::Leq if *a > 0.0 => up_locks[*j] += 1,
+                Sense::Leq => down_locks[*j] += 1,
+                Sense::Geq if *a > 0.0 => down_locks[*j] += 1,
+                Sense::Geq => up_locks[*j] += 1,
+                Sense::Eq => {
+                    down_locks[*j] += 1;
+                    up_locks[*j] += 1;
+                }
+            }
+        }
+    }
+    let mut rounded = values.to_vec();
+    for (j, x) in rounded.iter_mut().enumerate() {
+        if !model.integers[j] || is_integral(*x) {
+            continue;
+        }
+        if down_locks[j] == 0 {
+            *x = x.floor();
+        } else if up_locks[j] == 0 {
+            *x = x.ceil();
+        } else {
+            return None;
+        }
+    }
+    is_feasible(model, &rounded).then_some(rounded)
+}
+
+// Arrondi avec décalage : on arrondit au plus proche puis on répare les lignes violées
+// en déplaçant la variable qui diminue le plus la violation totale
+pub fn shifting(model: &LpModel, values: &[f64]) -> Option<Vec<f64>> {
+    let mut columns = vec![vec![]; values.len()];
+    for (i, row) in model.rows.iter().enumerate() {
+        for (j, a) in &row.coeffs {
+            columns[*j].push((i, *a));
+        }
+    }
+    let mut x = values
+        .iter()
+        .zip(&model.integers)
+        .map(|(v, integer)| if *integer { v.round() } else { *v })
+        .collect::<Vec<_>>();
+    let mut activity = activities(model, &x);
+    let total_violation = |activity: &[f64]| {
+        model
+            .rows
+            .iter()
+            .zip(activity)
+            .map(|(row, a)| violation(row.sense, *a, row.rhs))
+            .sum::<f64>()
+    };
+    let mut current = total_violation(&activity);
+    for _ in 0..MAX_SHIFTS {
+        if current == 0.0 {
+            return is_feasible(model, &x).then_some(x);
+        }
+        // Ligne la plus violée
+        let (i, excess) = model
+            .rows
+            .iter()
+            .zip(&activity)
+            .map(|(row, a)| violation(row.sense, *a, row.rhs))
+            .enumerate()
+            .fold(
+                (0, 0.0),
+                |best, (i, v)| if v > best.1 { (i, v) } else { best },
+            );
+        let row = &model.rows[i];
+        let decrease = activity[i] > row.rhs;
+        let mut best: Option<(usize, f64, f64)> = None;
+        for (j, a) in &row.coeffs {
+            // Sens du déplacement de x_j qui rapproche l'activité du second membre
+            let up = (*a > 0.0) != decrease;
+            let mut delta = excess / a.abs();
+            if model.integers[*j] {
+                delta = delta.ceil();
+            }
+            if !up {
+                delta = -delta.min(x[*j]);
+            }
+            if delta.abs() <= PRECISION {
+                continue;
+            }
+            let mut shifted = activity.clone();
+            for (k, b) in &columns[*j] {
+                shifted[*k] += b * delta;
+            }
+            let score = total_violation(&shifted);
+            if best.is_none_or(|(_, _, s)| score < s) {
+                best = Some((*j, delta, score));
+            }
+        }
+        match best {
+            Some((j, delta, score)) if score < current => {
+                x[j] += delta;
+                for (k, b) in &columns[j] {
+                    activity[*k] += b * delta;
+                }
+                current = score;
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+// Résout le programme d'origine avec les entières fixées pour ajuster les continues
+fn fix_and_solve(model: &LpModel, lp: &str, values: &[f64]) -> Option<Vec<f64>> {
+    if model.integers.iter().all(|x| *x) {
+        return is_feasible(model, values).then(|| values.to_vec());
+    }
+    let mut fixed = lp.to_string();
+    for (j, name) in model.names.iter().enumerate() {
+        if model.integers[j] {
+            fixed.push_str(&format!("\n{} = {};", name, values[j].round()));
+        }
+    }
+    solve_values(model, &fixed)
+        .filter(|x| is_feasible(model, x))
+        .or_else(|| is_feasible(model, values).then(|| values.to_vec()))
+}
+
+// Plongée : on fixe une variable fractionnaire à la fois et on résout à nouveau la relaxation.
+// Plongée fractionnaire sans guide (variable la moins fractionnaire arrondie au plus proche),
+// plongée guidée vers l'incumbent sinon. Un seul retour arrière par plongée.
+pub fn dive(
+    model: &LpModel,
+    lp: &str,
+    values: &[f64],
+    guide: Option<&[f64]>,
+    cutoff: Option<f64>,
+) -> Option<Vec<f64>> {
+    let mut lp = lp.to_string();
+    let mut x = values.to_vec();
+    let mut backtracked = false;
+    for _ in 0..MAX_DIVE_DEPTH {
+        if let Some(rounded) = simple_rounding(model, &x) {
+            return Some(rounded);
+        }
+        let candidates = (0..x.len())
+            .filter(|j| model.integers[*j] && !is_integral(x[*j]))
+            .collect::<Vec<_>>();
+        // (distance au guide, variable, valeur visée)
+        let Some((_, j, target)) = candidates
+            .iter()
+            .map(|j| {
+                let target = match guide {
+                    Some(guide) => guide[*j].round(),
+                    None => x[*j].round(),
+                };
+                let target = target.clamp(x[*j].floor(), x[*j].ceil());
+                ((x[*j] - target).abs(), *j, target)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+        else {
+            return fix_and_solve(model, &lp, &x);
+        };
+        let bound = |target: f64| {
+            let op = if target > x[j] { ">=" } else { "<=" };
+            format!("\n{} {} {};", model.names[j], op, target)
+        };
+        let mut next = solve_values(model, &format!("{}{}", lp, bound(target)));
+        let mut target = target;
+        if next.is_none() && !backtracked {
+            backtracked = true;
+            target = if target > x[j] {
+                x[j].floor()
+            } else {
+                x[j].ceil()
+            };
+            next = solve_values(model, &format!("{}{}", lp, bound(target)));
+        }
+        let next = next?;
+        // La plongée ne peut plus améliorer l'incumbent
+        if let Some(cutoff) = cutoff
+            && model.is_min * (model.objective_value(&next) - cutoff) <= PRECISION
+        {
+            return None;
+        }
+        lp.push_str(&bound(target));
+        x = next;
+    }
+    None
+}
+
+// Générateur pseudo-aléatoire déterministe pour les perturbations de la pompe
+struct XorShift(u64);
+
+impl XorShift {
+    // Valeur uniforme dans [0, 1)
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// Arrondi décalé d'une unité vers le haut ou vers le bas, en restant positif
+#[inline(always)]
+fn flip(value: f64, up: bool) -> f64 {
+    if up {
+        value + 1.0
+    } else {
+        (value - 1.0).max(0.0)
+    }
+}
+
+// Pompe de faisabilité : on alterne l'arrondi de la solution LP et la projection
+// de l'arrondi sur le polyèdre (minimisation de la distance L1 aux entières)
+pub fn feasibility_pump(model: &LpModel, lp: &str, values: &[f64]) -> Option<Vec<f64>> {
+    let integers = (0..values.len())
+        .filter(|j| model.integers[*j])
+        .collect::<Vec<_>>();
+    if integers.is_empty() {
+        return None;
+    }
+    let mut x = values.to_vec();
+    let mut rounded = x.iter().map(|v| v.round().max(0.0)).collect::<Vec<_>>();
+    let mut history = vec![rounded.clone()];
+    let mut random = XorShift(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..PUMP_ITERATIONS {
+        if integers.iter().all(|j| is_integral(x[*j])) {
+            return fix_and_solve(model, lp, &x);
+        }
+        // Distance |x - arrondi| : x si l'arrondi vaut 0, variable auxiliaire sinon
+        let mut distance = "min:".to_string();
+        let mut constraints = String::new();
+        for j in &integers {
+            let name = &model.names[*j];
+            if rounded[*j] == 0.0 {
+                distance.push_str(&format!(" +1 {}", name));
+            } else {
+                let aux = format!("fp_distance_{}", name);
+                distance.push_str(&format!(" +1 {}", aux));
+                constraints.push_str(&format!(
+                    "\n{} - {} >= {};\n{} + {} >= {};",
+                    aux, name, -rounded[*j], aux, name, rounded[*j]
+                ));
+            }
+        }
+        distance.push(';');
+        x = solve_values(model, &(with_objective(lp, &distance) + &constraints))?;
+        let mut next = x.iter().map(|v| v.round().max(0.0)).collect::<Vec<_>>();
+        if integers.iter().all(|j| next[*j] == rounded[*j]) {
+            // Cycle de longueur 1 : on bascule les variables les plus éloignées de leur arrondi
+            let mut farthest = integers.clone();
+            farthest.sort_by(|a, b| {
+                (x[*b] - next[*b])
+                    .abs()
+                    .total_cmp(&(x[*a] - next[*a]).abs())
+                    .then(a.cmp(b))
+            });
+            for j in farthest.into_iter().take(PUMP_FLIPS) {
+                if (x[j] - next[j]).abs() <= PRECISION {
+                    break;
+                }
+                next[j] = flip(next[j], x[j] > next[j]);
+            }
+        } else if history.contains(&next) {
+            // Cycle plus long : perturbation pseudo-aléatoire (Fischetti, Glover et Lodi)
+            for j in &integers {
+                let rho = random.next() - 0.3;
+                if (x[*j] - next[*j]).abs() + rho.max(0.0) > 0.5 {
+                    next[*j] = flip(next[*j], x[*j] > next[*j] || next[*j] == 0.0);
+                }
+            }
+        }
+        history.push(next.clone());
+        rounded = next;
+    }
+    None
+}
+
+// RINS : les entières égales dans l'incumbent et la relaxation du noeud sont fixées,
+// le sous-problème restant est résolu par un branch and bound limité qui doit améliorer l'incumbent
+pub fn rins(
+    model: &LpModel,
+    lp: &str,
+    incumbent: &[f64],
+    values: &[f64],
+    options: &BranchAndBoundOptions,
+) -> Option<Vec<f64>> {
+    let integers = (0..values.len())
+        .filter(|j| model.integers[*j])
+        .collect::<Vec<_>>();
+    let fixed = integers
+        .iter()
+        .filter(|j| (incumbent[**j] - values[**j]).abs() <= PRECISION)
+        .collect::<Vec<_>>();
+    if fixed.len() == integers.len()
+        || (fixed.len() as f64) < RINS_MIN_FIXED * integers.len() as f64
+    {
+        return None;
+    }
+    let mut sub_lp = lp.to_string();
+    for j in fixed {
+        sub_lp.push_str(&format!(
+            "\n{} = {};",
+            model.names[*j],
+            incumbent[*j].round()
+        ));
+    }
+    let terms = model
+        .names
+        .iter()
+        .zip(&model.objective)
+        .filter(|(_, c)| **c != 0.0)
+        .map(|(name, c)| (name.clone(), *c))
+        .collect::<Vec<_>>();
+    if !terms.is_empty() {
+        let z = model.objective_value(incumbent);
+        let improvement = options.abs_gap.max(PRECISION * (1.0 + z.abs()));
+        let (op, rhs) = if model.is_min < 0.0 {
+            ("<=", z - improvement)
+        } else {
+            (">=", z + improvement)
+        };
+        sub_lp.push('\n');
+        sub_lp.push_str(&format_constraint(&terms, op, rhs));
+    }
+    let sub_options = BranchAndBoundOptions {
+        max_nodes: options.rins_nodes,
+        cut_rounds: 0,
+        node_cuts: false,
+        heuristics: false,
+        presolve: false,
+        solver: Solver::Simplex,
+        ..*options
+    };
+    let result = branch_and_bound(&sub_lp, &sub_options).ok()?;
+    let x = model.values(&result.variables);
+    is_feasible(model, &x).then_some(x)
+}
@@ -0,0 +1,376 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::model::{LpModel, Row, Sense, parse_lp_model};
+use crate::simplexef64::{
+    LpSolution, PRECISION, Start, Status, Variable, check_constraints, farkas_ray, two_phases,
+};
+use crate::sparse::CscMatrix;
+
+// Colonne des deux phases repérée par ce qu'elle représente, pour retrouver la base
+// d'un optimum dans la matrice du programme modifié
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Head {
+    Variable(usize),
+    Slack(usize),
+    Artificial(usize),
+}
+
+// Modification d'une session, les lignes sont numérotées dans l'ordre du fichier
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    Objective {
+        variable: String,
+        coeff: f64,
+    },
+    Rhs {
+        row: usize,
+        rhs: f64,
+    },
+    Bound {
+        variable: String,
+        sense: Sense,
+        value: f64,
+    },
+    AddRow {
+        coeffs: Vec<(String, f64)>,
+        sense: Sense,
+        rhs: f64,
+    },
+    RemoveRow {
+        row: usize,
+    },
+    AddColumn {
+        variable: String,
+        cost: f64,
+        coeffs: Vec<(usize, f64)>,
+    },
+}
+
+// Programme des deux phases construit depuis le modèle, sans relire de fichier. Les
+// colonnes 1..=n sont les variables du modèle, suivies de l'écart et de l'artificielle
+// de chaque ligne. Les lignes de second membre négatif sont multipliées par -1.
+struct Problem {
+    matrix: CscMatrix,
+    variables: Vec<Variable>,
+    logicals: Vec<usize>,
+    costs: Vec<f64>,
+    columns: HashMap<Head, usize>,
+}
+
+// Programme résolu qui garde sa base optimale : après une modification, le programme est
+// résolu à nouveau depuis cette base, par le simplexe primal si elle reste réalisable
+// (objectif, colonne ajoutée) ou par le simplexe dual si elle reste duale réalisable
+// (second membre, ligne ajoutée). Les déclarations int sont ignorées.
+#[derive(Debug, Clone)]
+pub struct Session {
+    model: LpModel,
+    heads: Vec<Head>,
+    // Rayon de Farkas de la dernière résolution infaisable, par ligne du modèle
+    ray: Option<Vec<f64>>,
+}
+
+impl Session {
+    pub fn new(file: &str) -> Result<Session, String> {
+        Session::from_model(parse_lp_model(file)?)
+    }
+
+    pub fn from_model(model: LpModel) -> Result<Session, String> {
+        if !model.quadratic.is_empty() {
+            return Err("Une session demande un programme linéaire".to_string());
+        }
+        if !model.objectives.is_empty() || !model.goals.is_empty() {
+            return Err("Une session demande un seul objectif".to_string());
+        }
+        Ok(Session {
+            model,
+            heads: vec![],
+            ray: None,
+        })
+    }
+
+    fn variable(&self, name: &str) -> Result<usize, String> {
+        self.model
+            .index
+            .get(name)
+            .copied()
+            .ok_or(format!("Variable inconnue : {}", name))
+    }
+
+    fn check_row(&self, row: usize) -> Result<(), String> {
+        if row >= self.model.rows.len() {
+            return Err(format!("Ligne inconnue : {}", row));
+        }
+        Ok(())
+    }
+
+    pub fn set_objective(&mut self, name: &str, coeff: f64) -> Result<(), String> {
+        let j = self.variable(name)?;
+        self.model.objective[j] = coeff;
+        Ok(())
+    }
+
+    pub fn set_rhs(&mut self, row: usize, rhs: f64) -> Result<(), String> {
+        self.check_row(row)?;
+        self.model.rows[row].rhs = rhs;
+        Ok(())
+    }
+
+    // Borne x <= value ou x >= value : la ligne de borne de la variable est modifiée,
+    // ajoutée si elle n'existe pas
+    pub fn set_bound(&mut self, name: &str, sense: Sense, value: f64) -> Result<(), String> {
+        let j = self.variable(name)?;
+        let bound = self
+            .model
+            .rows
+            .iter_mut()
+            .find(|row| row.sense == sense && row.coeffs == [(j, 1.0)]);
+        match bound {
+            Some(row) => row.rhs = value,
+            None => self.model.rows.push(Row {
+                coeffs: vec![(j, 1.0)],
+                sense,
+                rhs: value,
+            }),
+        }
+        Ok(())
+    }
+
+    // Rend le numéro de la ligne ajoutée
+    pub fn add_row(
+        &mut self,
+        coeffs: &[(String, f64)],
+        sense: Sense,
+        rhs: f64,
+    ) -> Result<usize, String> {
+        let mut row = Row {
+            coeffs: vec![],
+            sense,
+            rhs,
+        };
+        for (name, a) in coeffs {
+            let j = self.variable(name)?;
+            match row.coeffs.iter_mut().find(|(k, _)| *k == j) {
+                Some((_, b)) => *b += a,
+                None => row.coeffs.push((j, *a)),
+            }
+        }
+        self.model.rows.push(row);
+        Ok(self.model.rows.len() - 1)
+    }
+
+    // Les lignes suivantes sont renumérotées
+    pub fn remove_row(&mut self, row: usize) -> Result<(), String> {
+        self.check_row(row)?;
+        self.model.rows.remove(row);
+        let shift = |i: usize| (i != row).then_some(if i > row { i - 1 } else { i });
+        self.heads = self
+            .heads
+            .iter()
+            .filter_map(|head| match *head {
+                Head::Variable(j) => Some(Head::Variable(j)),
+                Head::Slack(i) => shift(i).map(Head::Slack),
+                Head::Artificial(i) => shift(i).map(Head::Artificial),
+            })
+            .collect();
+        Ok(())
+    }
+
+    // Nouvelle variable de coût cost et de coefficients (ligne, coefficient)
+    pub fn add_column(
+        &mut self,
+        name: &str,
+        cost: f64,
+        coeffs: &[(usize, f64)],
+    ) -> Result<(), String> {
+        if self.model.index.contains_key(name) {
+            return Err(format!("Variable déjà présente : {}", name));
+        }
+        for (row, _) in coeffs {
+            self.check_row(*row)?;
+        }
+        let j = self.model.names.len();
+        self.model.index.insert(name.to_string(), j);
+        self.model.names.push(name.to_string());
+        self.model.objective.push(cost);
+        self.model.integers.push(false);
+        for (row, a) in coeffs {
+            self.model.rows[*row].coeffs.push((j, *a));
+        }
+        Ok(())
+    }
+
+    // Vrai si une colonne de coût cost et de coefficients coeffs améliore l'optimum de duales
+    // duals : coût réduit c - y a négatif en minimisation, positif en maximisation
+    pub fn prices_out(&self, duals: &[f64], cost: f64, coeffs: &[(usize, f64)]) -> bool {
+        let reduced = cost - coeffs.iter().map(|(i, a)| duals[*i] * a).sum::<f64>();
+        self.model.is_min * reduced > PRECISION
+    }
+
+    // Après une résolution infaisable, w par ligne tel que w a_j <= 0 pour toute variable
+    // (et tout écart, w_i <= 0 sur une ligne <= et >= 0 sur une ligne >=) et w b > 0
+    pub fn farkas_ray(&self) -> Option<&[f64]> {
+        self.ray.as_deref()
+    }
+
+    // Programme courant au format du fichier LP
+    pub fn to_lp_string(&self) -> String {
+        self.model.to_lp_string()
+    }
+
+    pub fn apply(&mut self, change: &Change) -> Result<(), String> {
+        match change {
+            Change::Objective { variable, coeff } => self.set_objective(variable, *coeff),
+            Change::Rhs { row, rhs } => self.set_rhs(*row, *rhs),
+            Change::Bound {
+                variable,
+                sense,
+                value,
+            } => self.set_bound(variable, *sense, *value),
+            Change::AddRow { coeffs, sense, rhs } => self.add_row(coeffs, *sense, *rhs).map(|_| ()),
+            Change::RemoveRow { row } => self.remove_row(*row),
+            Change::AddColumn {
+                variable,
+                cost,
+                coeffs,
+            } => self.add_column(variable, *cost, coeffs),
+        }
+    }
+
+    fn problem(&self) -> Problem {
+        let model = &self.model;
+        let mut width = model.names.len() + 1;
+        let mut columns = HashMap::new();
+        let mut costs = vec![0.0; width];
+        let mut variables = vec![];
+        for (j, c) in model.objective.iter().enumerate() {
+            columns.insert(Head::Variable(j), j + 1);
+            costs[j + 1] = model.is_min * c;
+            variables.push(Variable {
+                in_base: false,
+                cout_original: costs[j + 1],
+                ligne: usize::MAX,
+                column: j + 1,
+                is_slack: false,
+                is_artificial: false,
+            });
+        }
+        let mut logicals = vec![];
+        let mut rows = vec![];
+        for (i, row) in model.rows.iter().enumerate() {
+            let flip = if row.rhs < 0.0 { -1.0 } else { 1.0 };
+            let mut entries = vec![(0, flip * row.rhs)];
+            entries.extend(row.coeffs.iter().map(|(j, a)| (j + 1, flip * a)));
+            let sense = match (row.sense, flip < 0.0) {
+                (Sense::Leq, true) => Sense::Geq,
+                (Sense::Geq, true) => Sense::Leq,
+                (sense, _) => sense,
+            };
+            let mut logical = |head, coeff, is_slack, entries: &mut Vec<(usize, f64)>| {
+                entries.push((width, coeff));
+                columns.insert(head, width);
+                variables.push(Variable {
+                    in_base: coeff > 0.0,
+                    cout_original: 0.0,
+                    ligne: if coeff > 0.0 { i } else { usize::MAX },
+                    column: width,
+                    is_slack,
+                    is_artificial: !is_slack,
+                });
+                width += 1;
+                width - 1
+            };
+            let column = match sense {
+                Sense::Leq => logical(Head::Slack(i), 1.0, true, &mut entries),
+                Sense::Geq => {
+                    logical(Head::Slack(i), -1.0, true, &mut entries);
+                    logical(Head::Artificial(i), 1.0, false, &mut entries)
+                }
+                Sense::Eq => logical(Head::Artificial(i), 1.0, false, &mut entries),
+            };
+            logicals.push(column);
+            rows.push(entries);
+        }
+        costs.resize(width, 0.0);
+        Problem {
+            matrix: CscMatrix::from_rows(&rows, width),
+            variables,
+            logicals,
+            costs,
+            columns,
+        }
+    }
+
+    // Résout le programme depuis la base de l'optimum précédent, la première résolution
+    // part de la base des colonnes logiques
+    pub fn solve(&mut self) -> Result<LpSolution, String> {
+        let problem = self.problem();
+        let heads = self
+            .heads
+            .iter()
+            .filter_map(|head| problem.columns.get(head).copied())
+            .collect::<Vec<_>>();
+        let start = if heads.is_empty() {
+            Start::Logical
+        } else {
+            Start::Basis(&heads)
+        };
+        let (status, basis) = two_phases(
+            &problem.matrix,
+            &problem.variables,
+            &problem.logicals,
+            &problem.costs,
+            start,
+        );
+        self.ray = None;
+        if status == Status::Infeasible
+            || !check_constraints(&problem.matrix, &basis, &problem.variables)
+        {
+            // Les lignes de second membre négatif ont été multipliées par -1
+            self.ray = farkas_ray(&problem.matrix, &problem.variables, &basis).map(|ray| {
+                ray.iter()
+                    .zip(&self.model.rows)
+                    .map(|(w, row)| if row.rhs < 0.0 { -w } else { *w })
+                    .collect()
+            });
+            return Err("Le problème est infaisable".to_string());
+        }
+        if status == Status::Unbounded {
+            return Err("Le problème est non borné".to_string());
+        }
+        let mut keys = HashMap::new();
+        for (head, column) in &problem.columns {
+            keys.insert(*column, *head);
+        }
+        self.heads = basis.heads.iter().map(|j| keys[j]).collect();
+        let values = basis.column_values(problem.matrix.columns());
+        let values = values[1..=self.model.names.len()].to_vec();
+        let duals = basis
+            .duals(&problem.costs)
+            .iter()
+            .zip(&self.model.rows)
+            .map(|(dual, row)| {
+                // Les lignes de second membre négatif ont été multipliées par -1
+                let flip = if row.rhs < 0.0 { -1.0 } else { 1.0 };
+                flip * self.model.is_min * dual
+            })
+            .collect();
+        Ok(LpSolution {
+            variables: self
+                .model
+                .names
+                .iter()
+                .cloned()
+                .zip(values.iter().copied())
+                .collect(),
+            z: self.model.objective_value(&values),
+            duals,
+            objectives: vec![],
+            goals: vec![],
+            alternatives: None,
+            presolve: None,
+            basis: None,
+        })
+    }
+}
@@ -0,0 +1,251 @@
+use crate::sparse::{CscMatrix, NormalEquations};
+
+// Résidus relatifs et écart de dualité relatif à atteindre
+const TOLERANCE: f64 = 1.0e-8;
+// Faute de mieux, le meilleur itéré est rendu s'il atteint cette précision : le crossover
+// et le simplexe terminent la résolution
+const ACCEPTABLE: f64 = 1.0e-6;
+const DEGRADATION: f64 = 1.0e3;
+const MAX_ITERATIONS: usize = 100;
+// Fraction du pas maximal vers le bord de l'orthant
+const STEP_FRACTION: f64 = 0.99;
+// Au-delà, les itérés divergent : problème infaisable ou non borné
+const DIVERGENCE: f64 = 1.0e15;
+
+// Point optimal rendu par la méthode de points intérieurs, x est indicé par colonne
+// de la matrice [b A] (la colonne 0 et les colonnes ignorées valent 0)
+pub struct InteriorPoint {
+    pub x: Vec<f64>,
+    pub iterations: usize,
+}
+
+// A x, x indicé par colonne
+fn product(matrix: &CscMatrix, x: &[f64], used: &[bool]) -> Vec<f64> {
+    let mut ax = vec![0.0; matrix.rows];
+    for j in (1..matrix.columns()).filter(|j| used[*j] && x[*j] != 0.0) {
+        for (i, a) in matrix.column(j) {
+            ax[i] += a * x[j];
+        }
+    }
+    ax
+}
+
+// A^T y, indicé par colonne
+fn transpose_product(matrix: &CscMatrix, y: &[f64], used: &[bool]) -> Vec<f64> {
+    (0..matrix.columns())
+        .map(|j| {
+            if j > 0 && used[j] {
+                matrix.dot_column(j, y)
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+fn norm(x: &[f64]) -> f64 {
+    x.iter().map(|a| a * a).sum::<f64>().sqrt()
+}
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+// Plus grand pas qui garde v + pas * dv >= 0
+fn max_step(v: &[f64], dv: &[f64], used: &[bool]) -> f64 {
+    v.iter()
+        .zip(dv)
+        .zip(used)
+        .filter(|((_, d), used)| **used && **d < 0.0)
+        .map(|((v, d), _)| -v / d)
+        .fold(f64::INFINITY, f64::min)
+}
+
+// Direction de Newton pour A dx = rp, A^T dy + dz = rd, Z dx + X dz = rxz :
+// A D A^T dy = rp + A (D rd - Z^-1 rxz), D = X Z^-1
+struct Newton<'a> {
+    matrix: &'a CscMatrix,
+    used: &'a [bool],
+    normal: &'a NormalEquations<'a>,
+    x: &'a [f64],
+    z: &'a [f64],
+}
+
+impl Newton<'_> {
+    fn direction(&self, rp: &[f64], rd: &[f64], rxz: &[f64]) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let v = (0..self.x.len())
+            .map(|j| {
+                if self.used[j] {
+                    (self.x[j] * rd[j] - rxz[j]) / self.z[j]
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut dy = product(self.matrix, &v, self.used);
+        for (r, p) in dy.iter_mut().zip(rp) {
+            *r += p;
+        }
+        self.normal.solve(&mut dy);
+        let aty = transpose_product(self.matrix, &dy, self.used);
+        let dz = (0..self.x.len())
+            .map(|j| if self.used[j] { rd[j] - aty[j] } else { 0.0 })
+            .collect::<Vec<_>>();
+        let dx = (0..self.x.len())
+            .map(|j| {
+                if self.used[j] {
+                    (rxz[j] - self.x[j] * dz[j]) / self.z[j]
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+        (dx, dy, dz)
+    }
+}
+
+// Prédicteur-correcteur de Mehrotra pour max c x, A x = b, x >= 0 sur les colonnes used
+// de la matrice [b A]. Les équations normales sont résolues par Cholesky creux.
+pub fn solve(matrix: &CscMatrix, costs: &[f64], used: &[bool]) -> Result<InteriorPoint, String> {
+    let width = matrix.columns();
+    let b = matrix.dense_column(0);
+    // On minimise -c x
+    let c = (0..width)
+        .map(|j| if used[j] && j > 0 { -costs[j] } else { 0.0 })
+        .collect::<Vec<_>>();
+    let used = (0..width).map(|j| j > 0 && used[j]).collect::<Vec<_>>();
+    let n = used.iter().filter(|u| **u).count() as f64;
+    let mut normal = NormalEquations::new(matrix, &used);
+
+    // Point de départ de Mehrotra : moindres carrés, puis décalage dans l'orthant
+    let ones = used
+        .iter()
+        .map(|u| if *u { 1.0 } else { 0.0 })
+        .collect::<Vec<_>>();
+    normal.factorize(&ones);
+    let mut w = b.clone();
+    normal.solve(&mut w);
+    let mut x = transpose_product(matrix, &w, &used);
+    let mut y = product(matrix, &c, &used);
+    normal.solve(&mut y);
+    let aty = transpose_product(matrix, &y, &used);
+    let mut z = (0..width)
+        .map(|j| if used[j] { c[j] - aty[j] } else { 0.0 })
+        .collect::<Vec<_>>();
+    let shift = |v: &mut [f64]| {
+        let min = v
+            .iter()
+            .zip(&used)
+            .filter(|(_, u)| **u)
+            .map(|(a, _)| *a)
+            .fold(f64::INFINITY, f64::min);
+        let delta = (-1.5 * min).max(0.0);
+        for (a, u) in v.iter_mut().zip(&used) {
+            if *u {
+                *a += delta;
+            }
+        }
+    };
+    shift(&mut x);
+    shift(&mut z);
+    let xz = dot(&x, &z).max(1.0);
+    let (sum_x, sum_z) = (
+        x.iter().sum::<f64>().max(1.0),
+        z.iter().sum::<f64>().max(1.0),
+    );
+    for j in (0..width).filter(|j| used[*j]) {
+        x[j] += 0.5 * xz / sum_z;
+        z[j] += 0.5 * xz / sum_x;
+    }
+
+    let (norm_b, norm_c) = (1.0 + norm(&b), 1.0 + norm(&c));
+    let mut best: Option<(f64, InteriorPoint)> = None;
+    for iteration in 0..MAX_ITERATIONS {
+        let ax = product(matrix, &x, &used);
+        let rp = b.iter().zip(&ax).map(|(b, a)| b - a).collect::<Vec<_>>();
+        let aty = transpose_product(matrix, &y, &used);
+        let rd = (0..width)
+            .map(|j| if used[j] { c[j] - aty[j] - z[j] } else { 0.0 })
+            .collect::<Vec<_>>();
+        let primal = dot(&c, &x);
+        let dual = dot(&b, &y);
+        let error = (norm(&rp) / norm_b)
+            .max(norm(&rd) / norm_c)
+            .max((primal - dual).abs() / (1.0 + primal.abs()));
+        if error < TOLERANCE {
+            return Ok(InteriorPoint {
+                x,
+                iterations: iteration,
+            });
+        }
+        // Les itérés se dégradent quand les équations normales deviennent trop mal conditionnées
+        if best
+            .as_ref()
+            .is_some_and(|(best, _)| error > DEGRADATION * best)
+        {
+            break;
+        }
+        if best.as_ref().is_none_or(|(best, _)| error < *best) {
+            let point = InteriorPoint {
+                x: x.clone(),
+                iterations: iteration,
+            };
+            best = Some((error, point));
+        }
+        if x.iter()
+            .chain(&y)
+            .any(|v| !v.is_finite() || v.abs() > DIVERGENCE)
+        {
+            break;
+        }
+        let d = (0..width)
+            .map(|j| if used[j] { x[j] / z[j] } else { 0.0 })
+            .collect::<Vec<_>>();
+        normal.factorize(&d);
+        let newton = Newton {
+            matrix,
+            used: &used,
+            normal: &normal,
+            x: &x,
+            z: &z,
+        };
+        let mu = dot(&x, &z) / n;
+
+        // Prédicteur affine
+        let rxz = x.iter().zip(&z).map(|(x, z)| -x * z).collect::<Vec<_>>();
+        let (dx, _, dz) = newton.direction(&rp, &rd, &rxz);
+        let alpha_p = max_step(&x, &dx, &used).min(1.0);
+        let alpha_d = max_step(&z, &dz, &used).min(1.0);
+        let mu_affine = (0..width)
+            .filter(|j| used[*j])
+            .map(|j| (x[j] + alpha_p * dx[j]) * (z[j] + alpha_d * dz[j]))
+            .sum::<f64>()
+            / n;
+        let sigma = (mu_affine / mu).powi(3);
+
+        // Correcteur et centrage
+        let rxz = (0..width)
+            .map(|j| {
+                if used[j] {
+                    -x[j] * z[j] - dx[j] * dz[j] + sigma * mu
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+        let (dx, dy, dz) = newton.direction(&rp, &rd, &rxz);
+        let alpha_p = (STEP_FRACTION * max_step(&x, &dx, &used)).min(1.0);
+        let alpha_d = (STEP_FRACTION * max_step(&z, &dz, &used)).min(1.0);
+        for j in (0..width).filter(|j| used[*j]) {
+            x[j] += alpha_p * dx[j];
+            z[j] += alpha_d * dz[j];
+        }
+        for (y, dy) in y.iter_mut().zip(&dy) {
+            *y += alpha_d * dy;
+        }
+    }
+    match best {
+        Some((error, point)) if error < ACCEPTABLE => Ok(point),
+        _ => Err("La méthode de points intérieurs ne converge pas".to_string()),
+    }
+}
@@ -1,102 +1,942 @@
 use axum::{
-    extract::{Json, DefaultBodyLimit},
-    http::StatusCode,
-    routing::{post},
     Router,
+    extract::{DefaultBodyLimit, Json, Path, Query},
+    http::StatusCode,
     response::IntoResponse,
+    routing::post,
 };
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+mod alternatives;
+mod assignment;
+mod basis_file;
+mod benders;
+mod column_generation;
+mod cuts;
+mod cutting_stock;
+mod dantzig_wolfe;
+mod heuristics;
+mod incremental;
+mod interior_point;
+mod model;
+mod multi_objective;
+mod network;
+mod parametric;
+mod presolve;
+mod progress;
+mod quadratic;
+mod scaling;
 mod simplexef64;
+mod sparse;
+mod transportation;
+mod tsp;
+
+// Journal écrit sur la sortie d'erreur, de niveau SIMPLEXE_LOG (off, error, warn, info,
+// debug ou trace) ou default
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Logger = Logger;
+
+fn init_logger(default: log::LevelFilter) {
+    let level = std::env::var("SIMPLEXE_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(default);
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+// Observateur qui écrit chaque événement dans un fichier, une ligne JSON par événement
+struct ProgressFile(Mutex<std::fs::File>);
+
+impl progress::Observer for ProgressFile {
+    fn notify(&self, event: &progress::Progress) {
+        if let (Ok(mut file), Ok(line)) = (self.0.lock(), serde_json::to_string(event)) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+// Lance f en observant sa progression dans le fichier path s'il est donné
+fn with_progress<R>(path: Option<&str>, f: impl FnOnce() -> R) -> Result<R, String> {
+    match path {
+        Some(path) => {
+            let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+            Ok(progress::observe(
+                Arc::new(ProgressFile(Mutex::new(file))),
+                f,
+            ))
+        }
+        None => Ok(f()),
+    }
+}
 
 #[tokio::main]
 async fn server() {
     let app = Router::new()
         .route("/simplex", post(simplexe))
         .route("/branch_and_bound", post(branch_and_bound))
-        .layer(DefaultBodyLimit::max(1024*1024*50));
+        .route("/benders", post(benders))
+        .route("/dantzig_wolfe", post(dantzig_wolfe))
+        .route("/transportation", post(transportation))
+        .route("/assignment", post(assignment))
+        .route("/cutting_stock", post(cutting_stock))
+        .route("/tsp", post(tsp))
+        .route("/parametric", post(parametric))
+        .route("/session", post(create_session))
+        .route("/session/{id}", post(update_session).delete(close_session))
+        .layer(DefaultBodyLimit::max(1024 * 1024 * 50));
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8888")
-        .await
-        .unwrap();
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:8888").await.unwrap();
 
     axum::serve(listener, app.into_make_service())
         .await
         .unwrap();
 }
 
-async fn simplexe(lpfile: String) -> impl IntoResponse {
-    let (mut matrix, mut variables, mut hash_map_vars, is_min, original_cost) = 
-    match simplexef64::parse_lp_two_phases(&lpfile) {
-        Ok((matrix, variables, is_min, hash_map_vars, original_cost)) => (matrix, variables, hash_map_vars, is_min, original_cost),
+async fn simplexe(
+    Query(options): Query<simplexef64::SimplexOptions>,
+    lpfile: String,
+) -> impl IntoResponse {
+    let solution = match simplexef64::solve_lp(&lpfile, &options) {
+        Ok(solution) => solution,
         Err(e) => {
-            // println!("❌ Failed to parse LP file with error: {:?}", e);
-            return (StatusCode::BAD_REQUEST, ("Failed to parse LP file ".to_string()+&e).into_response());
+            // println!("❌ Failed to solve LP with error: {:?}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.into_response());
         }
     };
-    //println!("Parsed LP file successfully");
-    let (variables,z) = 
-    match simplexef64::solve_system_two_phases(&mut matrix, &mut variables, &mut hash_map_vars, &original_cost, is_min) {
-        Ok((variables,  z)) => (variables, z),
+    if options.duals || options.basis {
+        return (StatusCode::OK, Json(solution).into_response());
+    }
+    (
+        StatusCode::OK,
+        Json((solution.variables, solution.z)).into_response(),
+    )
+}
+
+async fn branch_and_bound(
+    Query(options): Query<simplexef64::BranchAndBoundOptions>,
+    lpfile: String,
+) -> impl IntoResponse {
+    let result = match simplexef64::branch_and_bound(&lpfile, &options) {
+        Ok(result) => result,
         Err(e) => {
             // println!("❌ Failed to solve LP with error: {:?}", e);
             return (StatusCode::INTERNAL_SERVER_ERROR, e.into_response());
         }
     };
-    // println!("{:?}", Json((variables.clone(), z.clone())));
-    // (StatusCode::OK, Json((variables.iter().map(|(a, b, c)| (a, b.to_string(), c)).collect::<Vec<_>>(), z.const_term.numer().to_string()+"/"+&z.const_term.denom().to_string())).into_response())
-    //println!("z = {:?}", z);
     // let variables = variables.iter().map(|(a, b)| (a, b.to_f64())).collect::<Vec<_>>();
-    (StatusCode::OK, Json((variables, z)).into_response())
+    (StatusCode::OK, Json(result).into_response())
+}
 
+async fn benders(
+    Query(options): Query<benders::BendersOptions>,
+    lpfile: String,
+) -> impl IntoResponse {
+    match benders::benders(&lpfile, &options) {
+        Ok(result) => (StatusCode::OK, Json(result).into_response()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+    }
+}
+
+async fn dantzig_wolfe(
+    Query(options): Query<dantzig_wolfe::DantzigWolfeOptions>,
+    lpfile: String,
+) -> impl IntoResponse {
+    match dantzig_wolfe::dantzig_wolfe(&lpfile, &options) {
+        Ok(result) => (StatusCode::OK, Json(result).into_response()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+    }
+}
+
+async fn transportation(
+    Query(options): Query<transportation::TransportationOptions>,
+    body: String,
+) -> impl IntoResponse {
+    let solution = transportation::parse_transportation(&body)
+        .and_then(|problem| transportation::solve_transportation(&problem, &options));
+    match solution {
+        Ok(solution) => (StatusCode::OK, Json(solution).into_response()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+    }
+}
+
+async fn assignment(body: String) -> impl IntoResponse {
+    let solution = assignment::parse_assignment(&body)
+        .and_then(|problem| assignment::solve_assignment(&problem));
+    match solution {
+        Ok(solution) => (StatusCode::OK, Json(solution).into_response()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+    }
+}
+
+async fn cutting_stock(body: String) -> impl IntoResponse {
+    let solution = cutting_stock::parse_cutting_stock(&body)
+        .and_then(|problem| cutting_stock::solve_cutting_stock(&problem));
+    match solution {
+        Ok(solution) => (StatusCode::OK, Json(solution).into_response()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+    }
+}
+
+async fn tsp(body: String) -> impl IntoResponse {
+    let solution = tsp::parse_tsp(&body).and_then(|problem| tsp::solve_tsp(&problem));
+    match solution {
+        Ok(solution) => (StatusCode::OK, Json(solution).into_response()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+    }
+}
+
+async fn parametric(
+    Query(options): Query<parametric::ParametricOptions>,
+    lpfile: String,
+) -> impl IntoResponse {
+    match parametric::parametric(&lpfile, &options) {
+        Ok(result) => (StatusCode::OK, Json(result).into_response()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+    }
 }
 
-async fn branch_and_bound(lpfile: String) -> impl IntoResponse {
-    let (variables,_, z) = 
-    match simplexef64::branch_and_bound(&lpfile) {
-        Ok((variables,  x, z)) => (variables, x, z),
+// Au-delà de MAX_SESSIONS sessions ouvertes, la moins récemment utilisée est fermée
+const MAX_SESSIONS: usize = 100;
+// Une session inutilisée depuis SESSION_TTL est fermée
+const SESSION_TTL: Duration = Duration::from_secs(3600);
+
+struct OpenSession {
+    session: Arc<Mutex<incremental::Session>>,
+    last_used: Instant,
+}
+
+// Sessions ouvertes par le serveur, par identifiant. Chaque session a son propre verrou :
+// une résolution ne bloque que les requêtes sur la même session.
+#[derive(Default)]
+struct Sessions {
+    last_id: u64,
+    open: HashMap<u64, OpenSession>,
+}
+
+impl Sessions {
+    // Ferme les sessions expirées
+    fn expire(&mut self) {
+        self.open
+            .retain(|_, open| open.last_used.elapsed() < SESSION_TTL);
+    }
+
+    // Ferme les sessions expirées puis les moins récemment utilisées pour en ouvrir une
+    fn make_room(&mut self) {
+        self.expire();
+        while self.open.len() >= MAX_SESSIONS
+            && let Some(oldest) = self
+                .open
+                .iter()
+                .min_by_key(|(_, open)| open.last_used)
+                .map(|(id, _)| *id)
+        {
+            self.open.remove(&oldest);
+        }
+    }
+}
+
+static SESSIONS: LazyLock<Mutex<Sessions>> = LazyLock::new(Mutex::default);
+
+// Un verrou empoisonné par une panique pendant une résolution est repris : une session
+// n'est remplacée qu'une fois la résolution de sa copie réussie, elle reste cohérente
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+async fn create_session(lpfile: String) -> impl IntoResponse {
+    let created = tokio::task::spawn_blocking(move || {
+        let mut session = incremental::Session::new(&lpfile)?;
+        let solution = session.solve()?;
+        Ok::<_, String>((session, solution))
+    })
+    .await;
+    let (session, solution) = match created {
+        Ok(Ok(created)) => created,
+        Ok(Err(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
         Err(e) => {
-            // println!("❌ Failed to solve LP with error: {:?}", e);
-            return (StatusCode::INTERNAL_SERVER_ERROR, e.into_response());
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                e.to_string().into_response(),
+            );
         }
     };
-    // let variables = variables.iter().map(|(a, b)| (a, b.to_f64())).collect::<Vec<_>>();
-    (StatusCode::OK, Json((variables, z)).into_response())
+    let mut sessions = lock(&SESSIONS);
+    sessions.make_room();
+    sessions.last_id += 1;
+    let id = sessions.last_id;
+    sessions.open.insert(
+        id,
+        OpenSession {
+            session: Arc::new(Mutex::new(session)),
+            last_used: Instant::now(),
+        },
+    );
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "id": id, "solution": solution })).into_response(),
+    )
+}
+
+async fn update_session(
+    Path(id): Path<u64>,
+    Json(changes): Json<Vec<incremental::Change>>,
+) -> impl IntoResponse {
+    // Le verrou des sessions n'est pas gardé pendant la résolution
+    let session = {
+        let mut sessions = lock(&SESSIONS);
+        sessions.expire();
+        match sessions.open.get_mut(&id) {
+            Some(open) => {
+                open.last_used = Instant::now();
+                open.session.clone()
+            }
+            None => return (StatusCode::NOT_FOUND, "Session inconnue".into_response()),
+        }
+    };
+    // La résolution tourne sur un thread bloquant : une mise à jour qui attend le verrou de
+    // sa session n'immobilise pas les threads du serveur
+    let solved = tokio::task::spawn_blocking(move || {
+        let mut session = lock(&session);
+        // Les modifications ne sont gardées que si elles s'appliquent toutes
+        let mut updated = session.clone();
+        let solution = changes
+            .iter()
+            .try_for_each(|change| updated.apply(change))
+            .and_then(|_| updated.solve())?;
+        *session = updated;
+        Ok::<_, String>(solution)
+    })
+    .await;
+    match solved {
+        Ok(Ok(solution)) => (StatusCode::OK, Json(solution).into_response()),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.into_response()),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string().into_response(),
+        ),
+    }
+}
+
+async fn close_session(Path(id): Path<u64>) -> impl IntoResponse {
+    match lock(&SESSIONS).open.remove(&id) {
+        Some(_) => StatusCode::OK,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+fn parse_solver(value: &str) -> Option<simplexef64::Solver> {
+    match value {
+        "simplex" => Some(simplexef64::Solver::Simplex),
+        "interior_point" => Some(simplexef64::Solver::InteriorPoint),
+        _ => None,
+    }
+}
+
+fn parse_multi_objective(value: &str) -> Option<multi_objective::MultiObjective> {
+    match value {
+        "lexicographic" => Some(multi_objective::MultiObjective::Lexicographic),
+        "weighted" => Some(multi_objective::MultiObjective::Weighted),
+        _ => None,
+    }
 }
 
-fn simplexe_cmd(path: &str) {
+fn print_goals(goals: &[multi_objective::GoalAchievement]) {
+    for goal in goals {
+        println!(
+            "goal {}: target {:?}, achieved {:?} (under {:?}, over {:?})",
+            goal.name, goal.target, goal.value, goal.under, goal.over
+        );
+    }
+}
+
+fn simplexe_cmd(path: &str, args: &[String]) {
     let now = std::time::Instant::now();
     //let argv1 = "../warehouse100.lp";
+    let mut options = simplexef64::SimplexOptions::default();
+    let (mut basis_path, mut write_basis) = (None, None);
+    let mut progress_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().map(|v| v.as_str()).unwrap_or_default();
+        let parsed = match arg.as_str() {
+            "--basis" => {
+                basis_path = Some(value.to_string());
+                true
+            }
+            "--write-basis" => {
+                options.basis = true;
+                write_basis = Some(value.to_string());
+                true
+            }
+            "--progress" => {
+                progress_path = Some(value.to_string());
+                true
+            }
+            "--presolve" => value.parse().map(|v| options.presolve = v).is_ok(),
+            "--scaling" => value.parse().map(|v| options.scaling = v).is_ok(),
+            "--solver" => parse_solver(value).map(|v| options.solver = v).is_some(),
+            "--network" => value.parse().map(|v| options.network = v).is_ok(),
+            "--assignment" => value.parse().map(|v| options.assignment = v).is_ok(),
+            "--multi-objective" => parse_multi_objective(value)
+                .map(|v| options.multi_objective = v)
+                .is_some(),
+            "--alternatives" => value.parse().map(|v| options.alternatives = v).is_ok(),
+            _ => false,
+        };
+        if !parsed {
+            println!("❌ Unknown option or invalid value: {} {}", arg, value);
+            return;
+        }
+    }
     let file_string = std::fs::read_to_string(path).unwrap();
-    let (mut matrix, mut variables, is_min, mut vars_hash_map, original_cost) = match simplexef64::parse_lp_two_phases(&file_string) {
-        Ok((matrix, variables, is_min, vars_hash_map, original_cost)) => (matrix, variables, is_min, vars_hash_map, original_cost),
-        Err(e) => {
-            println!("❌ Failed to parse LP file with error: {:?}", e);
+    let start = match basis_path.map(|path| {
+        std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| basis_file::StoredBasis::parse(&text))
+    }) {
+        Some(Ok(basis)) => Some(basis),
+        Some(Err(e)) => {
+            println!("❌ Failed to read the basis with error: {:?}", e);
             return;
         }
+        None => None,
     };
-    let (variables,z) = 
-    match simplexef64::solve_system_two_phases(&mut matrix, &mut variables, &mut vars_hash_map, &original_cost, is_min) {
-        Ok((variables,  z)) => (variables, z),
+    let solution = with_progress(progress_path.as_deref(), || {
+        simplexef64::solve_lp_from(&file_string, &options, start.as_ref())
+    });
+    let solution = match solution.and_then(|solution| solution) {
+        Ok(solution) => solution,
         Err(e) => {
             println!("❌ Failed to solve LP with error: {:?}", e);
             return;
         }
     };
-    println!("{:?}\nz = {:?}\nTime taken: {:?}", variables, z, now.elapsed());
+    println!(
+        "{:?}\nz = {:?}\nduals = {:?}",
+        solution.variables, solution.z, solution.duals
+    );
+    for (name, value) in &solution.objectives {
+        println!("objective {} = {:?}", name, value);
+    }
+    print_goals(&solution.goals);
+    if let Some(alternatives) = &solution.alternatives {
+        println!(
+            "{} optimal vertices{}",
+            alternatives.vertices.len(),
+            if alternatives.unbounded_face {
+                " (unbounded optimal face)"
+            } else {
+                ""
+            }
+        );
+        for vertex in &alternatives.vertices {
+            println!("{:?}", vertex);
+        }
+    }
+    // Base en JSON pour un fichier .json, au format BAS sinon
+    if let (Some(basis), Some(basis_path)) = (&solution.basis, write_basis) {
+        let text = if basis_path.ends_with(".json") {
+            serde_json::to_string_pretty(basis).unwrap()
+        } else {
+            let name = std::path::Path::new(path).file_stem().unwrap_or_default();
+            basis.to_bas(&name.to_string_lossy())
+        };
+        if let Err(e) = std::fs::write(&basis_path, text) {
+            println!("❌ Failed to write {}: {}", basis_path, e);
+            return;
+        }
+        println!("Basis written to {}", basis_path);
+    }
+    println!("Time taken: {:?}", now.elapsed());
+}
+
+fn branch_and_bound_cmd(path: &str, args: &[String]) {
+    let now = std::time::Instant::now();
+    let mut options = simplexef64::BranchAndBoundOptions::default();
+    let mut progress_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().map(|v| v.as_str()).unwrap_or_default();
+        let parsed = match arg.as_str() {
+            "--progress" => {
+                progress_path = Some(value.to_string());
+                true
+            }
+            "--abs-gap" => value.parse().map(|v| options.abs_gap = v).is_ok(),
+            "--rel-gap" => value.parse().map(|v| options.rel_gap = v).is_ok(),
+            "--reliability" => value.parse().map(|v| options.reliability = v).is_ok(),
+            "--strong-candidates" => value.parse().map(|v| options.strong_candidates = v).is_ok(),
+            "--cut-rounds" => value.parse().map(|v| options.cut_rounds = v).is_ok(),
+            "--cuts-per-round" => value.parse().map(|v| options.cuts_per_round = v).is_ok(),
+            "--node-cuts" => value.parse().map(|v| options.node_cuts = v).is_ok(),
+            "--clique-cuts" => value.parse().map(|v| options.clique_cuts = v).is_ok(),
+            "--cover-cuts" => value.parse().map(|v| options.cover_cuts = v).is_ok(),
+            "--heuristics" => value.parse().map(|v| options.heuristics = v).is_ok(),
+            "--heuristic-frequency" => value
+                .parse()
+                .map(|v| options.heuristic_frequency = v)
+                .is_ok(),
+            "--rins-nodes" => value.parse().map(|v| options.rins_nodes = v).is_ok(),
+            "--max-nodes" => value.parse().map(|v| options.max_nodes = v).is_ok(),
+            "--presolve" => value.parse().map(|v| options.presolve = v).is_ok(),
+            "--solver" => parse_solver(value).map(|v| options.solver = v).is_some(),
+            "--network" => value.parse().map(|v| options.network = v).is_ok(),
+            "--multi-objective" => parse_multi_objective(value)
+                .map(|v| options.multi_objective = v)
+                .is_some(),
+            "--pool-size" => value.parse().map(|v| options.pool_size = v).is_ok(),
+            "--branching" => {
+                options.branching = match value {
+                    "first_fractional" => simplexef64::BranchingRule::FirstFractional,
+                    "most_fractional" => simplexef64::BranchingRule::MostFractional,
+                    "pseudo_cost" => simplexef64::BranchingRule::PseudoCost,
+                    "strong" => simplexef64::BranchingRule::Strong,
+                    _ => {
+                        println!("❌ Unknown branching rule: {}", value);
+                        return;
+                    }
+                };
+                true
+            }
+            _ => false,
+        };
+        if !parsed {
+            println!("❌ Unknown option or invalid value: {} {}", arg, value);
+            return;
+        }
+    }
+    let file_string = std::fs::read_to_string(path).unwrap();
+    let result = with_progress(progress_path.as_deref(), || {
+        simplexef64::branch_and_bound(&file_string, &options)
+    });
+    let result = match result.and_then(|result| result) {
+        Ok(result) => result,
+        Err(e) => {
+            println!("❌ Failed to solve MIP with error: {:?}", e);
+            return;
+        }
+    };
+    println!(
+        "{:?}\nz = {:?}\nbest bound = {:?}\ngap = {:.4}%\nnodes explored = {}, open = {}\ncuts: {} gomory, {} clique ({} conflict edges), {} cover in {} rounds, {} in nodes (root bound {:?} -> {:?})\nTime taken: {:?}",
+        result.variables,
+        result.z,
+        result.best_bound,
+        result.gap * 100.0,
+        result.explored_nodes,
+        result.open_nodes,
+        result.cuts.gomory_cuts,
+        result.cuts.clique_cuts,
+        result.cuts.conflict_edges,
+        result.cuts.cover_cuts,
+        result.cuts.rounds,
+        result.cuts.node_cuts,
+        result.cuts.root_bound_before,
+        result.cuts.root_bound_after,
+        now.elapsed()
+    );
+    for (name, value) in &result.objectives {
+        println!("objective {} = {:?}", name, value);
+    }
+    print_goals(&result.goals);
+    if result.pool.len() > 1 {
+        println!("solution pool:");
+        for solution in &result.pool {
+            println!("z = {:?} {:?}", solution.z, solution.variables);
+        }
+    }
+}
+
+fn benders_cmd(path: &str, args: &[String]) {
+    let now = std::time::Instant::now();
+    let mut options = benders::BendersOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().map(|v| v.as_str()).unwrap_or_default();
+        let parsed = match arg.as_str() {
+            "--master" => {
+                options.master = value.to_string();
+                true
+            }
+            "--max-iterations" => value.parse().map(|v| options.max_iterations = v).is_ok(),
+            "--gap" => value.parse().map(|v| options.gap = v).is_ok(),
+            _ => false,
+        };
+        if !parsed {
+            println!("❌ Unknown option or invalid value: {} {}", arg, value);
+            return;
+        }
+    }
+    let file_string = std::fs::read_to_string(path).unwrap();
+    match benders::benders(&file_string, &options) {
+        Ok(result) => println!(
+            "{:?}\nz = {:?}\nbound = {:?}\n{} iterations, {} optimality cuts, {} feasibility cuts",
+            result.variables,
+            result.z,
+            result.bound,
+            result.iterations,
+            result.optimality_cuts,
+            result.feasibility_cuts
+        ),
+        Err(e) => println!("❌ Failed to run Benders decomposition with error: {:?}", e),
+    }
+    println!("Time taken: {:?}", now.elapsed());
+}
+
+fn dantzig_wolfe_cmd(path: &str, args: &[String]) {
+    let now = std::time::Instant::now();
+    let mut options = dantzig_wolfe::DantzigWolfeOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().map(|v| v.as_str()).unwrap_or_default();
+        match arg.as_str() {
+            "--blocks" => options.blocks = value.to_string(),
+            _ => {
+                println!("❌ Unknown option or invalid value: {} {}", arg, value);
+                return;
+            }
+        }
+    }
+    let file_string = std::fs::read_to_string(path).unwrap();
+    match dantzig_wolfe::dantzig_wolfe(&file_string, &options) {
+        Ok(result) => println!(
+            "{:?}\nz = {:?}\n{} blocks, {} linking rows, {} iterations, {} columns",
+            result.variables,
+            result.z,
+            result.blocks,
+            result.linking_rows,
+            result.iterations,
+            result.columns
+        ),
+        Err(e) => println!(
+            "❌ Failed to run Dantzig-Wolfe decomposition with error: {:?}",
+            e
+        ),
+    }
+    println!("Time taken: {:?}", now.elapsed());
+}
+
+fn transportation_cmd(path: &str, args: &[String]) {
+    let now = std::time::Instant::now();
+    let mut options = transportation::TransportationOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().map(|v| v.as_str()).unwrap_or_default();
+        let parsed = match arg.as_str() {
+            "--method" => {
+                options.method = match value {
+                    "north_west_corner" => transportation::InitialSolution::NorthWestCorner,
+                    "least_cost" => transportation::InitialSolution::LeastCost,
+                    "vogel" => transportation::InitialSolution::Vogel,
+                    _ => {
+                        println!("❌ Unknown initial solution: {}", value);
+                        return;
+                    }
+                };
+                true
+            }
+            _ => false,
+        };
+        if !parsed {
+            println!("❌ Unknown option or invalid value: {} {}", arg, value);
+            return;
+        }
+    }
+    let file_string = std::fs::read_to_string(path).unwrap();
+    let solution = transportation::parse_transportation(&file_string)
+        .and_then(|problem| transportation::solve_transportation(&problem, &options));
+    let solution = match solution {
+        Ok(solution) => solution,
+        Err(e) => {
+            println!(
+                "❌ Failed to solve transportation problem with error: {:?}",
+                e
+            );
+            return;
+        }
+    };
+    println!(
+        "{:?}\nz = {:?}\ninitial z = {:?}\nMODI iterations = {}\nunused supply = {:?}\nunmet demand = {:?}\nTime taken: {:?}",
+        solution.variables,
+        solution.z,
+        solution.initial_z,
+        solution.iterations,
+        solution.unused_supply,
+        solution.unmet_demand,
+        now.elapsed()
+    );
+}
+
+fn assignment_cmd(path: &str) {
+    let now = std::time::Instant::now();
+    let file_string = std::fs::read_to_string(path).unwrap();
+    let solution = assignment::parse_assignment(&file_string)
+        .and_then(|problem| assignment::solve_assignment(&problem));
+    let solution = match solution {
+        Ok(solution) => solution,
+        Err(e) => {
+            println!("❌ Failed to solve assignment problem with error: {:?}", e);
+            return;
+        }
+    };
+    println!(
+        "{:?}\nz = {:?}\nunassigned = {:?}\nTime taken: {:?}",
+        solution.variables,
+        solution.z,
+        solution.unassigned,
+        now.elapsed()
+    );
+}
+
+fn cutting_stock_cmd(path: &str) {
+    let now = std::time::Instant::now();
+    let input = std::fs::read_to_string(path).unwrap();
+    let solution = cutting_stock::parse_cutting_stock(&input)
+        .and_then(|problem| cutting_stock::solve_cutting_stock(&problem));
+    match solution {
+        Ok(solution) => {
+            println!(
+                "Column generation: {} iterations, {} columns added",
+                solution.iterations, solution.columns
+            );
+            println!("LP bound = {:?}", solution.bound);
+            println!("rolls = {:?}", solution.rolls);
+            for pattern in &solution.patterns {
+                println!("{:?} x {:?}", pattern.rolls, pattern.cuts);
+            }
+        }
+        Err(e) => println!(
+            "❌ Failed to solve cutting stock problem with error: {:?}",
+            e
+        ),
+    }
+    println!("Time taken: {:?}", now.elapsed());
+}
+
+fn tsp_cmd(path: &str) {
+    let now = std::time::Instant::now();
+    let input = std::fs::read_to_string(path).unwrap();
+    let solution = tsp::parse_tsp(&input).and_then(|problem| tsp::solve_tsp(&problem));
+    match solution {
+        Ok(solution) => {
+            println!("tour = {:?}", solution.tour);
+            println!("length = {:?}", solution.length);
+            println!(
+                "nodes explored = {}, {} lazy subtour constraints, {} subtour cuts",
+                solution.explored_nodes, solution.lazy_constraints, solution.user_cuts
+            );
+        }
+        Err(e) => println!("❌ Failed to solve TSP with error: {:?}", e),
+    }
+    println!("Time taken: {:?}", now.elapsed());
+}
+
+fn parametric_cmd(path: &str, args: &[String]) {
+    let now = std::time::Instant::now();
+    let mut options = parametric::ParametricOptions::default();
+    let mut svg_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().map(|v| v.as_str()).unwrap_or_default();
+        let parsed = match arg.as_str() {
+            "--parameter" => {
+                options.parameter = match value {
+                    "rhs" => parametric::Parameter::Rhs,
+                    "cost" => parametric::Parameter::Cost,
+                    _ => {
+                        println!("❌ Unknown parameter: {}", value);
+                        return;
+                    }
+                };
+                true
+            }
+            "--direction" => {
+                options.direction = value.to_string();
+                true
+            }
+            "--theta-min" => value.parse().map(|v| options.theta_min = v).is_ok(),
+            "--theta-max" => value.parse().map(|v| options.theta_max = v).is_ok(),
+            "--svg" => {
+                options.svg = true;
+                svg_path = Some(value.to_string());
+                true
+            }
+            _ => false,
+        };
+        if !parsed {
+            println!("❌ Unknown option or invalid value: {} {}", arg, value);
+            return;
+        }
+    }
+    let file_string = std::fs::read_to_string(path).unwrap();
+    let result = match parametric::parametric(&file_string, &options) {
+        Ok(result) => result,
+        Err(e) => {
+            println!("❌ Failed to run parametric analysis with error: {:?}", e);
+            return;
+        }
+    };
+    println!(
+        "{:>14} {:>14} {:>14} {:>14}  basis",
+        "theta start", "theta end", "z start", "z end"
+    );
+    for segment in &result.segments {
+        println!(
+            "{:>14.6} {:>14.6} {:>14.6} {:>14.6}  {}",
+            segment.theta_start,
+            segment.theta_end,
+            segment.z_start,
+            segment.z_end,
+            segment
+                .bases
+                .iter()
+                .map(|basis| basis.join(" "))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+    }
+    if let Some(stop) = &result.stop {
+        println!("{}", stop);
+    }
+    if let (Some(svg), Some(svg_path)) = (&result.svg, svg_path) {
+        if let Err(e) = std::fs::write(&svg_path, svg) {
+            println!("❌ Failed to write {}: {}", svg_path, e);
+            return;
+        }
+        println!("Plot written to {}", svg_path);
+    }
+    println!("Time taken: {:?}", now.elapsed());
+}
+
+// Résout le programme puis applique une à une les modifications du fichier JSON changes,
+// chacune suivie d'une nouvelle résolution depuis la base précédente
+fn session_cmd(path: &str, changes: &str) {
+    let now = std::time::Instant::now();
+    let file_string = std::fs::read_to_string(path).unwrap();
+    let changes = std::fs::read_to_string(changes)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            serde_json::from_str::<Vec<incremental::Change>>(&json).map_err(|e| e.to_string())
+        });
+    let changes = match changes {
+        Ok(changes) => changes,
+        Err(e) => {
+            println!("❌ Failed to read the changes with error: {:?}", e);
+            return;
+        }
+    };
+    let mut session = match incremental::Session::new(&file_string) {
+        Ok(session) => session,
+        Err(e) => {
+            println!("❌ Failed to open the session with error: {:?}", e);
+            return;
+        }
+    };
+    for (step, change) in std::iter::once(None)
+        .chain(changes.iter().map(Some))
+        .enumerate()
+    {
+        if let Some(change) = change
+            && let Err(e) = session.apply(change)
+        {
+            println!("❌ Failed to apply {:?} with error: {:?}", change, e);
+            return;
+        }
+        match session.solve() {
+            Ok(solution) => println!(
+                "step {}: {:?}\nz = {:?}\nduals = {:?}",
+                step, solution.variables, solution.z, solution.duals
+            ),
+            Err(e) => {
+                println!("step {}: ❌ {:?}", step, e);
+                return;
+            }
+        }
+    }
+    println!("Time taken: {:?}", now.elapsed());
 }
 
 fn main() {
-    let argv1 = std::env::args().nth(1);
-    match argv1 {
+    let args = std::env::args().collect::<Vec<_>>();
+    // Le serveur ne journalise que les avertissements, la progression reste dans la ligne de commande
+    init_logger(if args.get(1).is_some_and(|command| command == "server") {
+        log::LevelFilter::Warn
+    } else {
+        log::LevelFilter::Info
+    });
+    match args.get(1) {
         Some(path) => {
             if path == "server" {
                 server();
-            }
-            else {
-                simplexe_cmd(&path);
+            } else if path == "branch_and_bound" {
+                match args.get(2) {
+                    Some(path) => branch_and_bound_cmd(path, &args[3..]),
+                    None => println!("Please provide a path to the LP file"),
+                }
+            } else if path == "assignment" {
+                match args.get(2) {
+                    Some(path) => assignment_cmd(path),
+                    None => println!("Please provide a path to the assignment problem"),
+                }
+            } else if path == "benders" {
+                match args.get(2) {
+                    Some(path) => benders_cmd(path, &args[3..]),
+                    None => println!("Please provide a path to the LP file"),
+                }
+            } else if path == "cutting_stock" {
+                match args.get(2) {
+                    Some(path) => cutting_stock_cmd(path),
+                    None => println!("Please provide a path to the cutting stock problem"),
+                }
+            } else if path == "dantzig_wolfe" {
+                match args.get(2) {
+                    Some(path) => dantzig_wolfe_cmd(path, &args[3..]),
+                    None => println!("Please provide a path to the LP file"),
+                }
+            } else if path == "parametric" {
+                match args.get(2) {
+                    Some(path) => parametric_cmd(path, &args[3..]),
+                    None => println!("Please provide a path to the LP file"),
+                }
+            } else if path == "session" {
+                match (args.get(2), args.get(3)) {
+                    (Some(path), Some(changes)) => session_cmd(path, changes),
+                    _ => println!("Please provide a path to the LP file and to the changes"),
+                }
+            } else if path == "tsp" {
+                match args.get(2) {
+                    Some(path) => tsp_cmd(path),
+                    None => println!("Please provide a path to the distance matrix"),
+                }
+            } else if path == "transportation" {
+                match args.get(2) {
+                    Some(path) => transportation_cmd(path, &args[3..]),
+                    None => println!("Please provide a path to the transportation problem"),
+                }
+            } else {
+                simplexe_cmd(path, &args[2..]);
             }
         }
         None => {
             println!("Please provide a path to the LP file or uses \"server\" to run the server");
         }
     }
-}
\ No newline at end of file
+}
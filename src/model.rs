@@ -0,0 +1,550 @@
+use pest::Parser;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::simplexef64::{LPParser, Rule};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Sense {
+    #[serde(rename = "<=")]
+    Leq,
+    #[serde(rename = ">=")]
+    Geq,
+    #[serde(rename = "=")]
+    Eq,
+}
+
+impl Sense {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sense::Leq => "<=",
+            Sense::Geq => ">=",
+            Sense::Eq => "=",
+        }
+    }
+}
+
+// Ligne creuse : (indice de variable, coefficient)
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub coeffs: Vec<(usize, f64)>,
+    pub sense: Sense,
+    pub rhs: f64,
+}
+
+// Objectif nommé d'un programme multi-objectif, dans le sens du fichier
+#[derive(Debug, Clone)]
+pub struct Objective {
+    pub name: String,
+    pub is_min: f64,
+    pub coeffs: Vec<(usize, f64)>,
+    // Les niveaux sont optimisés par priorité croissante, par défaut l'ordre du fichier
+    pub priority: i64,
+    // Poids dans la somme des objectifs d'un même niveau ou de la somme pondérée
+    pub weight: f64,
+    // Dégradation relative admise quand le niveau est fixé pour optimiser les suivants
+    pub tolerance: f64,
+}
+
+// But "goal nom: a x = b weight w priority p;" : la ligne row reçoit les écarts under
+// (manque) et over (excès), a x + under - over = b. Le sens du but donne les écarts pénalisés.
+#[derive(Debug, Clone)]
+pub struct Goal {
+    pub name: String,
+    pub sense: Sense,
+    pub row: usize,
+    pub under: usize,
+    pub over: usize,
+    pub weight: f64,
+    pub priority: i64,
+    pub tolerance: f64,
+}
+
+// Programme linéaire tel qu'écrit dans le fichier, sans variables d'écart ni artificielles
+#[derive(Debug, Clone)]
+pub struct LpModel {
+    pub is_min: f64,
+    pub names: Vec<String>,
+    pub index: HashMap<String, usize>,
+    pub objective: Vec<f64>,
+    // Termes quadratiques de l'objectif (i, j, q) pour q x_i x_j, avec i <= j
+    pub quadratic: Vec<(usize, usize, f64)>,
+    // Objectifs du fichier quand il en a plusieurs, objective et is_min sont ceux du premier
+    pub objectives: Vec<Objective>,
+    pub rows: Vec<Row>,
+    // Buts du fichier, leurs lignes et leurs écarts sont dans rows et names
+    pub goals: Vec<Goal>,
+    pub integers: Vec<bool>,
+}
+
+// Ecrit une contrainte au format du fichier LP
+pub fn format_constraint(coeffs: &[(String, f64)], op: &str, rhs: f64) -> String {
+    let mut constraint = String::new();
+    for (name, coeff) in coeffs {
+        constraint.push_str(&format!("{:+} {} ", coeff, name));
+    }
+    constraint.push_str(&format!("{} {};", op, rhs));
+    constraint
+}
+
+// Vrai si name est l'un des noms patterns, "x*" désignant tous ceux qui commencent par x
+pub fn name_matches(patterns: &[&str], name: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == *pattern,
+        })
+}
+
+#[inline(always)]
+pub fn parse_coeff(coeff_str: &str) -> f64 {
+    let coeff_str = coeff_str.trim();
+    let (sign, coeff_str) = match coeff_str {
+        _ if coeff_str.starts_with('+') => (1.0, coeff_str[1..].trim()),
+        _ if coeff_str.starts_with('-') => (-1.0, coeff_str[1..].trim()),
+        _ => (1.0, coeff_str),
+    };
+    if coeff_str.is_empty() {
+        sign
+    } else {
+        coeff_str.parse::<f64>().unwrap() * sign
+    }
+}
+
+impl LpModel {
+    fn variable(&mut self, name: &str) -> usize {
+        if let Some(index) = self.index.get(name) {
+            return *index;
+        }
+        self.index.insert(name.to_string(), self.names.len());
+        self.names.push(name.to_string());
+        self.objective.push(0.0);
+        self.integers.push(false);
+        self.names.len() - 1
+    }
+
+    // Nom de variable absent du modèle
+    pub fn fresh_name(&self, mut name: String) -> String {
+        while self.index.contains_key(&name) {
+            name.insert(0, '_');
+        }
+        name
+    }
+
+    // Valeurs des variables du modèle lues dans une solution (nom, valeur)
+    pub fn values(&self, solution: &[(String, f64)]) -> Vec<f64> {
+        let mut values = vec![0.0; self.names.len()];
+        for (name, value) in solution {
+            if let Some(index) = self.index.get(name) {
+                values[*index] = *value;
+            }
+        }
+        values
+    }
+
+    // Bornes supérieures impliquées par les lignes à coefficients positifs (variables positives)
+    pub fn implied_upper_bounds(&self) -> Vec<f64> {
+        let mut upper = vec![f64::INFINITY; self.names.len()];
+        for row in &self.rows {
+            if row.sense == Sense::Geq || row.coeffs.iter().any(|(_, a)| *a < 0.0) {
+                continue;
+            }
+            for (j, a) in &row.coeffs {
+                if *a > 0.0 {
+                    upper[*j] = upper[*j].min(row.rhs / a);
+                }
+            }
+        }
+        upper
+    }
+
+    // Valeur de l'objectif, dans le sens du fichier
+    pub fn objective_value(&self, values: &[f64]) -> f64 {
+        let linear = self
+            .objective
+            .iter()
+            .zip(values)
+            .map(|(c, x)| c * x)
+            .sum::<f64>();
+        let quadratic = self
+            .quadratic
+            .iter()
+            .map(|(i, j, q)| q * values[*i] * values[*j])
+            .sum::<f64>();
+        linear + quadratic
+    }
+
+    // Ajoute q x_i x_j aux termes quadratiques de l'objectif
+    fn add_quadratic(&mut self, i: usize, j: usize, q: f64) {
+        let (i, j) = (i.min(j), i.max(j));
+        match self
+            .quadratic
+            .iter_mut()
+            .find(|(k, l, _)| *k == i && *l == j)
+        {
+            Some((_, _, a)) => *a += q,
+            None => self.quadratic.push((i, j, q)),
+        }
+    }
+
+    // Terme square (coeff x^2) ou product (coeff x*y) multiplié par factor
+    fn parse_quadratic(&mut self, term: pest::iterators::Pair<Rule>, factor: f64) {
+        let mut q = factor;
+        let mut variables = vec![];
+        for token in term.into_inner() {
+            match token.as_rule() {
+                Rule::coeff => q *= parse_coeff(token.as_str()),
+                Rule::varname => variables.push(self.variable(token.as_str().trim())),
+                _ => {}
+            }
+        }
+        match variables[..] {
+            [i] => self.add_quadratic(i, i, q),
+            [i, j] => self.add_quadratic(i, j, q),
+            _ => {}
+        }
+    }
+
+    // Ligne d'objectif "min nom attributs: termes;", avec au moins une variable
+    fn objective_line(&self, header: &str, mut terms: Vec<String>) -> String {
+        if terms.is_empty()
+            && let Some(name) = self.names.first()
+        {
+            terms.push(format!("+0 {}", name));
+        }
+        format!("{}: {};", header, terms.join(" "))
+    }
+
+    // Réécrit le modèle au format du fichier LP
+    pub fn to_lp_string(&self) -> String {
+        let mut lp =
+            if self.objectives.is_empty() {
+                let obj = if self.is_min < 0.0 { "min" } else { "max" };
+                let mut terms = self
+                    .names
+                    .iter()
+                    .zip(&self.objective)
+                    .filter(|(_, c)| **c != 0.0)
+                    .map(|(name, c)| format!("{:+} {}", c, name))
+                    .collect::<Vec<_>>();
+                terms.extend(self.quadratic.iter().filter(|(_, _, q)| *q != 0.0).map(
+                    |(i, j, q)| {
+                        if i == j {
+                            format!("{:+} {}^2", q, self.names[*i])
+                        } else {
+                            format!("{:+} {}*{}", q, self.names[*i], self.names[*j])
+                        }
+                    },
+                ));
+                self.objective_line(obj, terms)
+            } else {
+                self.objectives
+                    .iter()
+                    .map(|objective| {
+                        let header = format!(
+                            "{} {} priority {} weight {} tolerance {}",
+                            if objective.is_min < 0.0 { "min" } else { "max" },
+                            objective.name,
+                            objective.priority,
+                            objective.weight,
+                            objective.tolerance
+                        );
+                        let terms = objective
+                            .coeffs
+                            .iter()
+                            .map(|(j, c)| format!("{:+} {}", c, self.names[*j]))
+                            .collect();
+                        self.objective_line(&header, terms)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+        for row in &self.rows {
+            let coeffs = row
+                .coeffs
+                .iter()
+                .map(|(j, a)| (self.names[*j].clone(), *a))
+                .collect::<Vec<_>>();
+            lp.push('\n');
+            lp.push_str(&format_constraint(&coeffs, row.sense.as_str(), row.rhs));
+        }
+        let integers = self
+            .names
+            .iter()
+            .zip(&self.integers)
+            .filter(|(_, integer)| **integer)
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        if !integers.is_empty() {
+            lp.push_str(&format!("\nint {};", integers.join(", ")));
+        }
+        lp
+    }
+
+    // Ajoute les écarts de chaque but et, par niveau de priorité, l'objectif minimisant la
+    // somme pondérée des écarts pénalisés : le manque d'un but >=, l'excès d'un but <=,
+    // les deux pour un but =
+    fn add_goal_objectives(&mut self) {
+        let mut goals = std::mem::take(&mut self.goals);
+        for goal in &mut goals {
+            goal.under = self.variable(&self.fresh_name(format!("{}_under", goal.name)));
+            goal.over = self.variable(&self.fresh_name(format!("{}_over", goal.name)));
+            let row = &mut self.rows[goal.row];
+            row.coeffs.push((goal.under, 1.0));
+            row.coeffs.push((goal.over, -1.0));
+        }
+        let mut priorities = goals.iter().map(|goal| goal.priority).collect::<Vec<_>>();
+        priorities.sort();
+        priorities.dedup();
+        for priority in priorities {
+            let level = goals.iter().filter(|goal| goal.priority == priority);
+            let mut coeffs = vec![];
+            for goal in level.clone() {
+                if goal.sense != Sense::Leq {
+                    coeffs.push((goal.under, goal.weight));
+                }
+                if goal.sense != Sense::Geq {
+                    coeffs.push((goal.over, goal.weight));
+                }
+            }
+            self.objectives.push(Objective {
+                name: format!("goals{}", priority),
+                is_min: -1.0,
+                coeffs,
+                priority,
+                weight: 1.0,
+                tolerance: level
+                    .map(|goal| goal.tolerance)
+                    .fold(f64::INFINITY, f64::min),
+            });
+        }
+        self.goals = goals;
+    }
+
+    // Programme en minimisation des coûts objective restreint aux variables keep,
+    // renumérotées dans cet ordre, et aux lignes rows
+    pub fn restrict(&self, keep: &[usize], rows: &[usize], objective: &[f64]) -> LpModel {
+        let position = keep
+            .iter()
+            .enumerate()
+            .map(|(k, j)| (*j, k))
+            .collect::<HashMap<_, _>>();
+        let names = keep
+            .iter()
+            .map(|j| self.names[*j].clone())
+            .collect::<Vec<_>>();
+        LpModel {
+            is_min: -1.0,
+            index: names
+                .iter()
+                .enumerate()
+                .map(|(k, name)| (name.clone(), k))
+                .collect(),
+            names,
+            objective: keep.iter().map(|j| objective[*j]).collect(),
+            quadratic: vec![],
+            objectives: vec![],
+            rows: rows
+                .iter()
+                .map(|i| {
+                    let row = &self.rows[*i];
+                    Row {
+                        coeffs: row
+                            .coeffs
+                            .iter()
+                            .filter_map(|(j, a)| position.get(j).map(|k| (*k, *a)))
+                            .collect(),
+                        ..row.clone()
+                    }
+                })
+                .collect(),
+            goals: vec![],
+            integers: keep.iter().map(|j| self.integers[*j]).collect(),
+        }
+    }
+
+    // Variables entières de borne supérieure 1
+    pub fn binaries(&self) -> Vec<bool> {
+        self.implied_upper_bounds()
+            .iter()
+            .zip(&self.integers)
+            .map(|(upper, integer)| *integer && *upper < 2.0)
+            .collect()
+    }
+}
+
+// Attribut "priority p", "weight w" ou "tolerance t" d'un objectif ou d'un but
+fn parse_attribute(token: pest::iterators::Pair<Rule>) -> Result<(String, f64), String> {
+    let mut tokens = token.into_inner();
+    let attribute = tokens.next().unwrap().as_str().to_string();
+    let value = tokens.next().unwrap().as_str().trim();
+    let value = value
+        .parse::<f64>()
+        .map_err(|_| format!("Nombre invalide : {}", value))?;
+    Ok((attribute, value))
+}
+
+pub fn parse_lp_model(filename: &str) -> Result<LpModel, String> {
+    let file = match LPParser::parse(Rule::program, filename) {
+        Ok(mut file) => file.next().unwrap(),
+        Err(e) => {
+            return Err(format!("Error parsing file: {}", e));
+        }
+    };
+    let mut model = LpModel {
+        is_min: 1.0,
+        names: vec![],
+        index: HashMap::new(),
+        objective: vec![],
+        quadratic: vec![],
+        objectives: vec![],
+        rows: vec![],
+        goals: vec![],
+        integers: vec![],
+    };
+    for line in file.into_inner() {
+        match line.as_rule() {
+            Rule::function => {
+                // Seul le premier objectif donne objective et is_min
+                let first = model.objectives.is_empty();
+                let mut objective = Objective {
+                    name: format!("obj{}", model.objectives.len()),
+                    is_min: 1.0,
+                    coeffs: vec![],
+                    priority: model.objectives.len() as i64,
+                    weight: 1.0,
+                    tolerance: 0.0,
+                };
+                let mut cost = 0.0;
+                for token in line.into_inner() {
+                    match token.as_rule() {
+                        Rule::obj if token.as_str() == "min" => {
+                            objective.is_min = -1.0;
+                        }
+                        Rule::objective_name => objective.name = token.as_str().trim().to_string(),
+                        Rule::objective_attribute => match parse_attribute(token)? {
+                            (attribute, value) if attribute == "priority" => {
+                                objective.priority = value as i64
+                            }
+                            (attribute, value) if attribute == "weight" => objective.weight = value,
+                            (_, value) => objective.tolerance = value,
+                        },
+                        Rule::coeff => {
+                            cost = parse_coeff(token.as_str());
+                        }
+                        Rule::varname => {
+                            let j = model.variable(token.as_str().trim());
+                            match objective.coeffs.iter_mut().find(|(k, _)| *k == j) {
+                                Some((_, c)) => *c += cost,
+                                None => objective.coeffs.push((j, cost)),
+                            }
+                            if first {
+                                model.objective[j] += cost;
+                            }
+                        }
+                        Rule::square | Rule::product | Rule::quad_block if !first => {
+                            return Err(format!(
+                                "L'objectif {} doit être linéaire, seul le premier objectif peut être quadratique",
+                                objective.name
+                            ));
+                        }
+                        Rule::square | Rule::product => model.parse_quadratic(token, 1.0),
+                        // Bloc [ ... ]/2 au format CPLEX
+                        Rule::quad_block => {
+                            let mut factor = 0.5;
+                            for term in token.into_inner() {
+                                match term.as_rule() {
+                                    Rule::minus => factor = -0.5,
+                                    Rule::square | Rule::product => {
+                                        model.parse_quadratic(term, factor)
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if first {
+                    model.is_min = objective.is_min;
+                }
+                model.objectives.push(objective);
+            }
+            Rule::constraint | Rule::goal => {
+                let is_goal = line.as_rule() == Rule::goal;
+                let mut coeffs: Vec<(usize, f64)> = vec![];
+                let mut rhs = 0.0;
+                let mut sense = Sense::Eq;
+                let mut coeff = 1.0;
+                // Les écarts sont ajoutés une fois toutes les variables lues
+                let mut goal = Goal {
+                    name: String::new(),
+                    sense: Sense::Eq,
+                    row: model.rows.len(),
+                    under: usize::MAX,
+                    over: usize::MAX,
+                    weight: 1.0,
+                    priority: 1,
+                    tolerance: 0.0,
+                };
+                for token in line.into_inner() {
+                    match token.as_rule() {
+                        Rule::coeff => coeff = parse_coeff(token.as_str()),
+                        Rule::varname => {
+                            let j = model.variable(token.as_str().trim());
+                            match coeffs.iter_mut().find(|(k, _)| *k == j) {
+                                Some((_, a)) => *a += coeff,
+                                None => coeffs.push((j, coeff)),
+                            }
+                        }
+                        Rule::leq => sense = Sense::Leq,
+                        Rule::geq => sense = Sense::Geq,
+                        Rule::eq => sense = Sense::Eq,
+                        Rule::number => rhs = token.as_str().trim().parse::<f64>().unwrap(),
+                        Rule::goal_name => goal.name = token.as_str().to_string(),
+                        Rule::objective_attribute => match parse_attribute(token)? {
+                            (attribute, value) if attribute == "priority" => {
+                                goal.priority = value as i64
+                            }
+                            (attribute, value) if attribute == "weight" => goal.weight = value,
+                            (_, value) => goal.tolerance = value,
+                        },
+                        _ => {}
+                    }
+                }
+                if is_goal {
+                    goal.sense = sense;
+                    model.goals.push(goal);
+                    sense = Sense::Eq;
+                }
+                model.rows.push(Row { coeffs, sense, rhs });
+            }
+            Rule::int_decl => {
+                for token in line.into_inner() {
+                    if token.as_rule() == Rule::varname
+                        && let Some(j) = model.index.get(token.as_str().trim())
+                    {
+                        model.integers[*j] = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if !model.goals.is_empty() {
+        model.add_goal_objectives();
+    } else if model.objectives.is_empty() {
+        return Err("Le programme n'a pas d'objectif".to_string());
+    }
+    // Un seul objectif : le programme habituel
+    if model.objectives.len() == 1 {
+        let objective = model.objectives.pop().unwrap();
+        model.is_min = objective.is_min;
+        model.objective = vec![0.0; model.names.len()];
+        for (j, c) in objective.coeffs {
+            model.objective[j] += c;
+        }
+    }
+    Ok(model)
+}
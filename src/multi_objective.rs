@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{LpModel, Objective, Row, Sense};
+use crate::simplexef64::{
+    self, BranchAndBoundOptions, BranchAndBoundResult, LpSolution, SimplexOptions,
+};
+
+// Traitement des programmes à plusieurs objectifs : niveaux de priorité optimisés l'un
+// après l'autre, ou somme pondérée de tous les objectifs
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiObjective {
+    #[default]
+    Lexicographic,
+    Weighted,
+}
+
+// Résultat d'un but : valeur atteinte par a x et écarts à la cible
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalAchievement {
+    pub name: String,
+    pub target: f64,
+    pub value: f64,
+    pub under: f64,
+    pub over: f64,
+}
+
+pub fn goal_achievements(model: &LpModel, values: &[f64]) -> Vec<GoalAchievement> {
+    model
+        .goals
+        .iter()
+        .map(|goal| {
+            let (under, over) = (values[goal.under], values[goal.over]);
+            let target = model.rows[goal.row].rhs;
+            GoalAchievement {
+                name: goal.name.clone(),
+                target,
+                value: target - under + over,
+                under,
+                over,
+            }
+        })
+        .collect()
+}
+
+// Niveaux optimisés l'un après l'autre, les objectifs d'un niveau sont sommés avec leurs poids
+fn levels(model: &LpModel, mode: MultiObjective) -> Vec<Vec<&Objective>> {
+    let mut objectives = model.objectives.iter().collect::<Vec<_>>();
+    match mode {
+        MultiObjective::Weighted => vec![objectives],
+        MultiObjective::Lexicographic => {
+            objectives.sort_by_key(|objective| objective.priority);
+            objectives
+                .chunk_by(|a, b| a.priority == b.priority)
+                .map(<[_]>::to_vec)
+                .collect()
+        }
+    }
+}
+
+// Objectif d'un niveau dans le sens de son premier objectif, les objectifs de sens
+// contraire y sont retranchés
+fn combine(model: &LpModel, level: &[&Objective]) -> (f64, Vec<f64>) {
+    let is_min = level[0].is_min;
+    let mut coeffs = vec![0.0; model.names.len()];
+    for objective in level {
+        let factor = objective.weight * objective.is_min * is_min;
+        for (j, c) in &objective.coeffs {
+            coeffs[*j] += factor * c;
+        }
+    }
+    (is_min, coeffs)
+}
+
+// Contrainte gardant l'objectif d'un niveau à son optimum z, à la plus petite tolérance
+// du niveau près
+fn fix(level: &[&Objective], is_min: f64, coeffs: &[f64], z: f64) -> Row {
+    let tolerance = level
+        .iter()
+        .map(|objective| objective.tolerance)
+        .fold(f64::INFINITY, f64::min);
+    let slack = tolerance * z.abs();
+    let coeffs = coeffs
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| **c != 0.0)
+        .map(|(j, c)| (j, *c))
+        .collect();
+    if is_min < 0.0 {
+        Row {
+            coeffs,
+            sense: Sense::Leq,
+            rhs: z + slack,
+        }
+    } else {
+        Row {
+            coeffs,
+            sense: Sense::Geq,
+            rhs: z - slack,
+        }
+    }
+}
+
+// Dernier niveau résolu : résultat du solveur, valeurs des variables, sens et valeur de
+// l'objectif combiné du niveau, et valeur de chaque objectif du fichier
+struct Optimized<T> {
+    result: T,
+    values: Vec<f64>,
+    is_min: f64,
+    z: f64,
+    objectives: Vec<(String, f64)>,
+}
+
+// Résout les niveaux l'un après l'autre, solve rend le résultat du programme d'un niveau
+// et les valeurs de ses variables
+fn optimize<T>(
+    model: &LpModel,
+    mode: MultiObjective,
+    mut solve: impl FnMut(&LpModel) -> Result<(T, Vec<f64>), String>,
+) -> Result<Optimized<T>, String> {
+    if !model.quadratic.is_empty() {
+        return Err("Les programmes multi-objectifs doivent être linéaires".to_string());
+    }
+    let mut stage = LpModel {
+        objectives: vec![],
+        ..model.clone()
+    };
+    let mut last = None;
+    for level in levels(model, mode) {
+        let (is_min, coeffs) = combine(model, &level);
+        stage.is_min = is_min;
+        stage.objective = coeffs;
+        let (result, values) = solve(&stage)?;
+        let z = stage.objective_value(&values);
+        stage.rows.push(fix(&level, is_min, &stage.objective, z));
+        last = Some((result, values, is_min, z));
+    }
+    let (result, values, is_min, z) = last.ok_or("Aucun objectif".to_string())?;
+    let objectives = model
+        .objectives
+        .iter()
+        .map(|objective| {
+            let value = objective
+                .coeffs
+                .iter()
+                .map(|(j, c)| c * values[*j])
+                .sum::<f64>();
+            (objective.name.clone(), value)
+        })
+        .collect();
+    Ok(Optimized {
+        result,
+        values,
+        is_min,
+        z,
+        objectives,
+    })
+}
+
+pub fn solve_lp(model: &LpModel, options: &SimplexOptions) -> Result<LpSolution, String> {
+    let optimized = optimize(model, options.multi_objective, |stage| {
+        let solution = simplexef64::solve_lp(&stage.to_lp_string(), options)?;
+        let values = stage.values(&solution.variables);
+        Ok((solution, values))
+    })?;
+    let mut solution = optimized.result;
+    solution.variables = model.names.iter().cloned().zip(optimized.values).collect();
+    solution.z = optimized.z;
+    // Les duales des niveaux fixés ne sont pas rendues
+    solution.duals.truncate(model.rows.len());
+    // La base du dernier niveau porte aussi ses lignes de fixation
+    solution.basis = None;
+    solution.objectives = optimized.objectives;
+    Ok(solution)
+}
+
+// Branch and bound sur chaque niveau, declarations est ajouté au programme de chaque
+// niveau (priorités de branchement)
+pub fn branch_and_bound(
+    model: &LpModel,
+    declarations: &str,
+    options: &BranchAndBoundOptions,
+) -> Result<BranchAndBoundResult, String> {
+    let optimized = optimize(model, options.multi_objective, |stage| {
+        let lp = stage.to_lp_string() + declarations;
+        let result = simplexef64::branch_and_bound(&lp, options)?;
+        let values = stage.values(&result.variables);
+        Ok((result, values))
+    })?;
+    let mut result = optimized.result;
+    result.variables = model.names.iter().cloned().zip(optimized.values).collect();
+    result.is_min = optimized.is_min;
+    result.z = optimized.z;
+    result.objectives = optimized.objectives;
+    Ok(result)
+}
@@ -0,0 +1,305 @@
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+use crate::sparse::CscMatrix;
+
+const PRECISION: f64 = 1.0e-9;
+// Garde-fou contre le cyclage : au-delà, l'arbre courant est rendu au simplexe
+const MAX_PIVOTS_PER_ARC: usize = 50;
+
+// Réseau lu dans la matrice [b A] : chaque ligne est un noeud, éventuellement multipliée
+// par -1, et chaque colonne un arc allant du noeud de coefficient +1 vers celui de
+// coefficient -1. Un coefficient manquant est remplacé par la racine, le noeud rows.
+pub struct Network {
+    rows: usize,
+    // Colonne de la matrice de chaque arc, arcs indicés à partir de 0
+    columns: Vec<usize>,
+    tail: Vec<usize>,
+    head: Vec<usize>,
+    // Offre de chaque noeud, la racine équilibre les autres
+    supply: Vec<f64>,
+}
+
+impl Network {
+    // Détecte une matrice de réseau : coefficients ±1, au plus deux par colonne, et des
+    // lignes qu'on peut multiplier par -1 pour que chaque colonne ait un +1 et un -1.
+    // Les signes des lignes sont un 2-coloriage du graphe des colonnes à deux coefficients.
+    pub fn detect(matrix: &CscMatrix) -> Option<Network> {
+        let mut neighbours = vec![vec![]; matrix.rows];
+        for j in 1..matrix.columns() {
+            let entries = matrix.column(j).collect::<Vec<_>>();
+            if entries.len() > 2 || entries.iter().any(|(_, a)| a.abs() != 1.0) {
+                return None;
+            }
+            if let [(u, a), (v, b)] = entries[..] {
+                // Même signe : les deux lignes sont de signes opposés
+                let opposite = a == b;
+                neighbours[u].push((v, opposite));
+                neighbours[v].push((u, opposite));
+            }
+        }
+        let mut sign = vec![0.0; matrix.rows];
+        for start in 0..matrix.rows {
+            if sign[start] != 0.0 {
+                continue;
+            }
+            sign[start] = 1.0;
+            let mut queue = VecDeque::from([start]);
+            while let Some(u) = queue.pop_front() {
+                for (v, opposite) in &neighbours[u] {
+                    let expected = if *opposite { -sign[u] } else { sign[u] };
+                    if sign[*v] == 0.0 {
+                        sign[*v] = expected;
+                        queue.push_back(*v);
+                    } else if sign[*v] != expected {
+                        return None;
+                    }
+                }
+            }
+        }
+        let root = matrix.rows;
+        let mut network = Network {
+            rows: matrix.rows,
+            columns: vec![],
+            tail: vec![],
+            head: vec![],
+            supply: vec![0.0; matrix.rows + 1],
+        };
+        for j in 1..matrix.columns() {
+            let (mut tail, mut head) = (root, root);
+            for (i, a) in matrix.column(j) {
+                if a * sign[i] > 0.0 {
+                    tail = i;
+                } else {
+                    head = i;
+                }
+            }
+            network.columns.push(j);
+            network.tail.push(tail);
+            network.head.push(head);
+        }
+        for (i, b) in matrix.column(0) {
+            network.supply[i] = sign[i] * b;
+            network.supply[root] -= sign[i] * b;
+        }
+        Some(network)
+    }
+
+    // Simplexe réseau en deux phases sur un arbre couvrant enraciné, les coûts (à maximiser)
+    // et les artificielles sont indicés par colonne. L'arbre initial est formé des colonnes
+    // logiques, les artificielles ne rentrent jamais et ont une capacité nulle en phase 2.
+    // Rend les colonnes de l'arbre final, une base du programme, et le nombre de pivots.
+    pub fn solve(
+        &self,
+        costs: &[f64],
+        is_artificial: &[bool],
+        logicals: &[usize],
+    ) -> (Vec<usize>, usize) {
+        let mut arc_of_column = vec![usize::MAX; costs.len()];
+        for (arc, j) in self.columns.iter().enumerate() {
+            arc_of_column[*j] = arc;
+        }
+        let artificial = self
+            .columns
+            .iter()
+            .map(|j| is_artificial[*j])
+            .collect::<Vec<_>>();
+        let mut tree = SpanningTree {
+            network: self,
+            artificial: &artificial,
+            in_tree: vec![false; self.columns.len()],
+            flow: vec![0.0; self.columns.len()],
+            parent: vec![],
+            pred: vec![],
+            depth: vec![],
+            potential: vec![],
+            pivots: 0,
+        };
+        // Chaque colonne logique relie sa ligne à la racine et porte son second membre
+        for (i, j) in logicals.iter().enumerate() {
+            let arc = arc_of_column[*j];
+            tree.in_tree[arc] = true;
+            tree.flow[arc] = if self.tail[arc] == i {
+                self.supply[i]
+            } else {
+                -self.supply[i]
+            };
+        }
+        let max_pivots = MAX_PIVOTS_PER_ARC * (self.columns.len() + self.rows);
+        let phase_one = artificial
+            .iter()
+            .map(|a| if *a { 1.0 } else { 0.0 })
+            .collect::<Vec<_>>();
+        let feasible = tree.run(&phase_one, false, max_pivots)
+            && tree
+                .flow
+                .iter()
+                .zip(&artificial)
+                .filter(|(_, a)| **a)
+                .all(|(x, _)| *x <= PRECISION);
+        if feasible {
+            // On minimise -c
+            let phase_two = self
+                .columns
+                .iter()
+                .zip(&artificial)
+                .map(|(j, a)| if *a { 0.0 } else { -costs[*j] })
+                .collect::<Vec<_>>();
+            tree.run(&phase_two, true, max_pivots);
+        }
+        let heads = (0..self.columns.len())
+            .filter(|arc| tree.in_tree[*arc])
+            .map(|arc| self.columns[arc])
+            .collect();
+        (heads, tree.pivots)
+    }
+}
+
+// Base du simplexe réseau : arcs de l'arbre et leurs flots, avec pour chaque noeud l'arc
+// vers son père, sa profondeur et son potentiel (nul à la racine)
+struct SpanningTree<'a> {
+    network: &'a Network,
+    artificial: &'a [bool],
+    in_tree: Vec<bool>,
+    flow: Vec<f64>,
+    parent: Vec<usize>,
+    pred: Vec<usize>,
+    depth: Vec<usize>,
+    potential: Vec<f64>,
+    pivots: usize,
+}
+
+impl SpanningTree<'_> {
+    // Reconstruit pères, profondeurs et potentiels par un parcours depuis la racine
+    fn rebuild(&mut self, costs: &[f64]) {
+        let network = self.network;
+        let nodes = network.rows + 1;
+        let mut adjacency = vec![vec![]; nodes];
+        for arc in (0..self.in_tree.len()).filter(|arc| self.in_tree[*arc]) {
+            adjacency[network.tail[arc]].push(arc);
+            adjacency[network.head[arc]].push(arc);
+        }
+        self.parent = vec![usize::MAX; nodes];
+        self.pred = vec![usize::MAX; nodes];
+        self.depth = vec![0; nodes];
+        self.potential = vec![0.0; nodes];
+        let root = network.rows;
+        let mut visited = vec![false; nodes];
+        visited[root] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(u) = queue.pop_front() {
+            for arc in &adjacency[u] {
+                let (tail, head) = (network.tail[*arc], network.head[*arc]);
+                let v = if tail == u { head } else { tail };
+                if visited[v] {
+                    continue;
+                }
+                visited[v] = true;
+                self.parent[v] = u;
+                self.pred[v] = *arc;
+                self.depth[v] = self.depth[u] + 1;
+                // Coût réduit nul sur l'arbre : c - y_tail + y_head = 0
+                self.potential[v] = if tail == u {
+                    self.potential[u] - costs[*arc]
+                } else {
+                    self.potential[u] + costs[*arc]
+                };
+                queue.push_back(v);
+            }
+        }
+    }
+
+    // Itère jusqu'à l'optimum des coûts par arc (à minimiser). Rend false si le nombre
+    // de pivots dépasse max_pivots ou si un cycle de coût négatif n'est pas borné.
+    fn run(&mut self, costs: &[f64], capacitated: bool, max_pivots: usize) -> bool {
+        let network = self.network;
+        loop {
+            self.rebuild(costs);
+            let entering = (0..self.in_tree.len())
+                .into_par_iter()
+                .filter(|arc| !self.in_tree[*arc] && !self.artificial[*arc])
+                .map(|arc| {
+                    let reduced = costs[arc] - self.potential[network.tail[arc]]
+                        + self.potential[network.head[arc]];
+                    (arc, reduced)
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((entering, _)) = entering.filter(|(_, d)| *d < -PRECISION) else {
+                return true;
+            };
+            if self.pivots >= max_pivots {
+                return false;
+            }
+            let Some(cycle) = self.cycle(entering) else {
+                return false;
+            };
+            // Sans arc bloquant, le cycle de coût négatif n'est pas borné
+            if !self.pivot(entering, &cycle, capacitated) {
+                return false;
+            }
+            self.pivots += 1;
+        }
+    }
+
+    // Cycle formé par l'arc entrant u -> v, parcouru dans son sens à partir du sommet commun :
+    // de l'ancêtre vers u, l'arc entrant, puis de v vers l'ancêtre. Chaque arc de l'arbre est
+    // donné avec son sens de parcours (true si le flot augmente).
+    fn cycle(&self, entering: usize) -> Option<Vec<(usize, bool)>> {
+        let network = self.network;
+        let (mut u, mut v) = (network.tail[entering], network.head[entering]);
+        let mut down = vec![];
+        let mut up = vec![];
+        while u != v {
+            if self.depth[u] >= self.depth[v] {
+                let arc = self.pred[u];
+                // Parcouru du père vers u
+                down.push((arc, network.head[arc] == u));
+                u = self.parent[u];
+            } else {
+                let arc = self.pred[v];
+                // Parcouru de v vers son père
+                up.push((arc, network.tail[arc] == v));
+                v = self.parent[v];
+            }
+            if u == usize::MAX || v == usize::MAX {
+                return None;
+            }
+        }
+        down.reverse();
+        down.extend(up);
+        Some(down)
+    }
+
+    // Pousse le flot le long du cycle et fait sortir le dernier arc bloquant rencontré
+    // (règle de l'arbre fortement réalisable, qui évite le cyclage)
+    fn pivot(&mut self, entering: usize, cycle: &[(usize, bool)], capacitated: bool) -> bool {
+        let mut leaving: Option<(usize, f64)> = None;
+        for (arc, forward) in cycle {
+            let delta = if !forward {
+                self.flow[*arc].max(0.0)
+            } else if capacitated && self.artificial[*arc] {
+                0.0
+            } else {
+                continue;
+            };
+            if leaving.is_none_or(|(_, min)| delta <= min) {
+                leaving = Some((*arc, delta));
+            }
+        }
+        let Some((leaving, theta)) = leaving else {
+            return false;
+        };
+        for (arc, forward) in cycle {
+            if *forward {
+                self.flow[*arc] += theta;
+            } else {
+                self.flow[*arc] -= theta;
+            }
+        }
+        self.flow[entering] = theta;
+        self.flow[leaving] = 0.0;
+        self.in_tree[leaving] = false;
+        self.in_tree[entering] = true;
+        true
+    }
+}
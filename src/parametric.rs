@@ -0,0 +1,471 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::parse_lp_model;
+use crate::simplexef64::{
+    Basis, PRECISION, REFACTOR_FREQUENCY, Start, Status, get_solution, logical_columns,
+    parse_lp_two_phases, two_phases,
+};
+
+// Garde-fou contre le cyclage sur les points de rupture dégénérés
+const MAX_PIVOTS: usize = 10_000;
+
+// Données paramétrées : le second membre b + θ d ou les coûts c + θ d
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Parameter {
+    #[default]
+    Rhs,
+    Cost,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ParametricOptions {
+    pub parameter: Parameter,
+    // Direction d : "ligne:valeur,..." pour le second membre (lignes numérotées à partir de 0
+    // dans l'ordre du fichier), "variable:valeur,..." pour les coûts
+    pub direction: String,
+    pub theta_min: f64,
+    pub theta_max: f64,
+    // Rend aussi le graphe de z(θ) en SVG
+    pub svg: bool,
+}
+
+impl Default for ParametricOptions {
+    fn default() -> Self {
+        ParametricOptions {
+            parameter: Parameter::Rhs,
+            direction: String::new(),
+            theta_min: 0.0,
+            theta_max: 1.0,
+            svg: false,
+        }
+    }
+}
+
+// Intervalle de θ sur lequel z est linéaire. Un pivot dégénéré change la base optimale sans
+// changer la pente : les bases successives d'un même segment sont gardées.
+#[derive(Debug, Clone, Serialize)]
+pub struct Segment {
+    pub theta_start: f64,
+    pub theta_end: f64,
+    pub z_start: f64,
+    pub z_end: f64,
+    pub slope: f64,
+    // Variables de base de chaque base optimale sur le segment, dans l'ordre de θ, les
+    // écarts et les artificielles sont désignés par leur ligne
+    pub bases: Vec<Vec<String>>,
+    // Solution au début du segment
+    pub variables: Vec<(String, f64)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParametricResult {
+    pub segments: Vec<Segment>,
+    // (θ, z) aux extrémités des segments
+    pub breakpoints: Vec<(f64, f64)>,
+    // Raison de l'arrêt avant theta_max
+    pub stop: Option<String>,
+    pub svg: Option<String>,
+}
+
+fn parse_direction(direction: &str) -> Result<Vec<(String, f64)>, String> {
+    let direction = direction
+        .split(',')
+        .map(str::trim)
+        .filter(|term| !term.is_empty())
+        .map(|term| {
+            let (key, value) = term
+                .split_once(':')
+                .ok_or(format!("Terme de direction invalide : {}", term))?;
+            let value = value
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("Nombre invalide : {}", value))?;
+            Ok((key.trim().to_string(), value))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if direction.is_empty() {
+        return Err("La direction est vide".to_string());
+    }
+    Ok(direction)
+}
+
+fn parse_row(row: &str, rows: usize) -> Result<usize, String> {
+    row.parse::<usize>()
+        .ok()
+        .filter(|i| *i < rows)
+        .ok_or(format!("Ligne inconnue : {}", row))
+}
+
+// Programmation paramétrique : le programme est résolu en theta_min, puis θ augmente tant
+// que la base reste optimale. Au point de rupture, une variable de base s'annule (second
+// membre, pivot du simplexe dual) ou un coût réduit change de signe (coûts, pivot du
+// simplexe primal), et la nouvelle base donne le segment suivant.
+pub fn parametric(file: &str, options: &ParametricOptions) -> Result<ParametricResult, String> {
+    if options.theta_max < options.theta_min {
+        return Err("theta_max doit être supérieur à theta_min".to_string());
+    }
+    let mut model = parse_lp_model(file)?;
+    if !model.quadratic.is_empty() {
+        return Err(
+            "La programmation paramétrique ne traite pas les objectifs quadratiques".to_string(),
+        );
+    }
+    if !model.objectives.is_empty() {
+        return Err("La programmation paramétrique ne traite qu'un seul objectif".to_string());
+    }
+    let direction = parse_direction(&options.direction)?;
+    for (key, d) in &direction {
+        match options.parameter {
+            Parameter::Rhs => {
+                let i = parse_row(key, model.rows.len())?;
+                model.rows[i].rhs += options.theta_min * d;
+            }
+            Parameter::Cost => {
+                let j = model
+                    .index
+                    .get(key)
+                    .ok_or(format!("Variable inconnue : {}", key))?;
+                model.objective[*j] += options.theta_min * d;
+            }
+        }
+    }
+    let (matrix, variables, is_min, vars_hash_map, original_cost) =
+        parse_lp_two_phases(&model.to_lp_string())?;
+    let width = matrix.columns();
+    let logicals = logical_columns(&variables, matrix.rows);
+    let mut costs = vec![0.0; width];
+    for (name, index) in &vars_hash_map {
+        costs[variables[*index].column] = original_cost[name];
+    }
+    // Direction dans le tableau : les lignes de second membre négatif ont été retournées
+    // à la lecture et les coûts sont à maximiser
+    let mut direction_rhs = vec![0.0; matrix.rows];
+    let mut direction_costs = vec![0.0; width];
+    for (key, d) in &direction {
+        match options.parameter {
+            Parameter::Rhs => {
+                let i = parse_row(key, model.rows.len())?;
+                let flip = if model.rows[i].rhs < 0.0 { -1.0 } else { 1.0 };
+                direction_rhs[i] += flip * d;
+            }
+            Parameter::Cost => {
+                let index = vars_hash_map
+                    .get(key)
+                    .ok_or(format!("Variable inconnue : {}", key))?;
+                direction_costs[variables[*index].column] += is_min * d;
+            }
+        }
+    }
+    let (status, mut basis) = two_phases(&matrix, &variables, &logicals, &costs, Start::Logical);
+    match status {
+        Status::Infeasible => {
+            return Err(format!(
+                "Le problème est infaisable pour theta = {}",
+                options.theta_min
+            ));
+        }
+        Status::Unbounded => {
+            return Err(format!(
+                "Le problème est non borné pour theta = {}",
+                options.theta_min
+            ));
+        }
+        Status::Optimal => {}
+    }
+    let mut is_artificial = vec![false; width];
+    let mut names = vec![String::new(); width];
+    for (name, index) in &vars_hash_map {
+        let var = &variables[*index];
+        is_artificial[var.column] = var.is_artificial;
+        let row = matrix.column(var.column).next().map_or(0, |(i, _)| i);
+        names[var.column] = if var.is_slack {
+            format!("slack{}", row)
+        } else if var.is_artificial {
+            format!("artificial{}", row)
+        } else {
+            name.clone()
+        };
+    }
+
+    let b = matrix.dense_column(0);
+    let mut theta = options.theta_min;
+    let mut segments: Vec<Segment> = vec![];
+    let mut stop = None;
+    for pivots in 0.. {
+        if pivots == MAX_PIVOTS {
+            stop = Some(format!(
+                "Trop de points de rupture dégénérés en theta = {}",
+                theta
+            ));
+            break;
+        }
+        let shift = theta - options.theta_min;
+        let current_costs = costs
+            .iter()
+            .zip(&direction_costs)
+            .map(|(c, d)| c + shift * d)
+            .collect::<Vec<_>>();
+        if options.parameter == Parameter::Rhs {
+            let mut values = b
+                .iter()
+                .zip(&direction_rhs)
+                .map(|(b, d)| b + shift * d)
+                .collect::<Vec<_>>();
+            basis.inverse.ftran(&mut values);
+            basis.values = values;
+        }
+        let mut in_base = vec![false; width];
+        for j in &basis.heads {
+            in_base[*j] = true;
+        }
+        let z = basis
+            .heads
+            .iter()
+            .zip(&basis.values)
+            .map(|(j, x)| current_costs[*j] * x)
+            .sum::<f64>();
+        let y = basis.duals(&current_costs);
+        let reduced = |j: usize| matrix.dot_column(j, &y) - current_costs[j];
+        let candidates = (1..width).filter(|j| !in_base[*j] && !is_artificial[*j]);
+
+        // Pas jusqu'au prochain point de rupture, variation de z par unité de θ
+        // et position (second membre) ou colonne (coûts) du pivot
+        let (step, slope, pivot, delta) = match options.parameter {
+            Parameter::Rhs => {
+                let mut delta = direction_rhs.clone();
+                basis.inverse.ftran(&mut delta);
+                let slope = basis
+                    .heads
+                    .iter()
+                    .zip(&delta)
+                    .map(|(j, d)| current_costs[*j] * d)
+                    .sum::<f64>();
+                // Une artificielle de base doit rester nulle
+                let leaving = (0..delta.len())
+                    .filter_map(|p| {
+                        if is_artificial[basis.heads[p]] && delta[p].abs() > PRECISION {
+                            Some((p, 0.0))
+                        } else if delta[p] < -PRECISION {
+                            Some((p, basis.values[p].max(0.0) / -delta[p]))
+                        } else {
+                            None
+                        }
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                let step = leaving.map_or(f64::INFINITY, |(_, t)| t);
+                (step, slope, leaving.map(|(p, _)| p), delta)
+            }
+            Parameter::Cost => {
+                let yd = basis.duals(&direction_costs);
+                let slope = basis
+                    .heads
+                    .iter()
+                    .zip(&basis.values)
+                    .map(|(j, x)| direction_costs[*j] * x)
+                    .sum::<f64>();
+                let entering = candidates
+                    .clone()
+                    .filter_map(|j| {
+                        let s = matrix.dot_column(j, &yd) - direction_costs[j];
+                        (s < -PRECISION).then(|| (j, reduced(j).max(0.0) / -s))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                let step = entering.map_or(f64::INFINITY, |(_, t)| t);
+                (step, slope, entering.map(|(j, _)| j), vec![])
+            }
+        };
+        let end = (theta + step).min(options.theta_max);
+        // Les segments de longueur nulle (pivots dégénérés) ne sont pas gardés, sauf s'il
+        // n'y en aura pas d'autre
+        let last = pivot.is_none() || end >= options.theta_max;
+        if end > theta || segments.is_empty() && last {
+            let heads = basis.heads.iter().map(|j| names[*j].clone()).collect();
+            let z_end = is_min * (z + slope * (end - theta));
+            match segments.last_mut() {
+                // Même pente qu'au segment précédent : θ n'est pas un point de rupture
+                Some(previous)
+                    if (previous.slope - is_min * slope).abs()
+                        <= PRECISION * slope.abs().max(1.0) =>
+                {
+                    previous.theta_end = end;
+                    previous.z_end = z_end;
+                    previous.bases.push(heads);
+                }
+                _ => {
+                    let values = basis.column_values(width);
+                    let (variables, _) = get_solution(&values, &variables, &vars_hash_map, is_min)?;
+                    segments.push(Segment {
+                        theta_start: theta,
+                        theta_end: end,
+                        z_start: is_min * z,
+                        z_end,
+                        slope: is_min * slope,
+                        bases: vec![heads],
+                        variables,
+                    });
+                }
+            }
+        }
+        let Some(pivot) = pivot.filter(|_| end < options.theta_max) else {
+            break;
+        };
+        match options.parameter {
+            Parameter::Rhs => {
+                let p = pivot;
+                for (x, d) in basis.values.iter_mut().zip(&delta) {
+                    *x += step * d;
+                }
+                basis.values[p] = 0.0;
+                // Simplexe dual : x_p quitte la base, la colonne entrante garde les coûts
+                // réduits positifs (plus petit rapport d_j / |ρ_pj|)
+                let sign = if delta[p] < 0.0 { 1.0 } else { -1.0 };
+                let row = basis.row(&matrix, p);
+                let entering = candidates
+                    .filter(|j| sign * row[*j] < -PRECISION)
+                    .map(|j| (j, reduced(j).max(0.0) / row[j].abs()))
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                let Some((j, _)) = entering else {
+                    stop = Some(format!("Le problème est infaisable pour theta > {}", end));
+                    break;
+                };
+                let alpha = basis.column(&matrix, j);
+                basis.pivot(p, j, &alpha);
+            }
+            Parameter::Cost => {
+                let j = pivot;
+                let alpha = basis.column(&matrix, j);
+                let leaving = (0..alpha.len())
+                    .filter(|p| alpha[*p] > PRECISION)
+                    .map(|p| (p, basis.values[p].max(0.0) / alpha[p]))
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                let Some((p, _)) = leaving else {
+                    stop = Some(format!("Le problème est non borné pour theta > {}", end));
+                    break;
+                };
+                basis.pivot(p, j, &alpha);
+            }
+        }
+        if basis.inverse.len() >= REFACTOR_FREQUENCY {
+            basis = Basis::factor(&matrix, &basis.heads, &logicals);
+        }
+        theta = end;
+    }
+    let mut breakpoints = vec![(segments[0].theta_start, segments[0].z_start)];
+    breakpoints.extend(segments.iter().map(|s| (s.theta_end, s.z_end)));
+    let svg = options.svg.then(|| svg_plot(&breakpoints));
+    Ok(ParametricResult {
+        segments,
+        breakpoints,
+        stop,
+        svg,
+    })
+}
+
+// Graphe de z(θ) : ligne brisée passant par les points de rupture
+pub fn svg_plot(breakpoints: &[(f64, f64)]) -> String {
+    let (width, height, margin) = (640.0, 400.0, 60.0);
+    let range = |values: Vec<f64>| {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if max > min {
+            (min, max)
+        } else {
+            (min - 1.0, max + 1.0)
+        }
+    };
+    let (theta_min, theta_max) = range(breakpoints.iter().map(|p| p.0).collect());
+    let (z_min, z_max) = range(breakpoints.iter().map(|p| p.1).collect());
+    let x = |theta: f64| {
+        margin + (theta - theta_min) / (theta_max - theta_min) * (width - 2.0 * margin)
+    };
+    let y = |z: f64| height - margin - (z - z_min) / (z_max - z_min) * (height - 2.0 * margin);
+    let points = breakpoints
+        .iter()
+        .map(|(theta, z)| format!("{:.2},{:.2}", x(*theta), y(*z)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n\
+         <line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"black\"/>\n\
+         <line x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{b}\" stroke=\"black\"/>\n\
+         <polyline points=\"{points}\" fill=\"none\" stroke=\"steelblue\" stroke-width=\"2\"/>\n",
+        w = width,
+        h = height,
+        m = margin,
+        b = height - margin,
+        r = width - margin,
+        points = points,
+    );
+    for (theta, z) in breakpoints {
+        svg.push_str(&format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"3\" fill=\"steelblue\"/>\n",
+            x(*theta),
+            y(*z)
+        ));
+    }
+    svg.push_str(&format!(
+        "<text x=\"{m}\" y=\"{ty}\" font-size=\"12\">{:.4}</text>\n\
+         <text x=\"{r}\" y=\"{ty}\" font-size=\"12\" text-anchor=\"end\">{:.4}</text>\n\
+         <text x=\"{lx}\" y=\"{b}\" font-size=\"12\" text-anchor=\"end\">{:.4}</text>\n\
+         <text x=\"{lx}\" y=\"{top}\" font-size=\"12\" text-anchor=\"end\">{:.4}</text>\n\
+         <text x=\"{cx}\" y=\"{ly}\" font-size=\"14\" text-anchor=\"middle\">theta</text>\n\
+         <text x=\"15\" y=\"{cy}\" font-size=\"14\">z</text>\n</svg>\n",
+        theta_min,
+        theta_max,
+        z_min,
+        z_max,
+        m = margin,
+        r = width - margin,
+        b = height - margin,
+        ty = height - margin + 18.0,
+        lx = margin - 6.0,
+        top = margin,
+        cx = width / 2.0,
+        ly = height - 15.0,
+        cy = height / 2.0,
+    ));
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < PRECISION,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    // (3, 1) est un sommet dégénéré : en θ = 1 la base change sans changer la pente, seul
+    // θ = 7 est un point de rupture
+    #[test]
+    fn degenerate_pivot_is_not_a_breakpoint() {
+        let file = "max: 3x + 2y;\nx + y <= 4;\nx + 3y <= 6;\nx <= 3;\n";
+        let options = ParametricOptions {
+            parameter: Parameter::Cost,
+            direction: "y:1".to_string(),
+            theta_min: 0.0,
+            theta_max: 10.0,
+            svg: false,
+        };
+        let result = parametric(file, &options).unwrap();
+        let expected = [(0.0, 11.0), (7.0, 18.0), (10.0, 24.0)];
+        assert_eq!(result.breakpoints.len(), expected.len());
+        for ((theta, z), (t, e)) in result.breakpoints.iter().zip(expected) {
+            assert_close(*theta, t);
+            assert_close(*z, e);
+        }
+        assert_eq!(result.segments.len(), 2);
+        assert_close(result.segments[0].slope, 1.0);
+        assert_close(result.segments[1].slope, 2.0);
+        assert_eq!(result.segments[0].bases.len(), 2);
+        assert_eq!(result.segments[1].bases.len(), 1);
+    }
+}
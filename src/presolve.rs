@@ -0,0 +1,1505 @@
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{LpModel, Row, Sense};
+
+// Zéro numérique des coefficients
+const ZERO: f64 = 1.0e-12;
+// Tolérance de faisabilité, relative au second membre
+const TOLERANCE: f64 = 1.0e-9;
+const MAX_PASSES: usize = 20;
+// Pivot relatif minimal pour éliminer une variable d'une égalité à deux variables
+const MIN_PIVOT: f64 = 1.0e-3;
+// Arrondi des bornes des variables entières
+const INTEGER_TOLERANCE: f64 = 1.0e-6;
+// Amélioration relative minimale d'une borne continue pendant la propagation
+const MIN_BOUND_IMPROVEMENT: f64 = 1.0e-3;
+// Coefficients lus au plus par la propagation de chaque passe et par le probing
+const PROPAGATION_WORK: usize = 5_000_000;
+const PROBING_WORK: usize = 20_000_000;
+// Longueur maximale des lignes dont on tire les arêtes du graphe de conflits
+const MAX_CLIQUE_ROW: usize = 100;
+pub(crate) const INFEASIBLE: &str = "Presolve : le problème est infaisable";
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PresolveStatistics {
+    pub rows_before: usize,
+    pub columns_before: usize,
+    pub rows_after: usize,
+    pub columns_after: usize,
+    pub passes: usize,
+    pub empty_rows: usize,
+    pub singleton_rows: usize,
+    pub redundant_rows: usize,
+    pub forcing_rows: usize,
+    pub doubleton_equations: usize,
+    pub duplicate_rows: usize,
+    pub fixed_columns: usize,
+    pub dominated_columns: usize,
+    pub duplicate_columns: usize,
+    // Réductions propres aux problèmes en nombres entiers
+    pub tightened_bounds: usize,
+    pub tightened_coefficients: usize,
+    pub probed_binaries: usize,
+    pub probing_fixings: usize,
+    pub implications: usize,
+    pub clique_rows: usize,
+    pub dominated_rows: usize,
+}
+
+// Coût et coefficients d'une colonne dans les lignes actives au moment d'une réduction
+#[derive(Debug, Clone)]
+struct ColumnSnapshot {
+    cost: f64,
+    entries: Vec<(usize, f64)>,
+}
+
+impl ColumnSnapshot {
+    // Coût réduit de la colonne pour les duales courantes
+    fn reduced_cost(&self, duals: &[f64]) -> f64 {
+        self.cost - self.entries.iter().map(|(i, a)| a * duals[*i]).sum::<f64>()
+    }
+}
+
+// Réductions dans l'ordre où elles ont été faites, défaites en sens inverse par le postsolve
+#[derive(Debug, Clone)]
+enum Step {
+    // Ligne vide, redondante ou dupliquée moins serrée : duale nulle
+    RemovedRow {
+        row: usize,
+    },
+    // Ligne a x_j (sens) b transformée en borne de x_j
+    SingletonRow {
+        row: usize,
+        col: usize,
+        coeff: f64,
+        // Bornes de x_j avant et après la réduction
+        previous: (f64, f64),
+        bounds: (f64, f64),
+        column: ColumnSnapshot,
+    },
+    FixedColumn {
+        col: usize,
+        value: f64,
+    },
+    // Ligne dont l'activité minimale (ou maximale) atteint le second membre :
+    // ses variables sont fixées à la borne correspondante
+    ForcingRow {
+        row: usize,
+        sense: Sense,
+        at_min: bool,
+        columns: Vec<(f64, ColumnSnapshot)>,
+    },
+    // a x_kept + b x_removed = rhs, x_removed est substituée dans les autres lignes
+    DoubletonEquation {
+        row: usize,
+        kept: usize,
+        removed: usize,
+        kept_coeff: f64,
+        removed_coeff: f64,
+        rhs: f64,
+        removed_bounds: (f64, f64),
+        kept_column: ColumnSnapshot,
+        removed_column: ColumnSnapshot,
+    },
+    // Deux inégalités opposées de même second membre fusionnées en égalité (removed = ratio * kept)
+    DuplicateRow {
+        kept: usize,
+        removed: usize,
+        ratio: f64,
+        sense: Sense,
+    },
+}
+
+// Problème réduit et pile de postsolve
+pub struct Presolved {
+    pub reduced: LpModel,
+    pub statistics: PresolveStatistics,
+    is_min: f64,
+    width: usize,
+    height: usize,
+    // Lignes de cliques ajoutées après les lignes d'origine
+    added_rows: usize,
+    // Colonnes et lignes d'origine du problème réduit, dans son ordre
+    columns: Vec<usize>,
+    rows: Vec<usize>,
+    steps: Vec<Step>,
+}
+
+struct Presolver {
+    // Réductions entières : propagation, coefficients, probing et cliques
+    mip: bool,
+    is_min: f64,
+    objective: Vec<f64>,
+    rows: Vec<Row>,
+    integers: Vec<bool>,
+    row_alive: Vec<bool>,
+    col_alive: Vec<bool>,
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    // Lignes où chaque colonne a pu apparaître (filtrées à la lecture)
+    columns: Vec<Vec<usize>>,
+    // x_j = 1 implique x_k = 0, entre binaires, trouvées par le probing
+    implications: Vec<(usize, usize)>,
+    steps: Vec<Step>,
+    statistics: PresolveStatistics,
+}
+
+// Activité minimale et maximale d'une ligne : partie finie et nombre de termes infinis
+struct Activity {
+    min: f64,
+    min_infinite: usize,
+    max: f64,
+    max_infinite: usize,
+}
+
+impl Activity {
+    fn new(row: &Row, lower: &[f64], upper: &[f64]) -> Self {
+        let mut activity = Activity {
+            min: 0.0,
+            min_infinite: 0,
+            max: 0.0,
+            max_infinite: 0,
+        };
+        for (j, a) in &row.coeffs {
+            let (low, high) = if *a > 0.0 {
+                (a * lower[*j], a * upper[*j])
+            } else {
+                (a * upper[*j], a * lower[*j])
+            };
+            if low.is_finite() {
+                activity.min += low;
+            } else {
+                activity.min_infinite += 1;
+            }
+            if high.is_finite() {
+                activity.max += high;
+            } else {
+                activity.max_infinite += 1;
+            }
+        }
+        activity
+    }
+
+    // Bornes de x_j impliquées par la ligne, les autres variables restant dans leurs bornes
+    fn implied_bounds(
+        &self,
+        row: &Row,
+        j: usize,
+        a: f64,
+        lower: &[f64],
+        upper: &[f64],
+    ) -> (f64, f64) {
+        let (low, high) = if a > 0.0 {
+            (a * lower[j], a * upper[j])
+        } else {
+            (a * upper[j], a * lower[j])
+        };
+        let residual = |sum: f64, infinite: usize, own: f64, unbounded: f64| {
+            let infinite = infinite - usize::from(!own.is_finite());
+            if infinite > 0 {
+                unbounded
+            } else if own.is_finite() {
+                sum - own
+            } else {
+                sum
+            }
+        };
+        let min = residual(self.min, self.min_infinite, low, f64::NEG_INFINITY);
+        let max = residual(self.max, self.max_infinite, high, f64::INFINITY);
+        // a x_j <= rhs - min et a x_j >= rhs - max
+        let (mut lower, mut upper) = (f64::NEG_INFINITY, f64::INFINITY);
+        if row.sense != Sense::Geq {
+            let bound = (row.rhs - min) / a;
+            if a > 0.0 {
+                upper = bound
+            } else {
+                lower = bound
+            }
+        }
+        if row.sense != Sense::Leq {
+            let bound = (row.rhs - max) / a;
+            if a > 0.0 {
+                lower = bound
+            } else {
+                upper = bound
+            }
+        }
+        (lower, upper)
+    }
+}
+
+// Indices et coefficients normalisés d'une ligne ou d'une colonne
+type ParallelKey = Vec<(usize, i64)>;
+
+#[inline(always)]
+fn tolerance(rhs: f64) -> f64 {
+    TOLERANCE * (1.0 + rhs.abs())
+}
+
+impl Presolver {
+    fn new(model: &LpModel, mip: bool) -> Self {
+        let mut columns = vec![vec![]; model.names.len()];
+        let mut rows = model.rows.clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.coeffs.retain(|(_, a)| a.abs() > ZERO);
+            for (j, _) in &row.coeffs {
+                columns[*j].push(i);
+            }
+        }
+        Presolver {
+            mip,
+            is_min: model.is_min,
+            objective: model.objective.clone(),
+            row_alive: vec![true; rows.len()],
+            rows,
+            integers: model.integers.clone(),
+            col_alive: vec![true; model.names.len()],
+            lower: vec![0.0; model.names.len()],
+            upper: vec![f64::INFINITY; model.names.len()],
+            columns,
+            implications: vec![],
+            steps: vec![],
+            statistics: PresolveStatistics {
+                rows_before: model.rows.len(),
+                columns_before: model.names.len(),
+                ..Default::default()
+            },
+        }
+    }
+
+    // Coefficients de la colonne j dans les lignes actives
+    fn entries(&self, j: usize) -> Vec<(usize, f64)> {
+        let mut rows = self.columns[j]
+            .iter()
+            .copied()
+            .filter(|i| self.row_alive[*i])
+            .collect::<Vec<_>>();
+        rows.sort();
+        rows.dedup();
+        rows.into_iter()
+            .filter_map(|i| {
+                self.rows[i]
+                    .coeffs
+                    .iter()
+                    .find(|(k, _)| *k == j)
+                    .map(|(_, a)| (i, *a))
+            })
+            .collect()
+    }
+
+    fn snapshot(&self, j: usize, without: usize) -> ColumnSnapshot {
+        ColumnSnapshot {
+            cost: self.objective[j],
+            entries: self
+                .entries(j)
+                .into_iter()
+                .filter(|(i, _)| *i != without)
+                .collect(),
+        }
+    }
+
+    // Activités minimale et maximale d'une ligne sur les bornes des variables
+    fn activity_bounds(&self, row: &Row) -> (f64, f64) {
+        row.coeffs.iter().fold((0.0, 0.0), |(min, max), (j, a)| {
+            if *a > 0.0 {
+                (min + a * self.lower[*j], max + a * self.upper[*j])
+            } else {
+                (min + a * self.upper[*j], max + a * self.lower[*j])
+            }
+        })
+    }
+
+    fn remove_row(&mut self, i: usize) {
+        self.row_alive[i] = false;
+        self.steps.push(Step::RemovedRow { row: i });
+    }
+
+    // Fixe x_j et reporte sa valeur dans les seconds membres
+    fn fix_column(&mut self, j: usize, value: f64) {
+        for (i, a) in self.entries(j) {
+            self.rows[i].rhs -= a * value;
+            self.rows[i].coeffs.retain(|(k, _)| *k != j);
+        }
+        self.col_alive[j] = false;
+        self.lower[j] = value;
+        self.upper[j] = value;
+        self.steps.push(Step::FixedColumn { col: j, value });
+    }
+
+    fn set_bounds(&mut self, j: usize, lower: f64, upper: f64) -> Result<(), String> {
+        let (lower, upper) = self.rounded(j, lower, upper);
+        self.lower[j] = self.lower[j].max(lower);
+        self.upper[j] = self.upper[j].min(upper);
+        if self.lower[j] > self.upper[j] + tolerance(self.upper[j]) {
+            return Err(INFEASIBLE.to_string());
+        }
+        Ok(())
+    }
+
+    // Les bornes des variables entières sont arrondies vers l'intérieur
+    fn rounded(&self, j: usize, lower: f64, upper: f64) -> (f64, f64) {
+        if self.integers[j] {
+            (
+                (lower - INTEGER_TOLERANCE).ceil(),
+                (upper + INTEGER_TOLERANCE).floor(),
+            )
+        } else {
+            (lower, upper)
+        }
+    }
+
+    fn is_binary(&self, j: usize) -> bool {
+        self.col_alive[j] && self.integers[j] && self.lower[j] == 0.0 && self.upper[j] == 1.0
+    }
+
+    // Lignes vides et lignes singleton
+    fn row_singletons(&mut self) -> Result<bool, String> {
+        let mut changed = false;
+        for i in 0..self.rows.len() {
+            if !self.row_alive[i] || self.rows[i].coeffs.len() > 1 {
+                continue;
+            }
+            let Row { coeffs, sense, rhs } = self.rows[i].clone();
+            changed = true;
+            let Some((j, a)) = coeffs.first().copied() else {
+                let feasible = match sense {
+                    Sense::Leq => 0.0 <= rhs + tolerance(rhs),
+                    Sense::Geq => 0.0 >= rhs - tolerance(rhs),
+                    Sense::Eq => rhs.abs() <= tolerance(rhs),
+                };
+                if !feasible {
+                    return Err(INFEASIBLE.to_string());
+                }
+                self.statistics.empty_rows += 1;
+                self.remove_row(i);
+                continue;
+            };
+            let value = rhs / a;
+            let (lower, upper) = match (sense, a > 0.0) {
+                (Sense::Eq, _) => (value, value),
+                (Sense::Leq, true) | (Sense::Geq, false) => (f64::NEG_INFINITY, value),
+                (Sense::Leq, false) | (Sense::Geq, true) => (value, f64::INFINITY),
+            };
+            let previous = (self.lower[j], self.upper[j]);
+            let column = self.snapshot(j, i);
+            self.set_bounds(j, lower, upper)?;
+            self.row_alive[i] = false;
+            self.statistics.singleton_rows += 1;
+            self.steps.push(Step::SingletonRow {
+                row: i,
+                col: j,
+                coeff: a,
+                previous,
+                bounds: (self.lower[j], self.upper[j]),
+                column,
+            });
+        }
+        Ok(changed)
+    }
+
+    fn fixed_columns(&mut self) -> bool {
+        let mut changed = false;
+        for j in 0..self.col_alive.len() {
+            if self.col_alive[j] && self.upper[j] - self.lower[j] <= tolerance(self.lower[j]) {
+                self.fix_column(j, self.lower[j]);
+                self.statistics.fixed_columns += 1;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // Lignes infaisables, redondantes ou forçantes d'après les bornes des variables
+    fn activity_rows(&mut self) -> Result<bool, String> {
+        let mut changed = false;
+        for i in 0..self.rows.len() {
+            if !self.row_alive[i] {
+                continue;
+            }
+            let (min, max) = self.activity_bounds(&self.rows[i]);
+            let Row { coeffs, sense, rhs } = self.rows[i].clone();
+            let tol = tolerance(rhs);
+            let (infeasible, redundant, at_min, at_max) = match sense {
+                Sense::Leq => (min > rhs + tol, max <= rhs + tol, min >= rhs - tol, false),
+                Sense::Geq => (max < rhs - tol, min >= rhs - tol, false, max <= rhs + tol),
+                Sense::Eq => (
+                    min > rhs + tol || max < rhs - tol,
+                    false,
+                    min >= rhs - tol,
+                    max <= rhs + tol,
+                ),
+            };
+            if infeasible {
+                return Err(INFEASIBLE.to_string());
+            }
+            if redundant {
+                self.statistics.redundant_rows += 1;
+                self.remove_row(i);
+                changed = true;
+            } else if at_min || at_max {
+                // Chaque variable est à la borne qui minimise (ou maximise) l'activité
+                let values = coeffs
+                    .iter()
+                    .map(|(j, a)| {
+                        if (*a > 0.0) == at_min {
+                            self.lower[*j]
+                        } else {
+                            self.upper[*j]
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if values.iter().any(|x| !x.is_finite()) {
+                    continue;
+                }
+                let columns = coeffs
+                    .iter()
+                    .map(|(j, a)| (*a, self.snapshot(*j, i)))
+                    .collect();
+                self.row_alive[i] = false;
+                self.steps.push(Step::ForcingRow {
+                    row: i,
+                    sense,
+                    at_min,
+                    columns,
+                });
+                for ((j, _), value) in coeffs.iter().zip(values) {
+                    self.fix_column(*j, value);
+                }
+                self.statistics.forcing_rows += 1;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    // Colonnes vides ou dominées : aucune ligne n'empêche de déplacer la variable
+    // dans le sens qui améliore l'objectif, elle est fixée à la borne correspondante
+    fn dominated_columns(&mut self) -> Result<bool, String> {
+        let mut changed = false;
+        for j in 0..self.col_alive.len() {
+            if !self.col_alive[j] {
+                continue;
+            }
+            let (mut down_locks, mut up_locks) = (0, 0);
+            for (i, a) in self.entries(j) {
+                match (self.rows[i].sense, a > 0.0) {
+                    (Sense::Eq, _) => {
+                        down_locks += 1;
+                        up_locks += 1;
+                    }
+                    (Sense::Leq, true) | (Sense::Geq, false) => up_locks += 1,
+                    (Sense::Leq, false) | (Sense::Geq, true) => down_locks += 1,
+                }
+            }
+            // Coût dans le sens de la maximisation
+            let cost = self.is_min * self.objective[j];
+            if cost <= 0.0 && down_locks == 0 {
+                self.fix_column(j, self.lower[j]);
+            } else if cost > 0.0 && up_locks == 0 {
+                if !self.upper[j].is_finite() {
+                    return Err("Presolve : le problème est non borné".to_string());
+                }
+                self.fix_column(j, self.upper[j]);
+            } else {
+                continue;
+            }
+            self.statistics.dominated_columns += 1;
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    // Egalités à deux variables : la variable continue de plus grand coefficient est substituée
+    fn doubleton_equations(&mut self) -> Result<bool, String> {
+        let mut changed = false;
+        for e in 0..self.rows.len() {
+            if !self.row_alive[e]
+                || self.rows[e].sense != Sense::Eq
+                || self.rows[e].coeffs.len() != 2
+            {
+                continue;
+            }
+            let Row { coeffs, rhs, .. } = self.rows[e].clone();
+            let (mut first, mut second) = (coeffs[0], coeffs[1]);
+            if second.1.abs() > first.1.abs() {
+                std::mem::swap(&mut first, &mut second);
+            }
+            // (gardée, supprimée)
+            let ((j, a), (k, b)) = if !self.integers[first.0] {
+                (second, first)
+            } else if !self.integers[second.0] {
+                (first, second)
+            } else {
+                continue;
+            };
+            if b.abs() < MIN_PIVOT * a.abs() {
+                continue;
+            }
+            let kept_column = self.snapshot(j, e);
+            let removed_column = self.snapshot(k, e);
+            // Les bornes de x_k deviennent des bornes de x_j = (rhs - b x_k) / a
+            let image = |x: f64| {
+                if x.is_finite() {
+                    (rhs - b * x) / a
+                } else if -b / a * x > 0.0 {
+                    f64::INFINITY
+                } else {
+                    f64::NEG_INFINITY
+                }
+            };
+            let (low, high) = (image(self.lower[k]), image(self.upper[k]));
+            self.set_bounds(j, low.min(high), low.max(high))?;
+            for (i, c) in &removed_column.entries {
+                let row = &mut self.rows[*i];
+                row.coeffs.retain(|(l, _)| *l != k);
+                row.rhs -= c * rhs / b;
+                match row.coeffs.iter_mut().find(|(l, _)| *l == j) {
+                    Some((_, coeff)) => *coeff -= c * a / b,
+                    None => {
+                        row.coeffs.push((j, -c * a / b));
+                        self.columns[j].push(*i);
+                    }
+                }
+                row.coeffs.retain(|(_, coeff)| coeff.abs() > ZERO);
+            }
+            self.objective[j] -= self.objective[k] * a / b;
+            self.steps.push(Step::DoubletonEquation {
+                row: e,
+                kept: j,
+                removed: k,
+                kept_coeff: a,
+                removed_coeff: b,
+                rhs,
+                removed_bounds: (self.lower[k], self.upper[k]),
+                kept_column,
+                removed_column,
+            });
+            self.row_alive[e] = false;
+            self.col_alive[k] = false;
+            self.statistics.doubleton_equations += 1;
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    // Vecteur normalisé par le premier coefficient, pour repérer les lignes ou colonnes parallèles
+    fn parallel_key(entries: &[(usize, f64)]) -> ParallelKey {
+        let first = entries[0].1;
+        entries
+            .iter()
+            .map(|(k, a)| (*k, (a / first * 1.0e9).round() as i64))
+            .collect()
+    }
+
+    fn duplicate_rows(&mut self) -> Result<bool, String> {
+        let mut changed = false;
+        let mut seen: HashMap<ParallelKey, usize> = HashMap::new();
+        for i in 0..self.rows.len() {
+            if !self.row_alive[i] || self.rows[i].coeffs.len() < 2 {
+                continue;
+            }
+            self.rows[i].coeffs.sort_by_key(|(j, _)| *j);
+            let key = Self::parallel_key(&self.rows[i].coeffs);
+            let Some(&kept) = seen.get(&key) else {
+                seen.insert(key, i);
+                continue;
+            };
+            // Ligne i ramenée à l'échelle de la ligne gardée : i = ratio * kept
+            let ratio = self.rows[i].coeffs[0].1 / self.rows[kept].coeffs[0].1;
+            let rhs = self.rows[i].rhs / ratio;
+            let sense = match (self.rows[i].sense, ratio > 0.0) {
+                (Sense::Leq, false) => Sense::Geq,
+                (Sense::Geq, false) => Sense::Leq,
+                (sense, _) => sense,
+            };
+            let kept_rhs = self.rows[kept].rhs;
+            let tol = tolerance(kept_rhs.abs().max(rhs.abs()));
+            let reduced = match (self.rows[kept].sense, sense) {
+                (Sense::Eq, Sense::Eq) if (kept_rhs - rhs).abs() > tol => {
+                    return Err(INFEASIBLE.to_string());
+                }
+                (Sense::Eq, Sense::Leq) if kept_rhs > rhs + tol => {
+                    return Err(INFEASIBLE.to_string());
+                }
+                (Sense::Eq, Sense::Geq) if kept_rhs < rhs - tol => {
+                    return Err(INFEASIBLE.to_string());
+                }
+                (Sense::Eq, _) => {
+                    self.remove_row(i);
+                    true
+                }
+                (Sense::Leq, Sense::Eq) if rhs > kept_rhs + tol => {
+                    return Err(INFEASIBLE.to_string());
+                }
+                (Sense::Geq, Sense::Eq) if rhs < kept_rhs - tol => {
+                    return Err(INFEASIBLE.to_string());
+                }
+                // L'égalité devient la ligne gardée
+                (_, Sense::Eq) => {
+                    seen.insert(key, i);
+                    self.remove_row(kept);
+                    true
+                }
+                (Sense::Leq, Sense::Leq) | (Sense::Geq, Sense::Geq) => {
+                    let tighter = if sense == Sense::Leq {
+                        rhs < kept_rhs
+                    } else {
+                        rhs > kept_rhs
+                    };
+                    if tighter {
+                        seen.insert(key, i);
+                        self.remove_row(kept);
+                    } else {
+                        self.remove_row(i);
+                    }
+                    true
+                }
+                // Inégalités opposées : fusion en égalité si les seconds membres coïncident
+                (kept_sense, _) => {
+                    let (lower, upper) = if kept_sense == Sense::Leq {
+                        (rhs, kept_rhs)
+                    } else {
+                        (kept_rhs, rhs)
+                    };
+                    if lower > upper + tol {
+                        return Err(INFEASIBLE.to_string());
+                    }
+                    if upper - lower > tol {
+                        false
+                    } else {
+                        self.rows[kept].sense = Sense::Eq;
+                        self.row_alive[i] = false;
+                        self.steps.push(Step::DuplicateRow {
+                            kept,
+                            removed: i,
+                            ratio,
+                            sense: kept_sense,
+                        });
+                        true
+                    }
+                }
+            };
+            if reduced {
+                self.statistics.duplicate_rows += 1;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    // Colonnes parallèles positives et continues sur [0, +inf[ : la moins bonne est fixée à 0,
+    // à coût égal (au facteur près) l'une des deux suffit
+    fn duplicate_columns(&mut self) -> bool {
+        let mut changed = false;
+        let mut seen: HashMap<ParallelKey, (usize, Vec<(usize, f64)>)> = HashMap::new();
+        for j in 0..self.col_alive.len() {
+            if !self.col_alive[j]
+                || self.integers[j]
+                || self.lower[j] != 0.0
+                || self.upper[j].is_finite()
+            {
+                continue;
+            }
+            let entries = self.entries(j);
+            if entries.is_empty() {
+                continue;
+            }
+            let key = Self::parallel_key(&entries);
+            let Some((kept, kept_entries)) = seen.get(&key) else {
+                seen.insert(key, (j, entries));
+                continue;
+            };
+            let ratio = entries[0].1 / kept_entries[0].1;
+            if ratio <= 0.0 {
+                continue;
+            }
+            let kept = *kept;
+            // x_j équivaut à ratio unités de x_kept
+            let cost = self.is_min * (self.objective[j] - ratio * self.objective[kept]);
+            if cost > ZERO {
+                // C'est x_kept qui est dominée
+                self.fix_column(kept, 0.0);
+                seen.insert(key, (j, entries));
+                self.statistics.dominated_columns += 1;
+            } else {
+                self.fix_column(j, 0.0);
+                if cost < -ZERO {
+                    self.statistics.dominated_columns += 1;
+                } else {
+                    self.statistics.duplicate_columns += 1;
+                }
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    // Propagation des bornes depuis les lignes de la file, jusqu'à stabilité ou épuisement du
+    // travail. Rend les colonnes dont une borne a changé, None si un domaine devient vide.
+    fn propagate(
+        &self,
+        lower: &mut [f64],
+        upper: &mut [f64],
+        mut queue: Vec<usize>,
+        work: &mut usize,
+        limit: usize,
+    ) -> Option<Vec<usize>> {
+        let mut queued = queue.iter().copied().collect::<HashSet<_>>();
+        let mut changed = vec![];
+        while *work < limit
+            && let Some(i) = queue.pop()
+        {
+            queued.remove(&i);
+            if !self.row_alive[i] {
+                continue;
+            }
+            let row = &self.rows[i];
+            *work += row.coeffs.len();
+            let activity = Activity::new(row, lower, upper);
+            for (j, a) in &row.coeffs {
+                let (low, high) = activity.implied_bounds(row, *j, *a, lower, upper);
+                let (low, high) = self.rounded(*j, low, high);
+                let step = |bound: f64| {
+                    if self.integers[*j] {
+                        0.5
+                    } else {
+                        MIN_BOUND_IMPROVEMENT * (1.0 + bound.abs())
+                    }
+                };
+                let mut tightened = false;
+                if low > lower[*j] + step(lower[*j]) {
+                    lower[*j] = low;
+                    tightened = true;
+                }
+                if high < upper[*j] - step(upper[*j]) {
+                    upper[*j] = high;
+                    tightened = true;
+                }
+                if !tightened {
+                    continue;
+                }
+                if lower[*j] > upper[*j] + tolerance(upper[*j]) {
+                    return None;
+                }
+                changed.push(*j);
+                for k in &self.columns[*j] {
+                    if *k != i && queued.insert(*k) {
+                        queue.push(*k);
+                    }
+                }
+            }
+        }
+        changed.sort();
+        changed.dedup();
+        Some(changed)
+    }
+
+    // Propagation sur toutes les lignes, seules les bornes des variables entières sont gardées
+    fn propagate_bounds(&mut self) -> Result<bool, String> {
+        let (mut lower, mut upper) = (self.lower.clone(), self.upper.clone());
+        let queue = (0..self.rows.len())
+            .rev()
+            .filter(|i| self.row_alive[*i])
+            .collect();
+        let Some(columns) = self.propagate(&mut lower, &mut upper, queue, &mut 0, PROPAGATION_WORK)
+        else {
+            return Err(INFEASIBLE.to_string());
+        };
+        let mut changed = false;
+        for j in columns {
+            if self.col_alive[j] && self.integers[j] {
+                self.set_bounds(j, lower[j], upper[j])?;
+                self.statistics.tightened_bounds += 1;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    // Lignes de sac à dos a x <= b sur des binaires : si la ligne est toujours satisfaite quand
+    // x_j est à 0 (ou à 1 pour a_j < 0), a_j et b sont réduits d'autant sans changer les solutions entières
+    fn tighten_coefficients(&mut self) -> bool {
+        let mut changed = false;
+        for i in 0..self.rows.len() {
+            if !self.row_alive[i] || self.rows[i].sense == Sense::Eq {
+                continue;
+            }
+            // La ligne est ramenée au sens <=
+            let sign = if self.rows[i].sense == Sense::Leq {
+                1.0
+            } else {
+                -1.0
+            };
+            let mut coeffs = self.rows[i]
+                .coeffs
+                .iter()
+                .map(|(j, a)| (*j, sign * a))
+                .collect::<Vec<_>>();
+            let mut rhs = sign * self.rows[i].rhs;
+            let mut max = coeffs
+                .iter()
+                .map(|(j, a)| {
+                    if *a > 0.0 {
+                        a * self.upper[*j]
+                    } else {
+                        a * self.lower[*j]
+                    }
+                })
+                .sum::<f64>();
+            if !max.is_finite() || max <= rhs + tolerance(rhs) {
+                continue;
+            }
+            let mut tightened = false;
+            for (j, a) in coeffs.iter_mut() {
+                if !self.is_binary(*j) {
+                    continue;
+                }
+                if *a > 0.0 && max - *a < rhs - tolerance(rhs) {
+                    let delta = rhs - (max - *a);
+                    *a -= delta;
+                    rhs -= delta;
+                    max -= delta;
+                } else if *a < 0.0 && max + *a < rhs - tolerance(rhs) {
+                    *a = rhs - max;
+                } else {
+                    continue;
+                }
+                self.statistics.tightened_coefficients += 1;
+                tightened = true;
+            }
+            if tightened {
+                let row = &mut self.rows[i];
+                row.coeffs = coeffs.into_iter().map(|(j, a)| (j, sign * a)).collect();
+                row.coeffs.retain(|(_, a)| a.abs() > ZERO);
+                row.rhs = sign * rhs;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    // Chaque binaire est fixé à 0 puis à 1 et les bornes propagées : une valeur infaisable fixe
+    // la variable à l'autre, les bornes communes aux deux branches sont gardées
+    fn probing(&mut self) -> Result<bool, String> {
+        let mut changed = false;
+        let mut work = 0;
+        for j in 0..self.col_alive.len() {
+            if work >= PROBING_WORK {
+                break;
+            }
+            if !self.is_binary(j) {
+                continue;
+            }
+            self.statistics.probed_binaries += 1;
+            let branches = [0.0, 1.0].map(|value| {
+                let (mut lower, mut upper) = (self.lower.clone(), self.upper.clone());
+                lower[j] = value;
+                upper[j] = value;
+                let queue = self.columns[j].clone();
+                self.propagate(&mut lower, &mut upper, queue, &mut work, PROBING_WORK)
+                    .map(|columns| (lower, upper, columns))
+            });
+            let (zero, one) = match branches {
+                [None, None] => return Err(INFEASIBLE.to_string()),
+                [Some(zero), Some(one)] => (zero, one),
+                [zero, _] => {
+                    let value = if zero.is_none() { 1.0 } else { 0.0 };
+                    self.set_bounds(j, value, value)?;
+                    self.statistics.probing_fixings += 1;
+                    changed = true;
+                    continue;
+                }
+            };
+            let mut columns = zero.2.iter().chain(&one.2).copied().collect::<Vec<_>>();
+            columns.sort();
+            columns.dedup();
+            for k in columns {
+                if k == j {
+                    continue;
+                }
+                self.statistics.implications += 1;
+                if self.is_binary(k) && one.1[k] < 0.5 {
+                    self.implications.push((j, k));
+                }
+                if !self.integers[k] || !self.col_alive[k] {
+                    continue;
+                }
+                let (lower, upper) = (zero.0[k].min(one.0[k]), zero.1[k].max(one.1[k]));
+                if lower > self.lower[k] || upper < self.upper[k] {
+                    self.set_bounds(k, lower, upper)?;
+                    self.statistics.tightened_bounds += 1;
+                    if self.lower[k] == self.upper[k] {
+                        self.statistics.probing_fixings += 1;
+                    }
+                    changed = true;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    // Ligne sur des binaires à coefficients positifs dont deux variables ne peuvent valoir 1 ensemble
+    fn is_conflict_row(&self, i: usize) -> bool {
+        let row = &self.rows[i];
+        if !self.row_alive[i] || row.sense == Sense::Geq || row.coeffs.len() < 2 {
+            return false;
+        }
+        let mut smallest = [f64::INFINITY; 2];
+        for (j, a) in &row.coeffs {
+            if !self.is_binary(*j) || *a <= 0.0 || *a > row.rhs + tolerance(row.rhs) {
+                return false;
+            }
+            if *a < smallest[0] {
+                smallest = [*a, smallest[0]];
+            } else if *a < smallest[1] {
+                smallest[1] = *a;
+            }
+        }
+        smallest[0] + smallest[1] > row.rhs + tolerance(row.rhs)
+    }
+
+    // Lignes x + y <= 1 : supprimées si une ligne sum x <= 1 (ou = 1) les contient, sinon
+    // étendues en clique maximale du graphe de conflits qui remplace toutes celles qu'elle couvre
+    fn clique_rows(&mut self) -> bool {
+        let conflict_rows = (0..self.rows.len())
+            .filter(|i| self.is_conflict_row(*i))
+            .collect::<Vec<_>>();
+        let mut adjacency = vec![HashSet::new(); self.col_alive.len()];
+        let mut pairs: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for i in &conflict_rows {
+            let coeffs = &self.rows[*i].coeffs;
+            if coeffs.len() == 2 {
+                let (j, k) = (coeffs[0].0, coeffs[1].0);
+                pairs.entry((j.min(k), j.max(k))).or_default().push(*i);
+            }
+            if coeffs.len() > MAX_CLIQUE_ROW {
+                continue;
+            }
+            let rhs = self.rows[*i].rhs;
+            for (p, (j, a)) in coeffs.iter().enumerate() {
+                for (k, b) in &coeffs[p + 1..] {
+                    if a + b > rhs + tolerance(rhs) {
+                        adjacency[*j].insert(*k);
+                        adjacency[*k].insert(*j);
+                    }
+                }
+            }
+        }
+        for (j, k) in &self.implications {
+            if self.is_binary(*j) && self.is_binary(*k) {
+                adjacency[*j].insert(*k);
+                adjacency[*k].insert(*j);
+            }
+        }
+        let mut changed = false;
+        // Lignes de packing : tous les coefficients égaux au second membre
+        for i in conflict_rows {
+            let row = &self.rows[i];
+            if row.coeffs.len() < 3
+                || row.coeffs.len() > MAX_CLIQUE_ROW
+                || row
+                    .coeffs
+                    .iter()
+                    .any(|(_, a)| (a - row.rhs).abs() > tolerance(row.rhs))
+            {
+                continue;
+            }
+            let members = row.coeffs.iter().map(|(j, _)| *j).collect::<Vec<_>>();
+            for (p, j) in members.iter().enumerate() {
+                for k in &members[p + 1..] {
+                    for r in pairs.remove(&(*j.min(k), *j.max(k))).unwrap_or_default() {
+                        self.remove_row(r);
+                        self.statistics.dominated_rows += 1;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        let mut keys = pairs.keys().copied().collect::<Vec<_>>();
+        keys.sort();
+        for (j, k) in keys {
+            if !pairs.contains_key(&(j, k)) {
+                continue;
+            }
+            let mut clique = vec![j, k];
+            let mut candidates = adjacency[j]
+                .intersection(&adjacency[k])
+                .copied()
+                .collect::<Vec<_>>();
+            candidates.sort_by_key(|c| (Reverse(adjacency[*c].len()), *c));
+            for c in candidates {
+                if clique.iter().all(|m| adjacency[*m].contains(&c)) {
+                    clique.push(c);
+                }
+            }
+            if clique.len() < 3 {
+                continue;
+            }
+            for (p, a) in clique.iter().enumerate() {
+                for b in &clique[p + 1..] {
+                    for r in pairs.remove(&(*a.min(b), *a.max(b))).unwrap_or_default() {
+                        self.remove_row(r);
+                        self.statistics.dominated_rows += 1;
+                    }
+                }
+            }
+            clique.sort();
+            let i = self.rows.len();
+            for j in &clique {
+                self.columns[*j].push(i);
+            }
+            self.rows.push(Row {
+                coeffs: clique.into_iter().map(|j| (j, 1.0)).collect(),
+                sense: Sense::Leq,
+                rhs: 1.0,
+            });
+            self.row_alive.push(true);
+            self.statistics.clique_rows += 1;
+            changed = true;
+        }
+        changed
+    }
+
+    fn reduce(&mut self) -> Result<(), String> {
+        for _ in 0..MAX_PASSES {
+            self.statistics.passes += 1;
+            let mut changed = self.row_singletons()?;
+            changed |= self.fixed_columns();
+            changed |= self.activity_rows()?;
+            changed |= self.dominated_columns()?;
+            changed |= self.doubleton_equations()?;
+            changed |= self.duplicate_rows()?;
+            changed |= self.duplicate_columns();
+            if self.mip {
+                changed |= self.propagate_bounds()?;
+                changed |= self.tighten_coefficients();
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn run(&mut self) -> Result<(), String> {
+        self.reduce()?;
+        if self.mip {
+            let probed = self.probing()?;
+            let merged = self.clique_rows();
+            if probed || merged {
+                self.reduce()?;
+            }
+        }
+        Ok(())
+    }
+
+    // Bornes de x_j impliquées par une seule ligne active, les autres variables étant positives :
+    // elles n'ont pas besoin d'être écrites dans le problème réduit
+    fn row_implied_bounds(&self, j: usize) -> (f64, f64) {
+        let (mut lower, mut upper) = (0.0, f64::INFINITY);
+        for (i, a) in self.entries(j) {
+            let row = &self.rows[i];
+            let others = row.coeffs.iter().filter(|(k, _)| *k != j);
+            let (min, max) = others.fold((0.0, 0.0), |(min, max), (_, b)| {
+                if *b > 0.0 {
+                    (min, f64::INFINITY)
+                } else {
+                    (f64::NEG_INFINITY, max)
+                }
+            });
+            if row.sense != Sense::Geq {
+                let bound = (row.rhs - min) / a;
+                if a > 0.0 {
+                    upper = f64::min(upper, bound)
+                } else {
+                    lower = f64::max(lower, bound)
+                }
+            }
+            if row.sense != Sense::Leq {
+                let bound = (row.rhs - max) / a;
+                if a > 0.0 {
+                    lower = f64::max(lower, bound)
+                } else {
+                    upper = f64::min(upper, bound)
+                }
+            }
+        }
+        (lower, upper)
+    }
+
+    fn into_presolved(self, model: &LpModel) -> Presolved {
+        let columns = (0..self.col_alive.len())
+            .filter(|j| self.col_alive[*j])
+            .collect::<Vec<_>>();
+        let rows = (0..self.rows.len())
+            .filter(|i| self.row_alive[*i])
+            .collect::<Vec<_>>();
+        let mut position = vec![usize::MAX; self.col_alive.len()];
+        for (k, j) in columns.iter().enumerate() {
+            position[*j] = k;
+        }
+        let names = columns
+            .iter()
+            .map(|j| model.names[*j].clone())
+            .collect::<Vec<_>>();
+        let mut reduced_rows = rows
+            .iter()
+            .map(|i| Row {
+                coeffs: self.rows[*i]
+                    .coeffs
+                    .iter()
+                    .map(|(j, a)| (position[*j], *a))
+                    .collect(),
+                sense: self.rows[*i].sense,
+                rhs: self.rows[*i].rhs,
+            })
+            .collect::<Vec<_>>();
+        // Les bornes sont écrites comme des lignes, après les lignes d'origine
+        for (k, j) in columns.iter().enumerate() {
+            let (lower, upper) = self.row_implied_bounds(*j);
+            if self.lower[*j] > lower + tolerance(lower) {
+                reduced_rows.push(Row {
+                    coeffs: vec![(k, 1.0)],
+                    sense: Sense::Geq,
+                    rhs: self.lower[*j],
+                });
+            }
+            if self.upper[*j].is_finite()
+                && (!upper.is_finite() || self.upper[*j] < upper - tolerance(upper))
+            {
+                reduced_rows.push(Row {
+                    coeffs: vec![(k, 1.0)],
+                    sense: Sense::Leq,
+                    rhs: self.upper[*j],
+                });
+            }
+        }
+        let mut statistics = self.statistics;
+        statistics.rows_after = rows.len();
+        statistics.columns_after = columns.len();
+        Presolved {
+            reduced: LpModel {
+                is_min: self.is_min,
+                index: names
+                    .iter()
+                    .enumerate()
+                    .map(|(k, name)| (name.clone(), k))
+                    .collect(),
+                names,
+                objective: columns.iter().map(|j| self.objective[*j]).collect(),
+                // Les objectifs quadratiques ne passent pas par le presolve
+                quadratic: vec![],
+                objectives: vec![],
+                rows: reduced_rows,
+                goals: vec![],
+                integers: columns.iter().map(|j| self.integers[*j]).collect(),
+            },
+            statistics,
+            is_min: self.is_min,
+            width: model.names.len(),
+            height: model.rows.len(),
+            added_rows: self.rows.len() - model.rows.len(),
+            columns,
+            rows,
+            steps: self.steps,
+        }
+    }
+}
+
+pub fn presolve(model: &LpModel) -> Result<Presolved, String> {
+    run_presolve(model, false)
+}
+
+// Presolve des problèmes en nombres entiers, sans duales au postsolve
+pub fn presolve_mip(model: &LpModel) -> Result<Presolved, String> {
+    run_presolve(model, true)
+}
+
+fn run_presolve(model: &LpModel, mip: bool) -> Result<Presolved, String> {
+    let mut presolver = Presolver::new(model, mip);
+    presolver.run()?;
+    let presolved = presolver.into_presolved(model);
+    let stats = &presolved.statistics;
+    log::info!(
+        "Presolve: {} rows, {} columns -> {} rows, {} columns in {} passes",
+        stats.rows_before,
+        stats.columns_before,
+        stats.rows_after,
+        stats.columns_after,
+        stats.passes
+    );
+    log::info!(
+        "Presolve: {} empty, {} singleton, {} redundant, {} forcing, {} doubleton, {} duplicate rows; {} fixed, {} dominated, {} duplicate columns",
+        stats.empty_rows,
+        stats.singleton_rows,
+        stats.redundant_rows,
+        stats.forcing_rows,
+        stats.doubleton_equations,
+        stats.duplicate_rows,
+        stats.fixed_columns,
+        stats.dominated_columns,
+        stats.duplicate_columns
+    );
+    if mip {
+        log::info!(
+            "Presolve: {} tightened bounds, {} tightened coefficients, {} probed binaries ({} fixings, {} implications), {} cliques replacing {} rows",
+            stats.tightened_bounds,
+            stats.tightened_coefficients,
+            stats.probed_binaries,
+            stats.probing_fixings,
+            stats.implications,
+            stats.clique_rows,
+            stats.dominated_rows
+        );
+    }
+    Ok(presolved)
+}
+
+impl Presolved {
+    // Solution (valeurs et duales) du problème d'origine à partir de celle du problème réduit.
+    // Les duales des lignes de bornes ajoutées à la fin du problème réduit sont ignorées.
+    pub fn postsolve(&self, values: &[f64], duals: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let mut x = vec![0.0; self.width];
+        let mut y = vec![0.0; self.height + self.added_rows];
+        for (k, j) in self.columns.iter().enumerate() {
+            x[*j] = values[k];
+        }
+        for (k, i) in self.rows.iter().enumerate() {
+            y[*i] = duals[k];
+        }
+        let is_min = self.is_min;
+        for step in self.steps.iter().rev() {
+            match step {
+                Step::RemovedRow { row } => y[*row] = 0.0,
+                Step::FixedColumn { col, value } => x[*col] = *value,
+                Step::SingletonRow {
+                    row,
+                    col,
+                    coeff,
+                    previous,
+                    bounds,
+                    column,
+                } => {
+                    // La ligne porte la duale si la borne qu'elle a posée est active
+                    let value = x[*col];
+                    let reduced_cost = column.reduced_cost(&y);
+                    let cost = is_min * reduced_cost;
+                    let at_upper = bounds.1 < previous.1 - tolerance(bounds.1)
+                        && (value - bounds.1).abs() <= tolerance(bounds.1)
+                        && cost > 0.0;
+                    let at_lower = bounds.0 > previous.0 + tolerance(bounds.0)
+                        && (value - bounds.0).abs() <= tolerance(bounds.0)
+                        && cost < 0.0;
+                    y[*row] = if at_upper || at_lower {
+                        reduced_cost / coeff
+                    } else {
+                        0.0
+                    };
+                }
+                Step::ForcingRow {
+                    row,
+                    sense,
+                    at_min,
+                    columns,
+                } => {
+                    // Plus petite duale (sens max) qui rend les coûts réduits compatibles avec les bornes
+                    let ratios = columns
+                        .iter()
+                        .map(|(a, column)| is_min * column.reduced_cost(&y) / a);
+                    let dual = if *at_min {
+                        let dual = ratios.fold(f64::NEG_INFINITY, f64::max);
+                        if *sense == Sense::Eq {
+                            dual
+                        } else {
+                            dual.max(0.0)
+                        }
+                    } else {
+                        let dual = ratios.fold(f64::INFINITY, f64::min);
+                        if *sense == Sense::Eq {
+                            dual
+                        } else {
+                            dual.min(0.0)
+                        }
+                    };
+                    y[*row] = if dual.is_finite() { is_min * dual } else { 0.0 };
+                }
+                Step::DoubletonEquation {
+                    row,
+                    kept,
+                    removed,
+                    kept_coeff,
+                    removed_coeff,
+                    rhs,
+                    removed_bounds,
+                    kept_column,
+                    removed_column,
+                } => {
+                    let value = (rhs - kept_coeff * x[*kept]) / removed_coeff;
+                    x[*removed] = value;
+                    let inside = value > removed_bounds.0 + tolerance(removed_bounds.0)
+                        && (!removed_bounds.1.is_finite()
+                            || value < removed_bounds.1 - tolerance(removed_bounds.1));
+                    // Variable supprimée entre ses bornes : son coût réduit doit être nul,
+                    // sinon c'est celui de la variable gardée
+                    y[*row] = if inside {
+                        removed_column.reduced_cost(&y) / removed_coeff
+                    } else {
+                        kept_column.reduced_cost(&y) / kept_coeff
+                    };
+                }
+                Step::DuplicateRow {
+                    kept,
+                    removed,
+                    ratio,
+                    sense,
+                } => {
+                    // La duale de l'égalité revient à l'inégalité dont le signe lui correspond
+                    let dual = is_min * y[*kept];
+                    let compatible = match sense {
+                        Sense::Leq => dual >= 0.0,
+                        Sense::Geq => dual <= 0.0,
+                        Sense::Eq => true,
+                    };
+                    if !compatible {
+                        y[*removed] = y[*kept] / ratio;
+                        y[*kept] = 0.0;
+                    }
+                }
+            }
+        }
+        y.truncate(self.height);
+        (x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::heuristics::is_feasible;
+    use crate::model::parse_lp_model;
+    use crate::simplexef64::{LpSolution, PRECISION, SimplexOptions, solve_lp};
+
+    use super::PresolveStatistics;
+
+    fn solve(file: &str, presolve: bool) -> LpSolution {
+        let options = SimplexOptions {
+            presolve,
+            network: false,
+            assignment: false,
+            duals: true,
+            ..Default::default()
+        };
+        solve_lp(file, &options).unwrap()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < PRECISION, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    // Résout avec et sans presolve : la réduction comptée par count a été faite, le postsolve
+    // rend les valeurs et les duales calculées à la main, et z est le même sans presolve
+    fn check(
+        file: &str,
+        count: fn(&PresolveStatistics) -> usize,
+        values: &[f64],
+        duals: &[f64],
+    ) -> LpSolution {
+        let presolved = solve(file, true);
+        assert!(count(presolved.presolve.as_ref().unwrap()) > 0);
+        let x = presolved
+            .variables
+            .iter()
+            .map(|(_, v)| *v)
+            .collect::<Vec<_>>();
+        assert_close(&x, values);
+        assert_close(&presolved.duals, duals);
+        let direct = solve(file, false);
+        assert!((presolved.z - direct.z).abs() < PRECISION);
+        direct
+    }
+
+    #[test]
+    fn singleton_row() {
+        let file = "max: 3x + 2y;\nx + y <= 4;\nx <= 3;\n";
+        let direct = check(file, |s| s.singleton_rows, &[3.0, 1.0], &[2.0, 1.0]);
+        assert_close(&direct.duals, &[2.0, 1.0]);
+    }
+
+    #[test]
+    fn redundant_row() {
+        let file = "max: x + 2y;\nx + y <= 10;\nx <= 1;\ny <= 1;\n";
+        let direct = check(file, |s| s.redundant_rows, &[1.0, 1.0], &[0.0, 1.0, 2.0]);
+        assert_close(&direct.duals, &[0.0, 1.0, 2.0]);
+    }
+
+    // x + y <= 0 fixe x et y à 0, sa duale est la plus petite compatible avec leurs coûts
+    #[test]
+    fn forcing_row() {
+        let file = "max: 2x + 3y + z;\nx + y <= 0;\nz <= 2;\n";
+        check(file, |s| s.forcing_rows, &[0.0, 0.0, 2.0], &[3.0, 1.0]);
+    }
+
+    // x = y + 1 est substituée : x + y <= 5 donne y = 2, et les duales u + v = 2, v - u = 1
+    #[test]
+    fn doubleton_equation() {
+        let file = "max: 2x + y;\nx - y = 1;\nx + y <= 5;\n";
+        let direct = check(file, |s| s.doubleton_equations, &[3.0, 2.0], &[0.5, 1.5]);
+        assert_close(&direct.duals, &[0.5, 1.5]);
+    }
+
+    // Les deux inégalités forment une égalité, sa duale revient à celle du bon signe
+    #[test]
+    fn duplicate_rows() {
+        let file = "max: x + y;\nx + 2y <= 4;\nx + 2y >= 4;\nx <= 2;\n";
+        check(file, |s| s.duplicate_rows, &[2.0, 1.0], &[0.5, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn fixed_column() {
+        let file = "max: x + y;\nx + y <= 3;\ny = 1;\n";
+        let direct = check(file, |s| s.fixed_columns, &[2.0, 1.0], &[1.0, 0.0]);
+        assert_close(&direct.duals, &[1.0, 0.0]);
+    }
+
+    #[test]
+    fn dominated_column() {
+        let file = "max: 3x - z;\nx + z <= 4;\n";
+        let direct = check(file, |s| s.dominated_columns, &[4.0, 0.0], &[3.0]);
+        assert_close(&direct.duals, &[3.0]);
+    }
+
+    // x et y sont parallèles et de même coût : l'une des deux est fixée à 0
+    #[test]
+    fn duplicate_column() {
+        let file = "max: 3x + 3y;\nx + y <= 4;\n2x + 2y <= 10;\n";
+        let presolved = solve(file, true);
+        assert!(presolved.presolve.as_ref().unwrap().duplicate_columns > 0);
+        assert!((presolved.z - 12.0).abs() < PRECISION);
+        assert_close(&presolved.duals, &[3.0, 0.0]);
+        let model = parse_lp_model(file).unwrap();
+        assert!(is_feasible(&model, &model.values(&presolved.variables)));
+    }
+
+    #[test]
+    fn presolve_agrees_with_direct_solve() {
+        for path in ["mcdo.lp", "program.lp"] {
+            let file = std::fs::read_to_string(path).unwrap();
+            let presolved = solve(&file, true);
+            let direct = solve(&file, false);
+            assert!(
+                (presolved.z - direct.z).abs() <= PRECISION * direct.z.abs().max(1.0),
+                "{}: {} != {}",
+                path,
+                presolved.z,
+                direct.z
+            );
+            let mut model = parse_lp_model(&file).unwrap();
+            model.integers.fill(false);
+            assert!(is_feasible(&model, &model.values(&presolved.variables)));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use log::Level;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimplexPhase {
+    PhaseOne,
+    PhaseTwo,
+    Dual,
+}
+
+// Événement de progression d'une résolution
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Progress {
+    // Tous les 10 pivots du simplexe : objectif de la phase en maximisation (l'opposé de
+    // l'objectif du fichier pour un min, moins la somme des artificielles en phase 1) et
+    // infaisabilité primale de la base (somme des artificielles, plus grande violation pour
+    // le simplexe dual)
+    Iteration {
+        phase: SimplexPhase,
+        iteration: usize,
+        objective: f64,
+        primal_infeasibility: f64,
+    },
+    // Relaxation d'un noeud du branch and bound résolue
+    Node {
+        explored_nodes: usize,
+        open_nodes: usize,
+        z: f64,
+    },
+    Incumbent {
+        z: f64,
+        source: String,
+        explored_nodes: usize,
+    },
+    // Meilleure borne des noeuds ouverts quand elle change
+    Bound {
+        best_bound: f64,
+        gap: f64,
+        explored_nodes: usize,
+    },
+}
+
+impl Progress {
+    fn level(&self) -> Level {
+        match self {
+            Progress::Iteration { .. } | Progress::Bound { .. } => Level::Debug,
+            Progress::Node { .. } => Level::Trace,
+            Progress::Incumbent { .. } => Level::Info,
+        }
+    }
+
+    fn log(&self) {
+        if !log::log_enabled!(self.level()) {
+            return;
+        }
+        match self {
+            Progress::Iteration {
+                phase, iteration, ..
+            } => match phase {
+                SimplexPhase::PhaseOne => log::debug!("Pivoting (phase 1)... {}", iteration),
+                SimplexPhase::PhaseTwo => log::debug!("Pivoting (phase 2)... {}", iteration),
+                SimplexPhase::Dual => log::debug!("Pivoting (dual)... {}", iteration),
+            },
+            Progress::Node {
+                explored_nodes,
+                open_nodes,
+                z,
+            } => log::trace!(
+                "Node {} solved, z = {:?}, {} open nodes",
+                explored_nodes,
+                z,
+                open_nodes
+            ),
+            Progress::Incumbent {
+                z,
+                source,
+                explored_nodes,
+            } => log::info!(
+                "New incumbent z = {:?} ({}, {} nodes explored)",
+                z,
+                source,
+                explored_nodes
+            ),
+            Progress::Bound {
+                best_bound,
+                gap,
+                explored_nodes,
+            } => log::debug!(
+                "Best bound = {:?}, gap = {:.4}% ({} nodes explored)",
+                best_bound,
+                gap * 100.0,
+                explored_nodes
+            ),
+        }
+    }
+}
+
+// Reçoit les événements des résolutions lancées par observe
+pub trait Observer: Send + Sync {
+    fn notify(&self, event: &Progress);
+}
+
+thread_local! {
+    static OBSERVER: RefCell<Option<Arc<dyn Observer>>> = const { RefCell::new(None) };
+}
+
+// Remet l'observateur précédent à la fin de observe, même après une panique
+struct Restore(Option<Arc<dyn Observer>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        OBSERVER.with(|current| *current.borrow_mut() = self.0.take());
+    }
+}
+
+// Lance f en envoyant à observer les événements des résolutions faites sur ce thread.
+// Les sous-problèmes résolus en parallèle sur d'autres threads ne sont pas observés.
+pub fn observe<R>(observer: Arc<dyn Observer>, f: impl FnOnce() -> R) -> R {
+    let _restore = Restore(OBSERVER.with(|current| current.borrow_mut().replace(observer)));
+    f()
+}
+
+// Événement envoyé à l'observateur du thread et écrit dans le journal
+pub(crate) fn notify(event: Progress) {
+    let observer = OBSERVER.with(|current| current.borrow().clone());
+    if let Some(observer) = observer {
+        observer.notify(&event);
+    }
+    event.log();
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use crate::model::{LpModel, Row, Sense};
+use crate::simplexef64::complementary_phase_one;
+
+// Pivot en dessous duquel une matrice est considérée comme singulière
+const PSD_TOLERANCE: f64 = 1.0e-9;
+
+// Hessien H de l'objectif à minimiser 1/2 x^T H x + c^T x (l'objectif d'un max est opposé)
+fn hessian(model: &LpModel) -> Vec<Vec<f64>> {
+    let n = model.names.len();
+    let sign = -model.is_min;
+    let mut h = vec![vec![0.0; n]; n];
+    for (i, j, q) in &model.quadratic {
+        if i == j {
+            h[*i][*i] += 2.0 * sign * q;
+        } else {
+            h[*i][*j] += sign * q;
+            h[*j][*i] += sign * q;
+        }
+    }
+    h
+}
+
+// H est semi-définie positive si l'élimination de Gauss symétrique ne rencontre aucun pivot
+// négatif, et si chaque pivot nul a une colonne nulle sous lui
+fn is_positive_semidefinite(h: &[Vec<f64>]) -> bool {
+    let mut a = h.to_vec();
+    let n = a.len();
+    let scale = a
+        .iter()
+        .flatten()
+        .fold(0.0_f64, |max, x| max.max(x.abs()))
+        .max(1.0);
+    for k in 0..n {
+        let pivot = a[k][k];
+        if pivot < -PSD_TOLERANCE * scale {
+            return false;
+        }
+        if pivot <= PSD_TOLERANCE * scale {
+            if (k + 1..n).any(|i| a[i][k].abs() > PSD_TOLERANCE.sqrt() * scale) {
+                return false;
+            }
+            continue;
+        }
+        let (upper, lower) = a.split_at_mut(k + 1);
+        for row in lower {
+            let factor = row[k] / pivot;
+            if factor == 0.0 {
+                continue;
+            }
+            for (x, y) in row[k..].iter_mut().zip(&upper[k][k..]) {
+                *x -= factor * y;
+            }
+        }
+    }
+    true
+}
+
+// Programme quadratique convexe par la méthode de Wolfe : les conditions de KKT
+//   H x + c + sum λ_i a_i - sum ν_i a_i + sum w_i a_i - μ = 0  (lignes <=, >=, =)
+// forment avec les contraintes un système linéaire résolu par la phase 1 du simplexe, en
+// refusant l'entrée dans la base d'une variable dont la complémentaire y est déjà
+// (x_j et μ_j, λ_i ou ν_i et l'écart de la ligne i). Rend les valeurs et les duales.
+pub fn solve_qp(model: &LpModel) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let h = hessian(model);
+    if !is_positive_semidefinite(&h) {
+        return Err(if model.is_min < 0.0 {
+            "L'objectif quadratique n'est pas convexe".to_string()
+        } else {
+            "L'objectif quadratique n'est pas concave".to_string()
+        });
+    }
+    let n = model.names.len();
+    let sign = -model.is_min;
+    let mut kkt = LpModel {
+        is_min: -1.0,
+        names: model.names.clone(),
+        index: model.index.clone(),
+        objective: vec![],
+        quadratic: vec![],
+        objectives: vec![],
+        rows: model.rows.clone(),
+        goals: vec![],
+        integers: vec![],
+    };
+    let add = |kkt: &mut LpModel, name: String| {
+        kkt.index.insert(name.clone(), kkt.names.len());
+        kkt.names.push(name);
+        kkt.names.len() - 1
+    };
+    // Multiplicateurs de chaque ligne : (variable, signe dans la stationnarité)
+    let mut multipliers = vec![];
+    let mut row_complements = vec![];
+    for (i, row) in model.rows.iter().enumerate() {
+        match row.sense {
+            Sense::Leq | Sense::Geq => {
+                let name = model.fresh_name(format!("_lambda{}", i));
+                let k = add(&mut kkt, name.clone());
+                let direction = if row.sense == Sense::Leq { 1.0 } else { -1.0 };
+                multipliers.push(vec![(k, direction)]);
+                row_complements.push((i, name));
+            }
+            Sense::Eq => {
+                let plus = add(&mut kkt, model.fresh_name(format!("_wplus{}", i)));
+                let minus = add(&mut kkt, model.fresh_name(format!("_wminus{}", i)));
+                multipliers.push(vec![(plus, 1.0), (minus, -1.0)]);
+            }
+        }
+    }
+    let mut complements = vec![];
+    let mut stationarity = vec![vec![]; n];
+    for j in 0..n {
+        let mu = model.fresh_name(format!("_mu{}", j));
+        let k = add(&mut kkt, mu.clone());
+        complements.push((model.names[j].clone(), mu));
+        stationarity[j].push((k, -1.0));
+        for (l, a) in h[j].iter().enumerate().filter(|(_, a)| **a != 0.0) {
+            stationarity[j].push((l, *a));
+        }
+    }
+    for (row, multipliers) in model.rows.iter().zip(&multipliers) {
+        for (j, a) in &row.coeffs {
+            for (k, direction) in multipliers {
+                stationarity[*j].push((*k, direction * a));
+            }
+        }
+    }
+    for (j, coeffs) in stationarity.into_iter().enumerate() {
+        kkt.rows.push(Row {
+            coeffs,
+            sense: Sense::Eq,
+            rhs: -sign * model.objective[j],
+        });
+    }
+    kkt.objective = vec![0.0; kkt.names.len()];
+    kkt.integers = vec![false; kkt.names.len()];
+    let solution = complementary_phase_one(&kkt.to_lp_string(), &complements, &row_complements)
+        .map_err(|_| "Pas de point de KKT : le problème est infaisable ou non borné".to_string())?;
+    let values = solution.into_iter().collect::<HashMap<_, _>>();
+    let value = |k: usize| values.get(&kkt.names[k]).copied().unwrap_or(0.0);
+    let x = (0..n).map(value).collect();
+    // Duales dans le sens du fichier, comme celles du simplexe
+    let duals = multipliers
+        .iter()
+        .map(|multipliers| {
+            multipliers
+                .iter()
+                .map(|(k, direction)| -direction * value(*k))
+                .sum::<f64>()
+                * sign
+        })
+        .collect();
+    Ok((x, duals))
+}
@@ -0,0 +1,154 @@
+use crate::simplexef64::Variable;
+use crate::sparse::CscMatrix;
+
+// Passes de moyenne géométrique avant l'équilibrage
+const GEOMETRIC_PASSES: usize = 4;
+// On s'arrête quand une passe n'améliore plus l'écart des coefficients d'au moins 10 %
+const MIN_IMPROVEMENT: f64 = 0.9;
+
+// Facteurs de mise à l'échelle de la matrice [b A] de two_phases : a'_ij = r_i a_ij t_j et
+// x_j = t_j x'_j. Les écarts et artificielles d'une ligne ont t = 1 / r_i, leur coefficient
+// reste ±1. Les facteurs sont des puissances de 2, la mise à l'échelle est exacte.
+pub struct Scaling {
+    rows: Vec<f64>,
+    columns: Vec<f64>,
+}
+
+// Plus petit et plus grand coefficient mis à l'échelle de chaque ligne
+fn row_ranges(
+    matrix: &CscMatrix,
+    structural: &[bool],
+    rows: &[f64],
+    columns: &[f64],
+) -> Vec<(f64, f64)> {
+    let mut ranges = vec![(f64::INFINITY, 0.0_f64); matrix.rows];
+    for j in (1..matrix.columns()).filter(|j| structural[*j]) {
+        for (i, a) in matrix.column(j) {
+            let a = (a * rows[i] * columns[j]).abs();
+            ranges[i] = (ranges[i].0.min(a), ranges[i].1.max(a));
+        }
+    }
+    ranges
+}
+
+// Plus petit et plus grand coefficient mis à l'échelle de la colonne j
+fn column_range(matrix: &CscMatrix, j: usize, rows: &[f64], t: f64) -> (f64, f64) {
+    matrix
+        .column(j)
+        .map(|(i, a)| (a * rows[i] * t).abs())
+        .fold((f64::INFINITY, 0.0_f64), |(min, max), a| {
+            (min.min(a), max.max(a))
+        })
+}
+
+// Plus grand rapport entre deux coefficients non nuls d'une même ligne ou colonne
+fn spread(matrix: &CscMatrix, structural: &[bool], rows: &[f64], columns: &[f64]) -> f64 {
+    row_ranges(matrix, structural, rows, columns)
+        .into_iter()
+        .chain(
+            (1..matrix.columns())
+                .filter(|j| structural[*j])
+                .map(|j| column_range(matrix, j, rows, columns[j])),
+        )
+        .filter(|(_, max)| *max > 0.0)
+        .fold(1.0_f64, |worst, (min, max)| worst.max(max / min))
+}
+
+fn power_of_two(x: f64) -> f64 {
+    2.0_f64.powi(x.log2().round() as i32)
+}
+
+impl Scaling {
+    // Moyenne géométrique (r_i = 1 / sqrt(min |a_ij| max |a_ij|), puis de même pour les colonnes),
+    // équilibrage (plus grand coefficient de chaque ligne puis colonne ramené à 1) et arrondi
+    // à la puissance de 2 la plus proche. Seuls les coefficients des variables du fichier comptent.
+    pub fn new(matrix: &CscMatrix, variables: &[Variable]) -> Self {
+        let width = matrix.columns();
+        let mut structural = vec![false; width];
+        for var in variables {
+            structural[var.column] = !var.is_slack && !var.is_artificial;
+        }
+        let mut rows = vec![1.0; matrix.rows];
+        let mut columns = vec![1.0; width];
+        let mut previous = spread(matrix, &structural, &rows, &columns);
+        for _ in 0..GEOMETRIC_PASSES {
+            if previous <= 1.0 {
+                break;
+            }
+            let ranges = row_ranges(matrix, &structural, &vec![1.0; matrix.rows], &columns);
+            for (r, (min, max)) in rows.iter_mut().zip(ranges) {
+                if max > 0.0 {
+                    *r = 1.0 / (min * max).sqrt();
+                }
+            }
+            for j in (1..width).filter(|j| structural[*j]) {
+                let (min, max) = column_range(matrix, j, &rows, 1.0);
+                if max > 0.0 {
+                    columns[j] = 1.0 / (min * max).sqrt();
+                }
+            }
+            let current = spread(matrix, &structural, &rows, &columns);
+            if current > MIN_IMPROVEMENT * previous {
+                break;
+            }
+            previous = current;
+        }
+        let ranges = row_ranges(matrix, &structural, &vec![1.0; matrix.rows], &columns);
+        for (r, (_, max)) in rows.iter_mut().zip(ranges) {
+            if max > 0.0 {
+                *r = power_of_two(1.0 / max);
+            }
+        }
+        for j in (1..width).filter(|j| structural[*j]) {
+            let (_, max) = column_range(matrix, j, &rows, 1.0);
+            if max > 0.0 {
+                columns[j] = power_of_two(1.0 / max);
+            }
+        }
+        // Ecarts et artificielles : un seul coefficient dans leur colonne
+        for j in (1..width).filter(|j| !structural[*j]) {
+            if let Some((i, _)) = matrix.column(j).next() {
+                columns[j] = 1.0 / rows[i];
+            }
+        }
+        Scaling { rows, columns }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.rows
+            .iter()
+            .chain(&self.columns[1..])
+            .all(|x| *x == 1.0)
+    }
+
+    // Met à l'échelle la matrice [b A] et les coûts de la phase 2, indicés par colonne
+    pub fn apply(&self, matrix: &mut CscMatrix, costs: &mut [f64]) {
+        matrix.scale(|i, j| self.rows[i] * self.columns[j]);
+        for (cost, t) in costs.iter_mut().zip(&self.columns) {
+            *cost *= t;
+        }
+    }
+
+    // x_j = t_j x'_j, values est indicé par colonne
+    pub fn unscale_values(&self, values: &mut [f64]) {
+        for (x, t) in values.iter_mut().zip(&self.columns) {
+            *x *= t;
+        }
+    }
+
+    // y_i = r_i y'_i
+    pub fn unscale_duals(&self, duals: &mut [f64]) {
+        for (y, r) in duals.iter_mut().zip(&self.rows) {
+            *y *= r;
+        }
+    }
+
+    // Ligne de B'^-1 [b' A'] de variable de base basic ramenée à B^-1 [b A] :
+    // elle est multipliée par t_basic et chaque colonne j divisée par t_j
+    pub fn unscale_row(&self, row: &mut [f64], basic: usize) {
+        let t = self.columns[basic];
+        for (a, t_j) in row.iter_mut().zip(&self.columns) {
+            *a *= t / t_j;
+        }
+    }
+}
@@ -1,152 +1,423 @@
 use pest::Parser;
 use pest_derive::Parser;
 use rayon::prelude::*;
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::alternatives::{self, Alternatives, PoolSolution};
+use crate::assignment;
+use crate::basis_file::StoredBasis;
+use crate::cuts::{self, ConflictGraph};
+use crate::heuristics;
+use crate::interior_point;
+use crate::model::{LpModel, Sense, format_constraint, parse_lp_model};
+use crate::multi_objective::{self, GoalAchievement, MultiObjective};
+use crate::network::Network;
+use crate::presolve::{PresolveStatistics, presolve, presolve_mip};
+use crate::progress::{self, Progress, SimplexPhase};
+use crate::quadratic;
+use crate::scaling::Scaling;
+use crate::sparse::{CscMatrix, EtaFile};
 
 #[derive(Parser)]
 #[grammar = "lexer.pest"]
 pub struct LPParser;
-const PRECISION: f64 = 1.0e-6;
-#[inline(always)]
-pub fn solve_system(
-    matrix: &mut [Vec<f64>],
-    variables: &mut [Variable],
-    vars_hash_map: &mut HashMap<String, usize>,
-    is_min: f64,
-) -> Result<(Vec<(String, f64)>, f64), String> {
-    // rayon::ThreadPoolBuilder::new()
-    // .num_threads(4)
-    // .build_global()
-    // .unwrap();
-    let borned = big_m(matrix, variables, vars_hash_map, false);
-    if !borned {
-        return Err("Le problème est non borné".to_string());
+pub(crate) const PRECISION: f64 = 1.0e-6;
+// Erreur du branch and bound quand aucune solution entière n'est trouvée
+pub(crate) const NO_INTEGER_SOLUTION: &str = "Pas de solution entière";
+// Erreur du branch and bound arrêté par max_nodes avant de trouver une solution entière
+pub(crate) const NODE_LIMIT: &str = "Limite de noeuds atteinte sans solution entière";
+
+// (matrice creuse [b A], variables, is_min, variables par nom, coûts originaux)
+pub type TwoPhasesProblem = (
+    CscMatrix,
+    Vec<Variable>,
+    f64,
+    HashMap<String, usize>,
+    HashMap<String, f64>,
+);
+// Algorithme de résolution de la relaxation continue. Les points intérieurs sont suivis
+// d'un crossover vers une base optimale.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Solver {
+    #[default]
+    Simplex,
+    InteriorPoint,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct SimplexOptions {
+    pub presolve: bool,
+    // Mise à l'échelle des lignes et des colonnes avant le simplexe
+    pub scaling: bool,
+    pub solver: Solver,
+    // Simplexe réseau quand la matrice est celle d'un réseau (transport, affectation, flot)
+    pub network: bool,
+    // Algorithme hongrois quand le programme est une affectation
+    pub assignment: bool,
+    // Traitement des fichiers à plusieurs objectifs
+    pub multi_objective: MultiObjective,
+    // Nombre maximal de sommets optimaux énumérés, 0 pour ne pas les chercher
+    pub alternatives: usize,
+    // Rend aussi les duales et les statistiques du presolve
+    pub duals: bool,
+    // Rend aussi la base optimale, le programme est alors résolu sans presolve
+    pub basis: bool,
+}
+
+impl Default for SimplexOptions {
+    fn default() -> Self {
+        SimplexOptions {
+            presolve: true,
+            scaling: true,
+            solver: Solver::Simplex,
+            network: true,
+            assignment: true,
+            multi_objective: MultiObjective::Lexicographic,
+            alternatives: 0,
+            duals: false,
+            basis: false,
+        }
     }
-    let solved = check_all_constraints(matrix, variables);
-    if !solved {
-        return Err("Le problème est infaisable".to_string());
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LpSolution {
+    pub variables: Vec<(String, f64)>,
+    pub z: f64,
+    // Duale de chaque contrainte, dans l'ordre du fichier
+    pub duals: Vec<f64>,
+    // Valeur de chaque objectif d'un programme multi-objectif
+    pub objectives: Vec<(String, f64)>,
+    pub goals: Vec<GoalAchievement>,
+    // Sommets optimaux quand l'option alternatives est donnée
+    pub alternatives: Option<Alternatives>,
+    pub presolve: Option<PresolveStatistics>,
+    // Base optimale quand l'option basis est donnée
+    pub basis: Option<StoredBasis>,
+}
+
+pub fn solve_lp(file: &str, options: &SimplexOptions) -> Result<LpSolution, String> {
+    solve_lp_from(file, options, None)
+}
+
+// Résout le programme depuis la base enregistrée start : les deux phases sautent la phase 1
+// si elle est réalisable. Le presolve et l'algorithme hongrois sont alors sautés, la base
+// décrivant le programme du fichier. Une base n'est utilisée que pour un seul objectif.
+pub fn solve_lp_from(
+    file: &str,
+    options: &SimplexOptions,
+    start: Option<&StoredBasis>,
+) -> Result<LpSolution, String> {
+    let model = parse_lp_model(file)?;
+    if !model.goals.is_empty() {
+        // Les buts sont réécrits en contraintes avec leurs écarts et leurs objectifs
+        let mut solution = solve_lp(&model.to_lp_string(), options)?;
+        let values = model.values(&solution.variables);
+        solution.goals = multi_objective::goal_achievements(&model, &values);
+        solution.variables = model.names.iter().cloned().zip(values).collect();
+        return Ok(solution);
     }
-    get_solution(matrix, variables, vars_hash_map, is_min)
+    if !model.objectives.is_empty() {
+        return multi_objective::solve_lp(&model, options);
+    }
+    let warm = start.is_some() || options.basis;
+    let assignment = Some(&model)
+        .filter(|_| options.assignment && !warm)
+        .and_then(assignment::solve_model);
+    let mut basis = None;
+    let (values, duals, statistics) = if !model.quadratic.is_empty() {
+        let (values, duals) = quadratic::solve_qp(&model)?;
+        (values, duals, None)
+    } else if let Some((values, duals)) = assignment {
+        (values, duals, None)
+    } else if options.presolve && !warm {
+        // Le simplexe ignore les déclarations int, le presolve aussi
+        let mut relaxation = model.clone();
+        relaxation.integers.fill(false);
+        let presolved = presolve(&relaxation)?;
+        let (values, duals) = if presolved.reduced.names.is_empty() {
+            (vec![], vec![0.0; presolved.reduced.rows.len()])
+        } else {
+            let (values, duals, _) = solve_system_two_phases(
+                &presolved.reduced.to_lp_string(),
+                &presolved.reduced,
+                options,
+                None,
+            )?;
+            (values, duals)
+        };
+        let (values, duals) = presolved.postsolve(&values, &duals);
+        (values, duals, Some(presolved.statistics))
+    } else {
+        let (values, duals, optimal) = solve_system_two_phases(file, &model, options, start)?;
+        basis = Some(optimal).filter(|_| options.basis);
+        (values, duals, None)
+    };
+    let alternatives = if options.alternatives > 0 && model.quadratic.is_empty() {
+        Some(alternatives::optimal_vertices(
+            &model,
+            options.alternatives,
+        )?)
+    } else {
+        None
+    };
+    Ok(LpSolution {
+        variables: model
+            .names
+            .iter()
+            .cloned()
+            .zip(values.iter().copied())
+            .collect(),
+        z: model.objective_value(&values),
+        duals,
+        objectives: vec![],
+        goals: vec![],
+        alternatives,
+        presolve: statistics,
+        basis,
+    })
 }
 
-#[inline(always)]
-pub fn solve_system_two_phases(
-    matrix: &mut [Vec<f64>],
-    variables: &mut [Variable],
-    vars_hash_map: &mut HashMap<String, usize>,
-    original_cost: &HashMap<String, f64>,
+// Base finale des deux phases. Elle est factorisée sur la matrice mise à l'échelle,
+// les valeurs, duales et lignes du tableau sont ramenées à l'échelle du fichier.
+struct SolvedTableau {
+    matrix: CscMatrix,
+    scaled: Option<(CscMatrix, Scaling)>,
+    // Coûts de la phase 2 par colonne, à l'échelle de la base
+    costs: Vec<f64>,
+    basis: Basis,
+    variables: Vec<Variable>,
+    vars_hash_map: HashMap<String, usize>,
     is_min: f64,
-) -> Result<(Vec<(String, f64)>, f64), String> {
-    rayon::ThreadPoolBuilder::new()
-    .num_threads(4)
-    .build_global()
-    .unwrap();
-    two_phases(
+}
+
+impl SolvedTableau {
+    fn working_matrix(&self) -> &CscMatrix {
+        self.scaled
+            .as_ref()
+            .map_or(&self.matrix, |(matrix, _)| matrix)
+    }
+
+    // Valeur de chaque colonne, 0 hors base
+    fn values(&self) -> Vec<f64> {
+        let mut values = self.basis.column_values(self.matrix.columns());
+        if let Some((_, scaling)) = &self.scaled {
+            scaling.unscale_values(&mut values);
+        }
+        values
+    }
+
+    // Duales y = c_B B^-1 de chaque ligne
+    fn duals(&self) -> Vec<f64> {
+        let mut duals = self.basis.duals(&self.costs);
+        if let Some((_, scaling)) = &self.scaled {
+            scaling.unscale_duals(&mut duals);
+        }
+        duals.iter().map(|dual| self.is_min * dual).collect()
+    }
+
+    // Ligne ligne du tableau B^-1 [b A]
+    fn row(&self, ligne: usize) -> Vec<f64> {
+        let mut row = self.basis.row(self.working_matrix(), ligne);
+        if let Some((_, scaling)) = &self.scaled {
+            scaling.unscale_row(&mut row, self.basis.heads[ligne]);
+        }
+        row
+    }
+}
+
+// Deux phases sur la matrice mise à l'échelle, où se font aussi les vérifications de faisabilité.
+// Avec les points intérieurs, les deux phases partent du crossover de leur solution. Si network
+// est vrai et que la matrice est celle d'un réseau, elles partent de l'arbre du simplexe réseau.
+// Une base enregistrée start passe avant les deux.
+fn solve_tableau(
+    lp: &str,
+    scaling: bool,
+    solver: Solver,
+    network: bool,
+    start: Option<&StoredBasis>,
+) -> Result<SolvedTableau, String> {
+    let (matrix, mut variables, is_min, vars_hash_map, original_cost) = parse_lp_two_phases(lp)?;
+    let logicals = logical_columns(&variables, matrix.rows);
+    let mut costs = vec![0.0; matrix.columns()];
+    for (name, index) in &vars_hash_map {
+        costs[variables[*index].column] = original_cost[name];
+    }
+    for var in variables.iter_mut() {
+        var.cout_original = costs[var.column];
+    }
+    let heads = start.map(|basis| basis.heads(&matrix, &variables, &vars_hash_map));
+    // Le simplexe réseau travaille sur les coefficients ±1 du fichier, avant la mise à l'échelle
+    let tree = Network::detect(&matrix)
+        .filter(|_| network && heads.is_none())
+        .map(|network| {
+            let mut is_artificial = vec![false; matrix.columns()];
+            for var in &variables {
+                is_artificial[var.column] = var.is_artificial;
+            }
+            let (heads, pivots) = network.solve(&costs, &is_artificial, &logicals);
+            log::info!("Network simplex: {} pivots", pivots);
+            heads
+        });
+    let scaling = if scaling {
+        Some(Scaling::new(&matrix, &variables)).filter(|s| !s.is_identity())
+    } else {
+        None
+    };
+    let scaled = scaling.map(|scaling| {
+        let mut scaled_matrix = matrix.clone();
+        scaling.apply(&mut scaled_matrix, &mut costs);
+        (scaled_matrix, scaling)
+    });
+    let working_matrix = scaled.as_ref().map_or(&matrix, |(matrix, _)| matrix);
+    let point = match solver {
+        _ if tree.is_some() || heads.is_some() => None,
+        Solver::Simplex => None,
+        Solver::InteriorPoint => {
+            let mut used = vec![false; matrix.columns()];
+            for var in variables.iter().filter(|v| !v.is_artificial) {
+                used[var.column] = true;
+            }
+            match interior_point::solve(working_matrix, &costs, &used) {
+                Ok(point) => {
+                    log::info!("Interior point: {} iterations", point.iterations);
+                    Some(point.x)
+                }
+                Err(_) => {
+                    log::warn!("Interior point did not converge, solving with the simplex");
+                    None
+                }
+            }
+        }
+    };
+    let start = match (&heads, &tree, &point) {
+        (Some(heads), _, _) | (None, Some(heads), _) => Start::Basis(heads),
+        (None, None, Some(x)) => Start::Point(x),
+        (None, None, None) => Start::Logical,
+    };
+    let (status, basis) = two_phases(working_matrix, &variables, &logicals, &costs, start);
+    if status == Status::Infeasible || !check_constraints(working_matrix, &basis, &variables) {
+        return Err("Le problème est infaisable".to_string());
+    }
+    if status == Status::Unbounded {
+        return Err("Le problème est non borné".to_string());
+    }
+    basis.sync(&mut variables);
+    Ok(SolvedTableau {
         matrix,
+        scaled,
+        costs,
+        basis,
         variables,
         vars_hash_map,
-        original_cost,
-        false,
         is_min,
-    );
-    // if !borned {
-    //     return Err("Le problème est non borné".to_string());
-    // }
-    let solved = check_all_constraints(matrix, variables);
-    if !solved {
-        return Err("Le problème est infaisable".to_string());
+    })
+}
+
+// Résout le programme lp par les deux phases, depuis la base start si elle est donnée.
+// Rend les valeurs des variables de model, qui décrit le même programme, les duales de
+// ses lignes et la base optimale.
+#[inline(always)]
+fn solve_system_two_phases(
+    lp: &str,
+    model: &LpModel,
+    options: &SimplexOptions,
+    start: Option<&StoredBasis>,
+) -> Result<(Vec<f64>, Vec<f64>, StoredBasis), String> {
+    // Le pool global n'est construit qu'au premier appel
+    let _ = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build_global();
+    let tableau = solve_tableau(lp, options.scaling, options.solver, options.network, start)?;
+    let (vars_string, _) = get_solution(
+        &tableau.values(),
+        &tableau.variables,
+        &tableau.vars_hash_map,
+        tableau.is_min,
+    )?;
+    let mut duals = tableau.duals();
+    // Les lignes de second membre négatif ont été multipliées par -1 à la lecture
+    for (dual, row) in duals.iter_mut().zip(&model.rows) {
+        if row.rhs < 0.0 {
+            *dual = -*dual;
+        }
     }
-    get_solution(matrix, variables, vars_hash_map, is_min)
+    let basis = StoredBasis::from_heads(
+        &tableau.basis.heads,
+        &tableau.matrix,
+        &tableau.variables,
+        &tableau.vars_hash_map,
+        model,
+    );
+    Ok((model.values(&vars_string), duals, basis))
 }
 
+// Valeurs des variables du fichier, values est indicé par colonne
 #[inline(always)]
-fn get_solution(
-    matrix: &[Vec<f64>],
+pub(crate) fn get_solution(
+    values: &[f64],
     variables: &[Variable],
     vars_hash_map: &HashMap<String, usize>,
     is_min: f64,
 ) -> Result<(Vec<(String, f64)>, f64), String> {
+    let mut names = vec![""; variables.len()];
+    for (name, index) in vars_hash_map {
+        names[*index] = name;
+    }
     let mut vars_string = Vec::with_capacity(variables.len());
     let mut z = 0.0;
-    for (index, var) in variables.iter().enumerate() {
+    for (name, var) in names.iter().zip(variables) {
         if var.is_slack || var.is_artificial {
             continue;
         }
-        vars_string.push((
-            vars_hash_map
-                .iter()
-                .find(|(_, y)| **y == index)
-                .unwrap()
-                .0
-                .to_string(),
-            if var.in_base {
-                matrix[var.ligne][0]
-            } else {
-                0.0
-            },
-        ));
-        if var.in_base {
-            z += var.cout_original * matrix[var.ligne][0];
-        }
+        vars_string.push((name.to_string(), values[var.column]));
+        z += var.cout_original * values[var.column];
     }
     let z = is_min * z;
     Ok((vars_string, z))
 }
 
+// Vérifie A x = b ligne par ligne, artificielles à 0, et x >= 0
 #[inline(always)]
-fn get_objective(matrix: &[Vec<f64>], variables: &[Variable], is_min: f64) -> f64 {
-    let mut z = 0.0;
-    for var in variables.iter() {
-        if var.in_base {
-            z += var.cout_original * matrix[var.ligne][0];
-        }
+pub(crate) fn check_constraints(matrix: &CscMatrix, basis: &Basis, variables: &[Variable]) -> bool {
+    let mut values = basis.column_values(matrix.columns());
+    for var in variables.iter().filter(|v| v.is_artificial) {
+        values[var.column] = 0.0;
     }
-    z * is_min
-}
-
-#[inline(always)]
-fn check_all_constraints(matrix: &[Vec<f64>], variables: &[Variable]) -> bool {
-    let mut vars_sorted_by_column = variables.iter().collect::<Vec<_>>();
-    vars_sorted_by_column.sort_by(|a, b| a.column.cmp(&b.column));
-    for ligne in matrix.iter() {
-        let val = ligne[0];
-        let scalar = scalar_product(
-            &ligne.iter().skip(1).copied().collect::<Vec<_>>(),
-            &vars_sorted_by_column
-                .iter()
-                .map(|x| {
-                    if !x.in_base {
-                        0.0
-                    } else {
-                        matrix[x.ligne][0]
-                            * (if x.in_base && !x.is_artificial {
-                                1.0
-                            } else {
-                                0.0
-                            })
-                    }
-                })
-                .collect::<Vec<_>>(),
-        );
-        if (val - scalar).abs() > PRECISION {
-            return false;
+    if values.iter().skip(1).any(|x| *x < -PRECISION) {
+        return false;
+    }
+    let mut activity = vec![0.0; matrix.rows];
+    for (j, x) in values
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, x)| **x != 0.0)
+    {
+        for (i, a) in matrix.column(j) {
+            activity[i] += a * x;
         }
     }
-    true
+    let rhs = matrix.dense_column(0);
+    activity
+        .iter()
+        .zip(&rhs)
+        .all(|(activity, b)| (activity - b).abs() <= PRECISION * (1.0 + b.abs()))
 }
 
 #[inline(always)]
-pub fn parse_lp_bigm(
-    filename: &str,
-) -> Result<(Vec<Vec<f64>>, Vec<Variable>, f64, HashMap<String, usize>), String> {
+pub fn parse_lp_two_phases(filename: &str) -> Result<TwoPhasesProblem, String> {
     let file = match LPParser::parse(Rule::program, filename) {
         Ok(mut file) => file.next().unwrap(),
         Err(e) => {
             return Err(format!("Error parsing file: {}", e));
         }
     };
-    let mut matrix = vec![];
+    let mut rows = vec![];
     let mut variables = HashMap::new();
     let mut count_slack = 0;
     let mut count_artificial = 0;
@@ -154,16 +425,15 @@ pub fn parse_lp_bigm(
     let mut is_min = 1.0;
     let mut current_row = 0;
     let mut var_list = vec![];
+    let mut orignal_cost = HashMap::new();
     for line in file.into_inner() {
         match line.as_rule() {
             Rule::function => {
                 let mut cost = 0.0;
                 for token in line.into_inner() {
                     match token.as_rule() {
-                        Rule::obj => {
-                            if token.as_str() == "min" {
-                                is_min = -1.0;
-                            }
+                        Rule::obj if token.as_str() == "min" => {
+                            is_min = -1.0;
                         }
                         Rule::coeff => {
                             let coeff_str = token.as_str();
@@ -197,16 +467,19 @@ pub fn parse_lp_bigm(
                                 variables.insert(var_name.to_string(), var_list.len());
                                 var_list.push(Variable {
                                     in_base: false,
-                                    cout_original: cost,
+                                    cout_original: 0.0,
                                     ligne: usize::MAX,
                                     column: current_col,
                                     is_slack: false,
                                     is_artificial: false,
                                 });
+                                orignal_cost.insert(var_name.to_string(), cost);
                                 current_col += 1;
                             } else {
                                 let var = variables.get_mut(var_name).unwrap();
                                 var_list[*var].cout_original += cost;
+                                orignal_cost
+                                    .insert(var_name.to_string(), var_list[*var].cout_original);
                             }
                         }
                         _ => {}
@@ -239,33 +512,29 @@ pub fn parse_lp_bigm(
                                 coeff_str = "1";
                             }
                             let coeff = coeff_str.parse::<f64>().unwrap() * sign;
-                            if let Some(var_token) = tokens.next() {
-                                if var_token.as_rule() == Rule::varname {
-                                    let var_name = var_token.as_str().trim();
-                                    let column = if let Some(var) = variables.get(var_name) {
-                                        var_list[*var].column
-                                    } else {
-                                        // variables.insert(var_name.to_string(), Variable {
-                                        //     in_base: false,
-                                        //     cout_original: 0.0,
-                                        //     ligne: usize::MAX,
-                                        //     column: current_col,
-                                        //     is_slack: false,
-                                        //     is_artificial: false,
-                                        // });
-                                        variables.insert(var_name.to_string(), var_list.len());
-                                        var_list.push(Variable {
-                                            in_base: false,
-                                            cout_original: 0.0,
-                                            ligne: usize::MAX,
-                                            column: current_col,
-                                            is_slack: false,
-                                            is_artificial: false,
-                                        });
-                                        current_col += 1;
-                                        current_col - 1
-                                    };
-                                    constraint_vars.push((column, coeff));
+                            if let Some(var_token) = tokens.next()
+                                && var_token.as_rule() == Rule::varname
+                            {
+                                let var_name = var_token.as_str().trim();
+                                let column = if let Some(var) = variables.get(var_name) {
+                                    var_list[*var].column
+                                } else {
+                                    variables.insert(var_name.to_string(), var_list.len());
+                                    var_list.push(Variable {
+                                        in_base: false,
+                                        cout_original: 0.0,
+                                        ligne: usize::MAX,
+                                        column: current_col,
+                                        is_slack: false,
+                                        is_artificial: false,
+                                    });
+                                    orignal_cost.insert(var_name.to_string(), 0.0);
+                                    current_col += 1;
+                                    current_col - 1
+                                };
+                                match constraint_vars.iter_mut().find(|(j, _)| *j == column) {
+                                    Some((_, a)) => *a += coeff,
+                                    None => constraint_vars.push((column, coeff)),
                                 }
                             }
                         }
@@ -278,21 +547,26 @@ pub fn parse_lp_bigm(
                         _ => {}
                     }
                 }
-                let mut row = vec![0.0];
-                for (col, coeff) in constraint_vars {
-                    if col >= row.len() {
-                        row.resize(col + 1, 0.0);
-                    }
-                    row[col] = coeff;
+                // Un second membre négatif est ramené à un second membre positif
+                if rhs < 0.0 {
+                    rhs = -rhs;
+                    constraint_vars
+                        .iter_mut()
+                        .for_each(|(_, coeff)| *coeff = -*coeff);
+                    relation = match relation {
+                        Some(Rule::leq) => Some(Rule::geq),
+                        Some(Rule::geq) => Some(Rule::leq),
+                        relation => relation,
+                    };
                 }
+                // Ligne creuse (colonne, coefficient), le second membre en colonne 0
+                let mut row = vec![(0, rhs)];
+                row.extend(constraint_vars);
                 match relation {
                     Some(Rule::leq) => {
                         let slack_name = format!("s{}", count_slack);
                         let slack_col = current_col;
-                        if slack_col >= row.len() {
-                            row.resize(slack_col + 1, 0.0);
-                        }
-                        row[slack_col] = 1.0;
+                        row.push((slack_col, 1.0));
                         // variables.insert(slack_name, Variable {
                         //     in_base: true,
                         //     cout_original: 0.0,
@@ -301,7 +575,7 @@ pub fn parse_lp_bigm(
                         //     is_slack: true,
                         //     is_artificial: false,
                         // });
-                        variables.insert(slack_name, var_list.len());
+                        variables.insert(slack_name.to_string(), var_list.len());
                         var_list.push(Variable {
                             in_base: true,
                             cout_original: 0.0,
@@ -310,6 +584,7 @@ pub fn parse_lp_bigm(
                             is_slack: true,
                             is_artificial: false,
                         });
+                        orignal_cost.insert(slack_name, 0.0);
                         current_row += 1;
                         count_slack += 1;
                         current_col += 1;
@@ -319,10 +594,7 @@ pub fn parse_lp_bigm(
                         let art_name = format!("a{}", count_artificial);
 
                         let slack_col = current_col;
-                        if slack_col >= row.len() {
-                            row.resize(slack_col + 1, 0.0);
-                        }
-                        row[slack_col] = -1.0;
+                        row.push((slack_col, -1.0));
                         // variables.insert(slack_name, Variable {
                         //     in_base: false,
                         //     cout_original: 0.0,
@@ -331,7 +603,7 @@ pub fn parse_lp_bigm(
                         //     is_slack: true,
                         //     is_artificial: false,
                         // });
-                        variables.insert(slack_name, var_list.len());
+                        variables.insert(slack_name.to_string(), var_list.len());
                         var_list.push(Variable {
                             in_base: false,
                             cout_original: 0.0,
@@ -340,31 +612,22 @@ pub fn parse_lp_bigm(
                             is_slack: true,
                             is_artificial: false,
                         });
+                        orignal_cost.insert(slack_name, 0.0);
                         count_slack += 1;
                         current_col += 1;
 
                         let art_col = current_col;
-                        if art_col >= row.len() {
-                            row.resize(art_col + 1, 0.0);
-                        }
-                        row[art_col] = 1.0;
-                        // variables.insert(art_name, Variable {
-                        //     in_base: true,
-                        //     cout_original: -1.0e12,
-                        //     ligne: current_row,
-                        //     column: art_col,
-                        //     is_slack: false,
-                        //     is_artificial: true,
-                        //     });
-                        variables.insert(art_name, var_list.len());
+                        row.push((art_col, 1.0));
+                        variables.insert(art_name.to_string(), var_list.len());
                         var_list.push(Variable {
                             in_base: true,
-                            cout_original: -1.0e12,
+                            cout_original: -1.0,
                             ligne: current_row,
                             column: art_col,
                             is_slack: false,
                             is_artificial: true,
                         });
+                        orignal_cost.insert(art_name, 0.0);
                         current_row += 1;
                         count_artificial += 1;
                         current_col += 1;
@@ -372,800 +635,1811 @@ pub fn parse_lp_bigm(
                     Some(Rule::eq) => {
                         let art_name = format!("a{}", count_artificial);
                         let art_col = current_col;
-                        if art_col >= row.len() {
-                            row.resize(art_col + 1, 0.0);
-                        }
-                        row[art_col] = 1.0;
-                        // variables.insert(art_name, Variable {
-                        //     in_base: true,
-                        //     cout_original: -1.0e12,
-                        //     ligne: current_row,
-                        //     column: art_col,
-                        //     is_slack: false,
-                        //     is_artificial: true,
-                        // });
-                        variables.insert(art_name, var_list.len());
+                        row.push((art_col, 1.0));
+                        variables.insert(art_name.to_string(), var_list.len());
                         var_list.push(Variable {
                             in_base: true,
-                            cout_original: -1.0e12,
+                            cout_original: -1.0,
                             ligne: current_row,
                             column: art_col,
                             is_slack: false,
                             is_artificial: true,
                         });
+                        orignal_cost.insert(art_name, 0.0);
                         current_row += 1;
                         count_artificial += 1;
                         current_col += 1;
                     }
                     _ => {}
                 }
-                row[0] = rhs;
-                matrix.push(row);
+                rows.push(row);
             }
+            Rule::int_decl | Rule::priority_decl => {}
             _ => {
-                println!("Unknown rule: {:?}", line.as_rule());
+                log::warn!("Unknown rule: {:?}", line.as_rule());
+            }
+        }
+    }
+    let matrix = CscMatrix::from_rows(&rows, current_col);
+    Ok((matrix, var_list, is_min, variables, orignal_cost))
+}
+
+// Nombre d'etas au-delà duquel la base est refactorisée
+pub(crate) const REFACTOR_FREQUENCY: usize = 100;
+
+// Issue des deux phases
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Status {
+    Optimal,
+    Infeasible,
+    Unbounded,
+}
+
+// Colonne logique de chaque ligne : l'écart ou l'artificielle de coefficient +1
+// qui forme la base initiale
+pub(crate) fn logical_columns(variables: &[Variable], rows: usize) -> Vec<usize> {
+    let mut logicals = vec![0; rows];
+    for var in variables.iter().filter(|v| v.in_base) {
+        logicals[var.ligne] = var.column;
+    }
+    logicals
+}
+
+// Base du simplexe révisé : colonne de base de chaque position (la ligne du tableau),
+// valeurs x_B = B^-1 b et inverse de B sous forme produit
+#[derive(Debug, Clone)]
+pub(crate) struct Basis {
+    pub(crate) heads: Vec<usize>,
+    pub(crate) values: Vec<f64>,
+    pub(crate) inverse: EtaFile,
+}
+
+impl Basis {
+    // Factorise la base formée des colonnes heads en partant de l'identité des colonnes
+    // logiques : chaque colonne de base non logique prend la position libre de plus grand pivot.
+    // Une colonne dépendante des précédentes est remplacée par la logique de la position.
+    pub(crate) fn factor(matrix: &CscMatrix, heads: &[usize], logicals: &[usize]) -> Basis {
+        let mut is_head = vec![false; matrix.columns()];
+        for j in heads {
+            is_head[*j] = true;
+        }
+        let mut is_logical = vec![false; matrix.columns()];
+        for j in logicals {
+            is_logical[*j] = true;
+        }
+        let mut free = logicals.iter().map(|j| !is_head[*j]).collect::<Vec<_>>();
+        let mut structurals = heads
+            .iter()
+            .copied()
+            .filter(|j| !is_logical[*j])
+            .collect::<Vec<_>>();
+        // Les colonnes les plus creuses d'abord pour limiter le remplissage des etas
+        structurals.sort_by_key(|j| matrix.start[j + 1] - matrix.start[*j]);
+        let mut basis = Basis {
+            heads: logicals.to_vec(),
+            values: vec![],
+            inverse: EtaFile::default(),
+        };
+        for j in structurals {
+            let alpha = basis.column(matrix, j);
+            let position = (0..alpha.len())
+                .filter(|p| free[*p] && alpha[*p].abs() > PRECISION)
+                .max_by(|a, b| alpha[*a].abs().total_cmp(&alpha[*b].abs()));
+            if let Some(p) = position {
+                basis.inverse.push(p, &alpha);
+                basis.heads[p] = j;
+                free[p] = false;
             }
         }
+        basis.values = basis.column(matrix, 0);
+        basis
     }
-    let max_len = matrix.iter().map(|row| row.len()).max().unwrap_or(0);
-    for row in &mut matrix {
-        if row.len() < max_len {
-            row.resize(max_len, 0.0);
+
+    // B^-1 a_j
+    pub(crate) fn column(&self, matrix: &CscMatrix, j: usize) -> Vec<f64> {
+        let mut alpha = matrix.dense_column(j);
+        self.inverse.ftran(&mut alpha);
+        alpha
+    }
+
+    // c_B B^-1, costs est indicé par colonne
+    pub(crate) fn duals(&self, costs: &[f64]) -> Vec<f64> {
+        let mut y = self.heads.iter().map(|j| costs[*j]).collect::<Vec<_>>();
+        self.inverse.btran(&mut y);
+        y
+    }
+
+    // Ligne p du tableau B^-1 [b A], calculée à la demande
+    pub(crate) fn row(&self, matrix: &CscMatrix, p: usize) -> Vec<f64> {
+        let mut rho = vec![0.0; self.heads.len()];
+        rho[p] = 1.0;
+        self.inverse.btran(&mut rho);
+        (0..matrix.columns())
+            .into_par_iter()
+            .map(|j| matrix.dot_column(j, &rho))
+            .collect()
+    }
+
+    // Valeur de chaque colonne, 0 hors base
+    pub(crate) fn column_values(&self, width: usize) -> Vec<f64> {
+        let mut values = vec![0.0; width];
+        for (j, x) in self.heads.iter().zip(&self.values) {
+            values[*j] = *x;
+        }
+        values
+    }
+
+    // Fait entrer la colonne j, de colonne alpha = B^-1 a_j, à la position p
+    pub(crate) fn pivot(&mut self, p: usize, j: usize, alpha: &[f64]) {
+        let theta = self.values[p] / alpha[p];
+        for (x, a) in self.values.iter_mut().zip(alpha) {
+            *x -= theta * a;
+        }
+        self.values[p] = theta;
+        self.inverse.push(p, alpha);
+        self.heads[p] = j;
+    }
+
+    // Reporte la base dans les variables
+    fn sync(&self, variables: &mut [Variable]) {
+        let width = variables.iter().map(|v| v.column + 1).max().unwrap_or(0);
+        let mut position = vec![usize::MAX; width];
+        for (p, j) in self.heads.iter().enumerate() {
+            position[*j] = p;
+        }
+        for var in variables.iter_mut() {
+            var.ligne = position[var.column];
+            var.in_base = var.ligne != usize::MAX;
         }
     }
-    Ok((matrix, var_list, is_min, variables))
 }
 
-#[inline(always)]
-pub fn parse_lp_two_phases(
-    filename: &str,
-) -> Result<
-    (
-        Vec<Vec<f64>>,
-        Vec<Variable>,
-        f64,
-        HashMap<String, usize>,
-        HashMap<String, f64>,
-    ),
-    String,
-> {
-    let file = match LPParser::parse(Rule::program, filename) {
-        Ok(mut file) => file.next().unwrap(),
-        Err(e) => {
-            return Err(format!("Error parsing file: {}", e));
+// Simplexe révisé sur [b A] : les coûts réduits sont calculés en parallèle sur les colonnes
+// creuses avec y = c_B B^-1, seule la colonne entrante est transformée par B^-1
+struct RevisedSimplex<'a> {
+    matrix: &'a CscMatrix,
+    logicals: &'a [usize],
+    is_artificial: Vec<bool>,
+    in_base: Vec<bool>,
+    // Colonne complémentaire de chaque colonne (méthode de Wolfe) : une colonne n'entre pas
+    // tant que sa complémentaire est dans la base. usize::MAX sans complémentaire.
+    complement: Vec<usize>,
+    basis: Basis,
+    iterations: usize,
+}
+
+impl<'a> RevisedSimplex<'a> {
+    fn new(matrix: &'a CscMatrix, variables: &[Variable], logicals: &'a [usize]) -> Self {
+        let mut is_artificial = vec![false; matrix.columns()];
+        for var in variables {
+            is_artificial[var.column] = var.is_artificial;
         }
-    };
-    let mut matrix = vec![];
-    let mut variables = HashMap::new();
-    let mut count_slack = 0;
-    let mut count_artificial = 0;
-    let mut current_col = 1;
-    let mut is_min = 1.0;
-    let mut current_row = 0;
-    let mut var_list = vec![];
-    let mut orignal_cost = HashMap::new();
-    for line in file.into_inner() {
-        match line.as_rule() {
-            Rule::function => {
-                let mut cost = 0.0;
-                for token in line.into_inner() {
-                    match token.as_rule() {
-                        Rule::obj => {
-                            if token.as_str() == "min" {
-                                is_min = -1.0;
-                            }
-                        }
-                        Rule::coeff => {
-                            let coeff_str = token.as_str();
-                            let (sign, mut coeff_str) = match coeff_str {
-                                _ if coeff_str.starts_with('+') => (1.0, coeff_str[1..].trim()),
-                                _ if coeff_str.starts_with('-') => (-1.0, coeff_str[1..].trim()),
-                                _ => {
-                                    if coeff_str.chars().count() == 0 {
-                                        (1.0, "1")
-                                    } else {
-                                        (1.0, coeff_str)
-                                    }
-                                }
-                            };
-                            if coeff_str.is_empty() {
-                                coeff_str = "1";
-                            }
-                            cost = coeff_str.trim().parse::<f64>().unwrap() * sign * is_min;
-                        }
-                        Rule::varname => {
-                            let var_name = token.as_str().trim();
-                            if !variables.contains_key(var_name) {
-                                // variables.insert(var_name.to_string(), Variable {
-                                //     in_base: false,
-                                //     cout_original: cost,
-                                //     ligne: usize::MAX,
-                                //     column: current_col,
-                                //     is_slack: false,
-                                //     is_artificial: false,
-                                // });
-                                variables.insert(var_name.to_string(), var_list.len());
-                                var_list.push(Variable {
-                                    in_base: false,
-                                    cout_original: 0.0,
-                                    ligne: usize::MAX,
-                                    column: current_col,
-                                    is_slack: false,
-                                    is_artificial: false,
-                                });
-                                orignal_cost.insert(var_name.to_string(), cost);
-                                current_col += 1;
-                            } else {
-                                let var = variables.get_mut(var_name).unwrap();
-                                var_list[*var].cout_original += cost;
-                                orignal_cost
-                                    .insert(var_name.to_string(), var_list[*var].cout_original);
-                            }
-                        }
-                        _ => {}
+        let basis = Basis::factor(matrix, logicals, logicals);
+        let mut simplex = RevisedSimplex {
+            matrix,
+            logicals,
+            is_artificial,
+            in_base: vec![],
+            complement: vec![usize::MAX; matrix.columns()],
+            basis,
+            iterations: 1,
+        };
+        simplex.update_in_base();
+        simplex
+    }
+
+    fn update_in_base(&mut self) {
+        self.in_base = vec![false; self.matrix.columns()];
+        for j in &self.basis.heads {
+            self.in_base[*j] = true;
+        }
+    }
+
+    // Somme des artificielles de base, objectif de la phase 1
+    fn infeasibility(&self) -> f64 {
+        self.basis
+            .heads
+            .iter()
+            .zip(&self.basis.values)
+            .filter(|(j, _)| self.is_artificial[**j])
+            .map(|(_, x)| x)
+            .sum()
+    }
+
+    // x_B = B^-1 (b - somme des a_j x_j des colonnes hors base non nulles)
+    fn residual_values(&self, superbasics: &[(usize, f64)]) -> Vec<f64> {
+        let mut values = self.matrix.dense_column(0);
+        for (j, x) in superbasics {
+            for (i, a) in self.matrix.column(*j) {
+                values[i] -= a * x;
+            }
+        }
+        self.basis.inverse.ftran(&mut values);
+        values
+    }
+
+    // Part de la base formée des colonnes heads. Rend false si elle n'est pas réalisable.
+    fn warm_start(&mut self, heads: &[usize]) -> bool {
+        self.basis = Basis::factor(self.matrix, heads, self.logicals);
+        self.update_in_base();
+        self.basis.values.iter().all(|v| *v >= -PRECISION)
+    }
+
+    // Crossover depuis un point intérieur x (par colonne) : les plus grandes colonnes forment
+    // la base, les autres colonnes non nulles sont ramenées à 0 une par une en gardant
+    // A x = b et x >= 0. Une variable de base qui s'annule avant cède sa place à la colonne
+    // poussée. Rend false si x est trop loin d'être réalisable pour cette base.
+    fn crossover(&mut self, x: &[f64]) -> bool {
+        let mut candidates = (1..x.len())
+            .filter(|j| !self.is_artificial[*j] && x[*j] > PRECISION)
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| x[*b].total_cmp(&x[*a]));
+        candidates.truncate(self.basis.heads.len());
+        self.basis = Basis::factor(self.matrix, &candidates, self.logicals);
+        self.update_in_base();
+        let mut superbasics = (1..x.len())
+            .filter(|j| !self.in_base[*j] && !self.is_artificial[*j] && x[*j] > 0.0)
+            .map(|j| (j, x[j]))
+            .collect::<Vec<_>>();
+        self.basis.values = self.residual_values(&superbasics);
+        if self.basis.values.iter().any(|v| *v < -PRECISION) {
+            return false;
+        }
+        // Les plus petites valeurs d'abord
+        superbasics.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let (pushed, mut entered) = (superbasics.len(), 0);
+        while let Some((j, value)) = superbasics.pop() {
+            let alpha = self.basis.column(self.matrix, j);
+            // x_j diminue de t, x_B augmente de t alpha
+            let blocking = alpha
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| **a < -PRECISION)
+                .map(|(p, a)| (p, self.basis.values[p].max(0.0) / -a))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let step = blocking.map_or(value, |(_, t)| t.min(value));
+            for (v, a) in self.basis.values.iter_mut().zip(&alpha) {
+                *v += step * a;
+            }
+            let Some((p, _)) = blocking.filter(|(_, t)| *t < value) else {
+                continue;
+            };
+            self.basis.values[p] = value - step;
+            self.basis.inverse.push(p, &alpha);
+            self.in_base[self.basis.heads[p]] = false;
+            self.in_base[j] = true;
+            self.basis.heads[p] = j;
+            entered += 1;
+            if self.basis.inverse.len() >= REFACTOR_FREQUENCY {
+                let previous = self
+                    .basis
+                    .heads
+                    .iter()
+                    .copied()
+                    .zip(self.basis.values.clone())
+                    .collect::<Vec<_>>();
+                self.basis = Basis::factor(self.matrix, &self.basis.heads, self.logicals);
+                self.update_in_base();
+                // Une colonne écartée par la refactorisation garde sa valeur hors base
+                superbasics.extend(
+                    previous
+                        .into_iter()
+                        .filter(|(j, value)| !self.in_base[*j] && *value > 0.0),
+                );
+                self.basis.values = self.residual_values(&superbasics);
+            }
+        }
+        log::info!(
+            "Crossover: {} columns pushed, {} entered the basis",
+            pushed,
+            entered
+        );
+        true
+    }
+
+    // Plus petit rapport x_B / alpha ; à égalité la phase 1 fait sortir une artificielle
+    fn ratio_test(&self, alpha: &[f64], phase_one: bool) -> Option<usize> {
+        let mut leaving: Option<(usize, f64)> = None;
+        for (p, a) in alpha.iter().enumerate().filter(|(_, a)| **a > PRECISION) {
+            let ratio = self.basis.values[p].max(0.0) / a;
+            match leaving {
+                Some((_, min)) if ratio > min => {}
+                Some((q, min)) if ratio == min => {
+                    if phase_one
+                        && self.is_artificial[self.basis.heads[p]]
+                        && !self.is_artificial[self.basis.heads[q]]
+                    {
+                        leaving = Some((p, ratio));
                     }
                 }
+                _ => leaving = Some((p, ratio)),
             }
+        }
+        leaving.map(|(p, _)| p)
+    }
 
-            Rule::constraint => {
-                let mut constraint_vars: Vec<(usize, f64)> = vec![];
-                let mut rhs = 0.0;
-                let mut relation: Option<Rule> = None;
+    fn pivot(&mut self, p: usize, j: usize, alpha: &[f64]) {
+        self.in_base[self.basis.heads[p]] = false;
+        self.in_base[j] = true;
+        self.basis.pivot(p, j, alpha);
+        if self.basis.inverse.len() >= REFACTOR_FREQUENCY {
+            self.basis = Basis::factor(self.matrix, &self.basis.heads, self.logicals);
+            self.update_in_base();
+        }
+    }
 
-                let mut tokens = line.into_inner().peekable();
-                while let Some(token) = tokens.next() {
-                    match token.as_rule() {
-                        Rule::coeff => {
-                            let coeff_str = token.as_str().trim();
-                            let (sign, mut coeff_str) = match coeff_str {
-                                _ if coeff_str.starts_with('+') => (1.0, coeff_str[1..].trim()),
-                                _ if coeff_str.starts_with('-') => (-1.0, coeff_str[1..].trim()),
-                                _ => {
-                                    if coeff_str.chars().count() == 0 {
-                                        (1.0, "1")
-                                    } else {
-                                        (1.0, coeff_str)
-                                    }
-                                }
-                            };
-                            if coeff_str.is_empty() {
-                                coeff_str = "1";
-                            }
-                            let coeff = coeff_str.parse::<f64>().unwrap() * sign;
-                            if let Some(var_token) = tokens.next() {
-                                if var_token.as_rule() == Rule::varname {
-                                    let var_name = var_token.as_str().trim();
-                                    let column = if let Some(var) = variables.get(var_name) {
-                                        var_list[*var].column
-                                    } else {
-                                        variables.insert(var_name.to_string(), var_list.len());
-                                        var_list.push(Variable {
-                                            in_base: false,
-                                            cout_original: 0.0,
-                                            ligne: usize::MAX,
-                                            column: current_col,
-                                            is_slack: false,
-                                            is_artificial: false,
-                                        });
-                                        orignal_cost.insert(var_name.to_string(), 0.0);
-                                        current_col += 1;
-                                        current_col - 1
-                                    };
-                                    constraint_vars.push((column, coeff));
-                                }
-                            }
-                        }
-                        Rule::leq | Rule::geq | Rule::eq => {
-                            relation = Some(token.as_rule());
-                        }
-                        Rule::number => {
-                            rhs = token.as_str().parse::<f64>().unwrap();
-                        }
-                        _ => {}
-                    }
-                }
-                let mut row = vec![0.0];
-                for (col, coeff) in constraint_vars {
-                    if col >= row.len() {
-                        row.resize(col + 1, 0.0);
-                    }
-                    row[col] = coeff;
-                }
-                match relation {
-                    Some(Rule::leq) => {
-                        let slack_name = format!("s{}", count_slack);
-                        let slack_col = current_col;
-                        if slack_col >= row.len() {
-                            row.resize(slack_col + 1, 0.0);
-                        }
-                        row[slack_col] = 1.0;
-                        // variables.insert(slack_name, Variable {
-                        //     in_base: true,
-                        //     cout_original: 0.0,
-                        //     ligne: current_row,
-                        //     column: slack_col,
-                        //     is_slack: true,
-                        //     is_artificial: false,
-                        // });
-                        variables.insert(slack_name.to_string(), var_list.len());
-                        var_list.push(Variable {
-                            in_base: true,
-                            cout_original: 0.0,
-                            ligne: current_row,
-                            column: slack_col,
-                            is_slack: true,
-                            is_artificial: false,
-                        });
-                        orignal_cost.insert(slack_name, 0.0);
-                        current_row += 1;
-                        count_slack += 1;
-                        current_col += 1;
-                    }
-                    Some(Rule::geq) => {
-                        let slack_name = format!("s{}", count_slack);
-                        let art_name = format!("a{}", count_artificial);
+    // Itère jusqu'à l'optimum des coûts donnés par colonne, les artificielles n'entrent jamais.
+    // La phase 1 s'arrête dès que les artificielles sont nulles.
+    fn run(&mut self, costs: &[f64], phase_one: bool) -> Status {
+        loop {
+            if phase_one && self.infeasibility() <= PRECISION {
+                return Status::Optimal;
+            }
+            let y = self.basis.duals(costs);
+            let entering = (1..self.matrix.columns())
+                .into_par_iter()
+                .filter(|j| !self.in_base[*j] && !self.is_artificial[*j])
+                .filter(|j| {
+                    let complement = self.complement[*j];
+                    complement == usize::MAX || !self.in_base[complement]
+                })
+                .map(|j| (j, self.matrix.dot_column(j, &y) - costs[j]))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((j, _)) = entering.filter(|(_, d)| *d < -PRECISION) else {
+                return Status::Optimal;
+            };
+            let alpha = self.basis.column(self.matrix, j);
+            let Some(p) = self.ratio_test(&alpha, phase_one) else {
+                return Status::Unbounded;
+            };
+            self.pivot(p, j, &alpha);
+            if self.iterations.is_multiple_of(10) {
+                progress::notify(Progress::Iteration {
+                    phase: if phase_one {
+                        SimplexPhase::PhaseOne
+                    } else {
+                        SimplexPhase::PhaseTwo
+                    },
+                    iteration: self.iterations,
+                    objective: self.objective(costs),
+                    primal_infeasibility: self.infeasibility(),
+                });
+            }
+            self.iterations += 1;
+        }
+    }
 
-                        let slack_col = current_col;
-                        if slack_col >= row.len() {
-                            row.resize(slack_col + 1, 0.0);
-                        }
-                        row[slack_col] = -1.0;
-                        // variables.insert(slack_name, Variable {
-                        //     in_base: false,
-                        //     cout_original: 0.0,
-                        //     ligne: usize::MAX,
-                        //     column: slack_col,
-                        //     is_slack: true,
-                        //     is_artificial: false,
-                        // });
-                        variables.insert(slack_name.to_string(), var_list.len());
-                        var_list.push(Variable {
-                            in_base: false,
-                            cout_original: 0.0,
-                            ligne: usize::MAX,
-                            column: slack_col,
-                            is_slack: true,
-                            is_artificial: false,
-                        });
-                        orignal_cost.insert(slack_name, 0.0);
-                        count_slack += 1;
-                        current_col += 1;
+    // Valeur c_B x_B de la base pour les coûts donnés
+    fn objective(&self, costs: &[f64]) -> f64 {
+        self.basis
+            .heads
+            .iter()
+            .zip(&self.basis.values)
+            .map(|(j, x)| costs[*j] * x)
+            .sum()
+    }
 
-                        let art_col = current_col;
-                        if art_col >= row.len() {
-                            row.resize(art_col + 1, 0.0);
-                        }
-                        row[art_col] = 1.0;
-                        variables.insert(art_name.to_string(), var_list.len());
-                        var_list.push(Variable {
-                            in_base: true,
-                            cout_original: -1.0,
-                            ligne: current_row,
-                            column: art_col,
-                            is_slack: false,
-                            is_artificial: true,
-                        });
-                        orignal_cost.insert(art_name, 0.0);
-                        current_row += 1;
-                        count_artificial += 1;
-                        current_col += 1;
-                    }
-                    Some(Rule::eq) => {
-                        let art_name = format!("a{}", count_artificial);
-                        let art_col = current_col;
-                        if art_col >= row.len() {
-                            row.resize(art_col + 1, 0.0);
-                        }
-                        row[art_col] = 1.0;
-                        variables.insert(art_name.to_string(), var_list.len());
-                        var_list.push(Variable {
-                            in_base: true,
-                            cout_original: -1.0,
-                            ligne: current_row,
-                            column: art_col,
-                            is_slack: false,
-                            is_artificial: true,
-                        });
-                        orignal_cost.insert(art_name, 0.0);
-                        current_row += 1;
-                        count_artificial += 1;
-                        current_col += 1;
-                    }
-                    _ => {}
-                }
-                row[0] = rhs;
-                matrix.push(row);
+    // Plus grande violation de x_B >= 0, une artificielle de base doit être nulle
+    fn primal_infeasibility(&self) -> f64 {
+        self.basis
+            .heads
+            .iter()
+            .zip(&self.basis.values)
+            .map(|(j, x)| if self.is_artificial[*j] { x.abs() } else { -x })
+            .fold(0.0, f64::max)
+    }
+
+    // Coûts réduits y a_j - c_j positifs sur les colonnes hors base
+    fn is_dual_feasible(&self, costs: &[f64]) -> bool {
+        let y = self.basis.duals(costs);
+        (1..self.matrix.columns())
+            .into_par_iter()
+            .filter(|j| !self.in_base[*j] && !self.is_artificial[*j])
+            .all(|j| self.matrix.dot_column(j, &y) - costs[j] >= -PRECISION)
+    }
+
+    // Simplexe dual depuis une base duale réalisable : la variable de base la plus violée
+    // sort, la colonne entrante est celle du plus petit rapport d_j / |rho_j| parmi les
+    // coefficients de la ligne du bon signe, ce qui garde les coûts réduits positifs.
+    // Une ligne violée sans colonne entrante prouve que le problème est infaisable.
+    fn dual(&mut self, costs: &[f64]) -> Status {
+        let mut pivots = 0usize;
+        let status = loop {
+            let leaving = (0..self.basis.heads.len())
+                .map(|p| {
+                    let x = self.basis.values[p];
+                    let violation = if self.is_artificial[self.basis.heads[p]] {
+                        x.abs()
+                    } else {
+                        -x
+                    };
+                    (p, violation)
+                })
+                .filter(|(_, violation)| *violation > PRECISION)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((p, _)) = leaving else {
+                break Status::Optimal;
+            };
+            // x_p < 0 remonte à 0 avec rho_j < 0, une artificielle positive descend avec rho_j > 0
+            let sign = if self.basis.values[p] < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            let y = self.basis.duals(costs);
+            let row = self.basis.row(self.matrix, p);
+            let entering = (1..self.matrix.columns())
+                .filter(|j| !self.in_base[*j] && !self.is_artificial[*j])
+                .filter(|j| sign * row[*j] > PRECISION)
+                .map(|j| {
+                    let d = (self.matrix.dot_column(j, &y) - costs[j]).max(0.0);
+                    (j, d / row[j].abs())
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((j, _)) = entering else {
+                break Status::Infeasible;
+            };
+            let alpha = self.basis.column(self.matrix, j);
+            self.pivot(p, j, &alpha);
+            pivots += 1;
+            if pivots.is_multiple_of(10) {
+                progress::notify(Progress::Iteration {
+                    phase: SimplexPhase::Dual,
+                    iteration: pivots,
+                    objective: self.objective(costs),
+                    primal_infeasibility: self.primal_infeasibility(),
+                });
             }
-            _ => {
-                println!("Unknown rule: {:?}", line.as_rule());
+        };
+        log::info!("Dual simplex: {} pivots", pivots);
+        status
+    }
+
+    // Après la phase 1, les artificielles restées dans la base au niveau 0 pourraient
+    // redevenir positives en phase 2 : on les remplace par une variable de leur ligne
+    fn drive_out_artificials(&mut self) {
+        for p in 0..self.basis.heads.len() {
+            if !self.is_artificial[self.basis.heads[p]] {
+                continue;
+            }
+            let row = self.basis.row(self.matrix, p);
+            let entering = row
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter(|(j, x)| {
+                    !self.in_base[*j] && !self.is_artificial[*j] && x.abs() > PRECISION
+                })
+                .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+                .map(|(j, _)| j);
+            // Sans candidat la ligne est redondante et l'artificielle reste nulle
+            if let Some(j) = entering {
+                let alpha = self.basis.column(self.matrix, j);
+                self.basis.values[p] = 0.0;
+                self.pivot(p, j, &alpha);
             }
         }
     }
-    let max_len = matrix.iter().map(|row| row.len()).max().unwrap_or(0);
-    for row in &mut matrix {
-        if row.len() < max_len {
-            row.resize(max_len, 0.0);
+}
+
+// Phase 1 des deux phases avec la règle d'entrée restreinte de la méthode de Wolfe : les
+// couples de complements sont des variables du fichier, row_complements associe à une ligne
+// la variable complémentaire de son écart. Rend les variables du fichier d'une solution de
+// A x = b, x >= 0 où chaque couple a au plus une variable non nulle.
+pub(crate) fn complementary_phase_one(
+    lp: &str,
+    complements: &[(String, String)],
+    row_complements: &[(usize, String)],
+) -> Result<Vec<(String, f64)>, String> {
+    let (matrix, variables, is_min, vars_hash_map, _) = parse_lp_two_phases(lp)?;
+    let logicals = logical_columns(&variables, matrix.rows);
+    let mut simplex = RevisedSimplex::new(&matrix, &variables, &logicals);
+    let column = |name: &String| {
+        vars_hash_map
+            .get(name)
+            .map(|index| variables[*index].column)
+            .ok_or(format!("Variable inconnue : {}", name))
+    };
+    for (a, b) in complements {
+        let (a, b) = (column(a)?, column(b)?);
+        simplex.complement[a] = b;
+        simplex.complement[b] = a;
+    }
+    // Écart de chaque ligne : colonne d'écart dont l'unique coefficient est sur la ligne
+    let mut slack_of_row = vec![usize::MAX; matrix.rows];
+    for var in variables.iter().filter(|v| v.is_slack) {
+        if let Some((i, _)) = matrix.column(var.column).next() {
+            slack_of_row[i] = var.column;
         }
     }
-    Ok((matrix, var_list, is_min, variables, orignal_cost))
+    for (row, name) in row_complements {
+        let (a, b) = (column(name)?, slack_of_row[*row]);
+        if b != usize::MAX {
+            simplex.complement[a] = b;
+            simplex.complement[b] = a;
+        }
+    }
+    let phase_one = simplex
+        .is_artificial
+        .iter()
+        .map(|artificial| if *artificial { -1.0 } else { 0.0 })
+        .collect::<Vec<_>>();
+    simplex.run(&phase_one, true);
+    if simplex.infeasibility() > PRECISION {
+        return Err("Aucune solution complémentaire".to_string());
+    }
+    let values = simplex.basis.column_values(matrix.columns());
+    let (vars_string, _) = get_solution(&values, &variables, &vars_hash_map, is_min)?;
+    Ok(vars_string)
 }
 
+// Point de départ des deux phases : base des colonnes logiques, crossover depuis un point
+// intérieur ou base donnée (l'arbre du simplexe réseau, la base d'une session)
+pub(crate) enum Start<'a> {
+    Logical,
+    Point(&'a [f64]),
+    Basis(&'a [usize]),
+}
 
-// Simplex iteration
+// Phase 1 (maximise -somme des artificielles) puis phase 2 sur les coûts donnés par colonne,
+// depuis le point de départ start
 #[inline(always)]
-fn update_array(
-    matrix: &mut [Vec<f64>],
-    variables: &mut [Variable],
-    sorted_by_column: &mut [usize],
-    in_base: &mut [f64],
-    in_phase_one: bool,
-    in_phase_two: bool,
-) -> (bool, bool) {
-    // let mut min = f64::MAX;
-    // let mut min_col_index = 0;
-    // for i in 1..width {
-    //     let y = variables[sorted_by_column[i - 1]];
-    //     if !y.in_base && (!in_phase_two || !y.is_artificial) {
-    //         let scalar = scalar_product_column(in_base, matrix, i) - y.cout_original;
-    //         if scalar < 0.0 {
-    //             min = scalar;
-    //             min_col_index = i;
-    //             break;
-    //         }
-    //     }
-    // }
-    let (min_col_index, min) = sorted_by_column.par_iter().skip(1).enumerate().map(|(i, _)| {
-        let y = variables[sorted_by_column[i]];
-        if !y.in_base && (!in_phase_two || !y.is_artificial) {
-            Some((i+1, scalar_product_column(in_base, matrix, i+1) - y.cout_original))
+pub(crate) fn two_phases(
+    matrix: &CscMatrix,
+    variables: &[Variable],
+    logicals: &[usize],
+    costs: &[f64],
+    start: Start,
+) -> (Status, Basis) {
+    let mut simplex = RevisedSimplex::new(matrix, variables, logicals);
+    match start {
+        Start::Logical => {}
+        Start::Point(x) => {
+            if !simplex.crossover(x) {
+                log::warn!("Crossover failed, solving with the simplex");
+                simplex = RevisedSimplex::new(matrix, variables, logicals);
+            }
         }
-        else {
-            None
+        Start::Basis(heads) => {
+            let feasible = simplex.warm_start(heads);
+            // Une base duale réalisable (un optimum dont le second membre a changé ou qui
+            // a reçu une ligne) est réparée par le simplexe dual, sans phase 1
+            if simplex.primal_infeasibility() > PRECISION && simplex.is_dual_feasible(costs) {
+                if simplex.dual(costs) == Status::Infeasible {
+                    return (Status::Infeasible, simplex.basis);
+                }
+            } else if !feasible {
+                simplex = RevisedSimplex::new(matrix, variables, logicals);
+            }
         }
-    }).filter_map(|x| x).min_by(|a, b| a.1.total_cmp(&b.1)).unwrap_or((0, f64::MAX));
-    if min >= 0.0 {
-        return (true, true);
     }
-    let mut min = f64::MAX;
-    let mut line_index = 0;
-    let mut leaved = false;
-    for (i, item) in matrix.iter().enumerate() {
-        if item[min_col_index] <= 0.0 {
+    let phase_one = simplex
+        .is_artificial
+        .iter()
+        .map(|artificial| if *artificial { -1.0 } else { 0.0 })
+        .collect::<Vec<_>>();
+    simplex.run(&phase_one, true);
+    if simplex.infeasibility() > PRECISION {
+        return (Status::Infeasible, simplex.basis);
+    }
+    simplex.drive_out_artificials();
+    let status = simplex.run(costs, false);
+    (status, simplex.basis)
+}
+
+// Rayon de Farkas d'une base finale infaisable des deux phases : w tel que w a_j <= 0 pour
+// toute colonne non artificielle et w b > 0, qui prouve que A x = b, x >= 0 n'a pas de
+// solution. Ce sont les duales de la phase 1 changées de signe, ou la ligne de B^-1 où le
+// simplexe dual s'est arrêté. w est indicé par ligne de la matrice (lignes retournées).
+pub(crate) fn farkas_ray(
+    matrix: &CscMatrix,
+    variables: &[Variable],
+    basis: &Basis,
+) -> Option<Vec<f64>> {
+    let mut is_artificial = vec![false; matrix.columns()];
+    for var in variables {
+        is_artificial[var.column] = var.is_artificial;
+    }
+    let phase_one = is_artificial
+        .iter()
+        .map(|artificial| if *artificial { -1.0 } else { 0.0 })
+        .collect::<Vec<_>>();
+    let mut candidates = vec![basis.duals(&phase_one).iter().map(|y| -y).collect()];
+    for (p, x) in basis.values.iter().enumerate() {
+        let artificial = is_artificial[basis.heads[p]];
+        if *x >= -PRECISION && !(artificial && *x > PRECISION) {
             continue;
         }
-        let scalar = item[0] / item[min_col_index];
-        if in_phase_one
-            && scalar == min
-            && variables
-                .iter()
-                .find(|v| v.ligne == i)
-                .unwrap()
-                .is_artificial
-        {
-            line_index = i;
+        let mut rho = vec![0.0; basis.heads.len()];
+        rho[p] = 1.0;
+        basis.inverse.btran(&mut rho);
+        let sign = if *x < 0.0 { -1.0 } else { 1.0 };
+        candidates.push(rho.iter().map(|r| sign * r).collect());
+    }
+    candidates.into_iter().find(|w: &Vec<f64>| {
+        matrix.dot_column(0, w) > PRECISION
+            && (1..matrix.columns())
+                .filter(|j| !is_artificial[*j])
+                .all(|j| matrix.dot_column(j, w) <= PRECISION)
+    })
+}
+
+#[derive(Clone)]
+struct Node {
+    base_lp: String,
+    constraints: Vec<(String, String, f64)>,
+    // Valeur de la relaxation du noeud parent
+    bound: f64,
+    // Variable de branchement ayant créé le noeud : (nom, branche haute, distance à l'entier)
+    branching: Option<(String, bool, f64)>,
+    // Coupes valides uniquement dans le sous-arbre du noeud
+    cuts: Vec<String>,
+}
+
+impl Node {
+    fn to_lp_string(&self) -> String {
+        let mut full_lp = self.base_lp.clone();
+        for (var, op, val) in &self.constraints {
+            full_lp.push_str(&format!("\n{} {} {};", var, op, val));
         }
-        if scalar < min && scalar >= 0.0 {
-            leaved = true;
-            min = scalar;
-            line_index = i;
+        for cut in &self.cuts {
+            full_lp.push('\n');
+            full_lp.push_str(cut);
         }
+        full_lp
     }
-    if !leaved {
-        return (true, false);
+
+    fn contains_constraint(&self, var: &str, op: &str, val: f64) -> bool {
+        self.constraints
+            .iter()
+            .any(|(v, o, value)| v == var && o == op && *value == val)
     }
-    if let Some(var) = variables.iter().position(|v| v.ligne == line_index) {
-        variables[var].in_base = false;
-        sorted_by_column[variables[var].column - 1] = var;
-        variables[var].ligne = usize::MAX;
+
+    fn child(&self, var: &str, op: &str, val: f64, bound: f64, distance: f64) -> Node {
+        let mut constraints = self.constraints.clone();
+        constraints.push((var.to_string(), op.to_string(), val));
+        Node {
+            base_lp: self.base_lp.clone(),
+            constraints,
+            bound,
+            branching: Some((var.to_string(), op == ">=", distance)),
+            cuts: self.cuts.clone(),
+        }
     }
-    if let Some(y) = sorted_by_column.get_mut(min_col_index - 1) {
-        let v = variables.get_mut(*y).unwrap();
-        v.in_base = true;
-        v.ligne = line_index;
-        in_base[line_index] = v.cout_original;
-        sorted_by_column[min_col_index - 1] = *y;
-        let pos = variables
-            .iter()
-            .position(|v| v.column == min_col_index)
-            .unwrap();
-        variables[pos].in_base = true;
-        variables[pos].ligne = line_index;
-    }
-    let pivot = matrix[line_index][min_col_index];
-    matrix[line_index].iter_mut().for_each(|x| {
-            if (*x).abs() > PRECISION {
-                *x /= pivot;
-            }
-            else {
-                *x = 0.0;
-            }
-    });
-    let pivot_row = matrix[line_index].clone();
-    matrix.par_iter_mut().enumerate().filter(|(i, row)| *i != line_index && row[min_col_index].abs() > PRECISION).for_each(|(_, row)| {
-        let coeff = row[min_col_index];
-        pivot_row.iter().enumerate().filter(|(_, x)| (**x).abs() > PRECISION).for_each(|(j, x)| {
-                row[j] -= x * coeff;
-        });
-    });
-    (false, true)
-}
-
-// fn scalar_product(x: &[f64], y: &[f64]) -> f64 {
-//     let mut z = 0.0;
-//     for i in 0..x.len() {
-//         z += x[i]*y[i];
-//     }
-//     z
-// }
-
-// #[inline(always)]
-// fn scalar_product_column(x: &[f64], matrix: &[Vec<f64>], j: usize) -> f64 {
-//     let mut sum = 0.0;
-//     x.iter()
-//         .enumerate()
-//         .filter(|(i, xi)| **xi != 0.0 && matrix[*i][j] != 0.0)
-//         .for_each(|(i, xi)| {
-//             sum += xi * matrix[i][j];
-//         });
-//     sum
-// }
+}
 
-#[inline(always)]
-fn scalar_product(x: &[f64], y: &[f64]) -> f64 {
-    x.par_iter()
-        .zip(y.par_iter())
-        .filter(|(xi, yi)| (**xi).abs() > PRECISION  && (**yi).abs() > PRECISION )
-        .map(|(xi, yi)| xi * yi)
-        .sum()
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchingRule {
+    FirstFractional,
+    MostFractional,
+    PseudoCost,
+    Strong,
 }
 
-fn scalar_product_column(x: &[f64], matrix: &[Vec<f64>], j: usize) -> f64 {
-    x.par_iter()
-        .enumerate()
-        .filter(|(i, xi)| (**xi).abs() > PRECISION && matrix[*i][j].abs() > PRECISION)
-        .map(|(i, &xi)| xi * matrix[i][j])
-        .sum()
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct BranchAndBoundOptions {
+    pub abs_gap: f64,
+    pub rel_gap: f64,
+    pub branching: BranchingRule,
+    // Nombre d'observations avant de faire confiance au pseudo-coût d'une variable
+    pub reliability: usize,
+    // Nombre maximal de variables évaluées par strong branching à chaque noeud
+    pub strong_candidates: usize,
+    // Tours de séparation de coupes à la racine
+    pub cut_rounds: usize,
+    pub cuts_per_round: usize,
+    // Un tour de coupes locales à chaque noeud fractionnaire
+    pub node_cuts: bool,
+    // Tours du callback de coupes de l'utilisateur à chaque noeud fractionnaire
+    pub user_cut_rounds: usize,
+    // Coupes de cliques (graphe de conflits) et de couvertures sur les binaires
+    pub clique_cuts: bool,
+    pub cover_cuts: bool,
+    // Heuristiques primales : arrondis à chaque noeud, plongées et RINS tous les
+    // heuristic_frequency noeuds, pompe de faisabilité à la racine
+    pub heuristics: bool,
+    pub heuristic_frequency: usize,
+    // Limite de noeuds des sous-problèmes de RINS
+    pub rins_nodes: usize,
+    // Limite de noeuds explorés, l'incumbent est alors rendu avec son écart
+    pub max_nodes: usize,
+    // Presolve entier avant la recherche
+    pub presolve: bool,
+    // Algorithme de la relaxation de la racine, les autres noeuds utilisent le simplexe
+    pub solver: Solver,
+    // Simplexe réseau pour les relaxations dont la matrice est celle d'un réseau
+    pub network: bool,
+    // Traitement des fichiers à plusieurs objectifs
+    pub multi_objective: MultiObjective,
+    // Nombre de meilleures solutions distinctes gardées dans le pool, 1 pour l'optimum seul
+    pub pool_size: usize,
 }
-#[inline(always)]
-fn big_m(
-    matrix: &mut [Vec<f64>],
-    variables: &mut [Variable],
-    hmap_vars: &mut HashMap<String, usize>,
-    print: bool,
-) -> bool {
-    let mut sorted_by_column = hmap_vars.values().copied().collect::<Vec<_>>();
-    sorted_by_column.sort_by(|a, b| variables[*a].column.cmp(&variables[*b].column));
-    let mut compteur = 1;
-    let mut in_base = variables
-        .par_iter()
-        .filter(|x| x.in_base)
-        .map(|x| (x.ligne, x.cout_original))
-        .collect::<Vec<_>>();
-    in_base.sort_by(|a, b| a.0.cmp(&b.0));
-    let mut in_base = in_base.iter().map(|x| x.1).collect::<Vec<_>>();
-    loop {
-        if print {
-            print_system(matrix, variables, hmap_vars, true);
-        }
-        let now = std::time::Instant::now();
-        let (ended, borned) = update_array(
-            matrix,
-            variables,
-            &mut sorted_by_column,
-            &mut in_base,
-            false,
-            false,
-        );
-        let elapsed = now.elapsed();
-        if compteur % 1000 == 0 {
-            println!("Pivoting... {}\nelapsed : {:?}", compteur, elapsed);
+
+impl Default for BranchAndBoundOptions {
+    fn default() -> Self {
+        BranchAndBoundOptions {
+            abs_gap: 1.0e-6,
+            rel_gap: 1.0e-4,
+            branching: BranchingRule::MostFractional,
+            reliability: 4,
+            strong_candidates: 8,
+            cut_rounds: 5,
+            cuts_per_round: 50,
+            node_cuts: false,
+            user_cut_rounds: 5,
+            clique_cuts: true,
+            cover_cuts: true,
+            heuristics: true,
+            heuristic_frequency: 10,
+            rins_nodes: 200,
+            max_nodes: usize::MAX,
+            presolve: true,
+            solver: Solver::Simplex,
+            network: true,
+            multi_objective: MultiObjective::Lexicographic,
+            pool_size: 1,
         }
-        if print {
-            print_system(matrix, variables, hmap_vars, true);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchAndBoundResult {
+    pub variables: Vec<(String, f64)>,
+    pub is_min: f64,
+    pub z: f64,
+    pub best_bound: f64,
+    pub gap: f64,
+    pub explored_nodes: usize,
+    pub open_nodes: usize,
+    pub cuts: CutStatistics,
+    // Solutions améliorantes dans l'ordre où elles ont été trouvées
+    pub incumbents: Vec<IncumbentUpdate>,
+    // Valeur de chaque objectif d'un programme multi-objectif
+    pub objectives: Vec<(String, f64)>,
+    pub goals: Vec<GoalAchievement>,
+    // Meilleures solutions distinctes, la première est l'optimum
+    pub pool: Vec<PoolSolution>,
+    pub presolve: Option<PresolveStatistics>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncumbentUpdate {
+    // Noeud LP entier ou nom de l'heuristique
+    pub source: String,
+    pub z: f64,
+    pub explored_nodes: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CutStatistics {
+    pub rounds: usize,
+    pub gomory_cuts: usize,
+    pub clique_cuts: usize,
+    pub cover_cuts: usize,
+    pub node_cuts: usize,
+    // Lignes ajoutées par les callbacks
+    pub user_cuts: usize,
+    pub lazy_constraints: usize,
+    // Arêtes du graphe de conflits entre binaires
+    pub conflict_edges: usize,
+    // Relaxation de la racine avant et après les coupes
+    pub root_bound_before: f64,
+    pub root_bound_after: f64,
+}
+
+impl Default for CutStatistics {
+    fn default() -> Self {
+        CutStatistics {
+            rounds: 0,
+            gomory_cuts: 0,
+            clique_cuts: 0,
+            cover_cuts: 0,
+            node_cuts: 0,
+            user_cuts: 0,
+            lazy_constraints: 0,
+            conflict_edges: 0,
+            root_bound_before: f64::NAN,
+            root_bound_after: f64::NAN,
+        }
+    }
+}
+
+// Ligne rendue par un callback du branch and bound, sur les noms des variables du fichier
+#[derive(Debug, Clone)]
+pub struct CallbackRow {
+    pub coeffs: Vec<(String, f64)>,
+    pub sense: Sense,
+    pub rhs: f64,
+}
+
+pub type RowCallback<'a> = Box<dyn FnMut(&[(String, f64)]) -> Vec<CallbackRow> + 'a>;
+
+// Callbacks de la recherche. lazy reçoit chaque solution entière candidate et la rejette en
+// rendant des lignes qu'elle viole, ajoutées à tous les noeuds ; user_cuts reçoit la
+// relaxation des noeuds fractionnaires et rend des coupes, ajoutées au sous-arbre du noeud.
+// Les lignes satisfaites par la solution reçue sont ignorées.
+#[derive(Default)]
+pub struct BranchAndBoundCallbacks<'a> {
+    pub lazy: Option<RowCallback<'a>>,
+    pub user_cuts: Option<RowCallback<'a>>,
+}
+
+impl BranchAndBoundCallbacks<'_> {
+    fn is_empty(&self) -> bool {
+        self.lazy.is_none() && self.user_cuts.is_none()
+    }
+}
+
+// Lignes d'un callback violées par la solution variables, au format du fichier LP
+fn violated_rows(
+    rows: &[CallbackRow],
+    model: &LpModel,
+    variables: &[(String, f64)],
+) -> Result<Vec<String>, String> {
+    let values = model.values(variables);
+    let mut violated = vec![];
+    for row in rows {
+        let mut activity = 0.0;
+        for (name, a) in &row.coeffs {
+            let j = model
+                .index
+                .get(name)
+                .ok_or(format!("Variable inconnue : {}", name))?;
+            activity += a * values[*j];
         }
-        if ended {
-            if print {
-                print_system(matrix, variables, hmap_vars, true);
+        let tolerance = PRECISION * row.rhs.abs().max(1.0);
+        let is_violated = match row.sense {
+            Sense::Leq => activity > row.rhs + tolerance,
+            Sense::Geq => activity < row.rhs - tolerance,
+            Sense::Eq => (activity - row.rhs).abs() > tolerance,
+        };
+        if is_violated {
+            violated.push(format_constraint(&row.coeffs, row.sense.as_str(), row.rhs));
+        }
+    }
+    Ok(violated)
+}
+
+// Contraintes paresseuses ajoutées au noeud courant et aux noeuds ouverts
+fn add_lazy_rows(node: &mut Node, stack: &mut [Node], rows: &[String]) {
+    for open in stack.iter_mut().chain(std::iter::once(node)) {
+        for row in rows {
+            open.base_lp.push('\n');
+            open.base_lp.push_str(row);
+        }
+    }
+}
+
+// Dégradations moyennes de l'objectif par unité de variation, vers le bas et vers le haut
+#[derive(Default)]
+struct PseudoCosts {
+    down: HashMap<String, (f64, usize)>,
+    up: HashMap<String, (f64, usize)>,
+}
+
+impl PseudoCosts {
+    fn update(&mut self, var: &str, up: bool, distance: f64, degradation: f64) {
+        if !degradation.is_finite() || distance <= PRECISION {
+            return;
+        }
+        let costs = if up { &mut self.up } else { &mut self.down };
+        let entry = costs.entry(var.to_string()).or_insert((0.0, 0));
+        entry.0 += degradation.max(0.0) / distance;
+        entry.1 += 1;
+    }
+
+    fn is_reliable(&self, var: &str, reliability: usize) -> bool {
+        let count = |costs: &HashMap<String, (f64, usize)>| costs.get(var).map_or(0, |x| x.1);
+        count(&self.down).min(count(&self.up)) >= reliability
+    }
+
+    fn estimate(&self, var: &str, up: bool) -> f64 {
+        let costs = if up { &self.up } else { &self.down };
+        match costs.get(var) {
+            Some((sum, count)) if *count > 0 => sum / *count as f64,
+            // Variable jamais observée : moyenne des autres variables
+            _ => {
+                let (sum, count) = costs
+                    .values()
+                    .filter(|x| x.1 > 0)
+                    .fold((0.0, 0), |acc, x| (acc.0 + x.0 / x.1 as f64, acc.1 + 1));
+                if count > 0 { sum / count as f64 } else { 1.0 }
             }
-            return borned;
         }
-        compteur += 1;
+    }
+
+    fn score(&self, var: &str, fraction: f64) -> f64 {
+        product_score(
+            fraction * self.estimate(var, false),
+            (1.0 - fraction) * self.estimate(var, true),
+        )
     }
 }
 
 #[inline(always)]
-fn two_phases(
-    matrix: &mut [Vec<f64>],
-    variables: &mut [Variable],
-    hmap_vars: &mut HashMap<String, usize>,
-    original_cost: &HashMap<String, f64>,
-    print: bool,
-    is_min: f64,
-) -> bool {
-    let mut sorted_by_column = hmap_vars.values().copied().collect::<Vec<_>>();
-    sorted_by_column.sort_by(|a, b| variables[*a].column.cmp(&variables[*b].column));
-    let mut compteur = 1;
-    let mut in_base = variables
-        .par_iter()
-        .filter(|x| x.in_base)
-        .map(|x| (x.ligne, x.cout_original))
-        .collect::<Vec<_>>();
-    in_base.sort_by(|a, b| a.0.cmp(&b.0));
-    let mut in_base = in_base.iter().map(|x| x.1).collect::<Vec<_>>();
-    if print {
-        print_system(matrix, variables, hmap_vars, true);
-    }
-    loop {
-        //let now = std::time::Instant::now();
-        let (s1, s2) = update_array(
-            matrix,
-            variables,
-            &mut sorted_by_column,
-            &mut in_base,
-            true,
-            false,
-        );
-        if print {
-            print_system(matrix, variables, hmap_vars, true);
-        }
-        let z = get_objective(matrix, variables, is_min);
-        let all_positive = s1 && s2;
-        if compteur % 10 == 0 {
-            println!("Pivoting (phase 1)... {}", compteur);
-            matrix.par_iter_mut().for_each(|row| {
-            row.iter_mut().for_each(|x| {
-                if (*x).abs() <= PRECISION {
-                    *x = 0.0;
-                }
-                });
-            });
+fn product_score(down: f64, up: f64) -> f64 {
+    down.max(PRECISION) * up.max(PRECISION)
+}
+
+// Ecart absolu entre l'incumbent et une borne, positif si la borne peut encore améliorer l'incumbent
+#[inline(always)]
+fn absolute_gap(incumbent: f64, bound: f64, is_min: f64) -> f64 {
+    is_min * (bound - incumbent)
+}
+
+#[inline(always)]
+fn relative_gap(incumbent: f64, bound: f64, is_min: f64) -> f64 {
+    absolute_gap(incumbent, bound, is_min).max(0.0) / incumbent.abs().max(1.0e-10)
+}
+
+#[inline(always)]
+fn within_gap(incumbent: f64, bound: f64, is_min: f64, options: &BranchAndBoundOptions) -> bool {
+    absolute_gap(incumbent, bound, is_min) <= options.abs_gap
+        || relative_gap(incumbent, bound, is_min) <= options.rel_gap
+}
+
+// Partie fractionnaire d'une valeur, None si la valeur est entière à PRECISION près
+#[inline(always)]
+fn fractional_part(value: f64) -> Option<f64> {
+    let fraction = value - value.floor();
+    if fraction <= PRECISION || fraction >= 1.0 - PRECISION {
+        None
+    } else {
+        Some(fraction)
+    }
+}
+
+// Déclarations suivant les contraintes du fichier
+#[derive(Debug, Clone, Default)]
+pub struct Declarations {
+    pub integers: Vec<String>,
+    pub priorities: HashMap<String, f64>,
+}
+
+pub fn parse_declarations(filename: &str) -> Result<Declarations, String> {
+    let file = match LPParser::parse(Rule::program, filename) {
+        Ok(mut file) => file.next().unwrap(),
+        Err(e) => {
+            return Err(format!("Error parsing file: {}", e));
         }
-        if z.abs() < PRECISION {
-            let art_in_base = variables
-                .iter()
-                .filter(|x| x.in_base && x.is_artificial && matrix[x.ligne][0].abs() > PRECISION);
-            if art_in_base.count() > 0 {
-                return false;
-            }
-            let column_vars_hashmap = hmap_vars
-                .par_iter()
-                .map(|(_, y)| {
-                    (
-                        variables[*y].column,
-                        hmap_vars.iter().find(|(_, z)| **z == *y).unwrap().0,
-                    )
-                })
-                .collect::<HashMap<_, _>>();
-            for x in variables.iter_mut() {
-                //let v = vars.iter().position(|y| y.column == x.column).unwrap();
-                //let my_var = hmap_vars.iter().find(|(_, y)| **y == v).unwrap().0;
-                let v = column_vars_hashmap.get(&x.column).unwrap();
-                let original_cost = *original_cost.get(&v.to_string()).unwrap();
-                if x.in_base {
-                    in_base[x.ligne] = original_cost;
+    };
+    let mut declarations = Declarations::default();
+    for line in file.into_inner() {
+        match line.as_rule() {
+            Rule::int_decl => {
+                for token in line.into_inner() {
+                    if token.as_rule() == Rule::varname {
+                        declarations
+                            .integers
+                            .push(token.as_str().trim().to_string());
+                    }
                 }
-                x.cout_original = original_cost;
             }
-            loop {
-                compteur += 1;
-                // let now = std::time::Instant::now();
-                let (ended, borned) = update_array(
-                    matrix,
-                    variables,
-                    &mut sorted_by_column,
-                    &mut in_base,
-                    false,
-                    true,
-                );
-                // let elapsed = now.elapsed();
-                if compteur % 10 == 0 {
-                    println!("Pivoting (phase2)... {}", compteur);
-                    matrix.par_iter_mut().for_each(|row| {
-                        row.iter_mut().for_each(|x| {
-                            if (*x).abs() <= PRECISION {
-                                *x = 0.0;
-                            }
-                        });
-                    });
-                }
-                if print {
-                    print_system(matrix, variables, hmap_vars, true);
-                }
-                if ended {
-                    return borned;
+            Rule::priority_decl => {
+                for priority in line.into_inner() {
+                    if priority.as_rule() != Rule::priority {
+                        continue;
+                    }
+                    let mut tokens = priority.into_inner();
+                    let var_name = tokens.next().unwrap().as_str().trim().to_string();
+                    let value = tokens.nth(1).unwrap().as_str().parse::<f64>().unwrap();
+                    declarations.priorities.insert(var_name, value);
                 }
             }
-        } else if all_positive {
-            return false;
+            _ => {}
         }
-        compteur += 1;
     }
+    Ok(declarations)
 }
 
-#[derive(Clone)]
-struct Node {
-    base_lp: String,
-    constraints: Vec<(String, String, f64)>,
+pub(crate) struct NodeSolution {
+    pub(crate) vars_string: Vec<(String, f64)>,
+    is_min: f64,
+    z: f64,
+    // Base finale et nom de chaque variable
+    tableau: SolvedTableau,
+    names: Vec<String>,
 }
 
-impl Node {
-    fn to_lp_string(&self) -> String {
-        let mut full_lp = self.base_lp.clone();
-        for (var, op, val) in &self.constraints {
-            full_lp.push_str(&format!("\n{} {} {};", var, op, val));
+impl NodeSolution {
+    fn gomory_cuts(&self, is_integer: &dyn Fn(&str) -> bool, max_cuts: usize) -> Vec<String> {
+        cuts::gomory_cuts(
+            &|ligne| self.tableau.row(ligne),
+            &self.tableau.values(),
+            &self.tableau.matrix.to_csr(),
+            &self.tableau.variables,
+            &self.names,
+            is_integer,
+            max_cuts,
+        )
+    }
+}
+
+#[inline(always)]
+pub(crate) fn solve_node(lp_str: &str) -> Option<NodeSolution> {
+    solve_relaxation(lp_str, Solver::Simplex, true)
+}
+
+// Relaxation résolue avec l'algorithme choisi, par le simplexe réseau si network est vrai
+// et que la matrice du noeud est celle d'un réseau
+fn solve_relaxation(lp_str: &str, solver: Solver, network: bool) -> Option<NodeSolution> {
+    // Le grand M (1e12) donne des solutions infaisables en flottants, on passe par les deux phases
+    let tableau = solve_tableau(lp_str, true, solver, network, None).ok()?;
+    let (vars_string, z) = get_solution(
+        &tableau.values(),
+        &tableau.variables,
+        &tableau.vars_hash_map,
+        tableau.is_min,
+    )
+    .ok()?;
+    let mut names = vec![String::new(); tableau.variables.len()];
+    for (name, index) in &tableau.vars_hash_map {
+        names[*index] = name.clone();
+    }
+    Some(NodeSolution {
+        vars_string,
+        is_min: tableau.is_min,
+        z,
+        tableau,
+        names,
+    })
+}
+
+// Séparateurs de coupes : cliques et couvertures sur le modèle d'origine, puis Gomory sur le tableau
+struct Separator<'a> {
+    model: &'a LpModel,
+    binaries: Vec<bool>,
+    conflict_graph: ConflictGraph,
+    is_integer: &'a dyn Fn(&str) -> bool,
+    options: &'a BranchAndBoundOptions,
+}
+
+// Coupes trouvées par un tour de séparation, par famille
+#[derive(Default)]
+struct SeparatedCuts {
+    clique: Vec<String>,
+    cover: Vec<String>,
+    gomory: Vec<String>,
+}
+
+impl SeparatedCuts {
+    fn len(&self) -> usize {
+        self.clique.len() + self.cover.len() + self.gomory.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn into_cuts(self) -> Vec<String> {
+        [self.clique, self.cover, self.gomory].concat()
+    }
+}
+
+impl<'a> Separator<'a> {
+    fn new(
+        model: &'a LpModel,
+        is_integer: &'a dyn Fn(&str) -> bool,
+        options: &'a BranchAndBoundOptions,
+    ) -> Self {
+        let binaries = model.binaries();
+        let conflict_graph = ConflictGraph::new(model, &binaries);
+        Separator {
+            model,
+            binaries,
+            conflict_graph,
+            is_integer,
+            options,
         }
-        full_lp
     }
 
-    fn contains_constraint(&self, var: &str, op: &str, val: f64) -> bool {
-        self.constraints
+    fn separate(&self, solution: &NodeSolution) -> SeparatedCuts {
+        let values = self.model.values(&solution.vars_string);
+        let max_cuts = self.options.cuts_per_round;
+        let mut cuts = SeparatedCuts::default();
+        if self.options.clique_cuts {
+            cuts.clique = self
+                .conflict_graph
+                .clique_cuts(self.model, &values, max_cuts);
+        }
+        if self.options.cover_cuts {
+            cuts.cover = cuts::cover_cuts(
+                self.model,
+                &self.binaries,
+                &values,
+                max_cuts.saturating_sub(cuts.clique.len()),
+            );
+        }
+        let remaining = max_cuts.saturating_sub(cuts.clique.len() + cuts.cover.len());
+        if remaining > 0 {
+            cuts.gomory = solution.gomory_cuts(self.is_integer, remaining);
+        }
+        cuts
+    }
+}
+
+// Tours de coupes à la racine, les coupes sont ajoutées au programme de base.
+// Un tour dont le programme ne se résout plus (erreurs numériques du tableau dense) est abandonné.
+fn root_cuts(file: &str, separator: &Separator, statistics: &mut CutStatistics) -> String {
+    let mut lp = file.to_string();
+    statistics.conflict_edges = separator.conflict_graph.edges();
+    if separator.options.cut_rounds == 0 {
+        return lp;
+    }
+    let Some(mut solution) =
+        solve_relaxation(&lp, separator.options.solver, separator.options.network)
+    else {
+        return lp;
+    };
+    statistics.root_bound_before = solution.z;
+    for _ in 0..separator.options.cut_rounds {
+        let new_cuts = separator.separate(&solution);
+        if new_cuts.is_empty() {
+            break;
+        }
+        let counts = (
+            new_cuts.clique.len(),
+            new_cuts.cover.len(),
+            new_cuts.gomory.len(),
+        );
+        let mut candidate = lp.clone();
+        for cut in new_cuts.into_cuts() {
+            candidate.push('\n');
+            candidate.push_str(&cut);
+        }
+        let Some(next) = solve_relaxation(
+            &candidate,
+            separator.options.solver,
+            separator.options.network,
+        ) else {
+            break;
+        };
+        // On arrête quand la borne ne progresse plus
+        if (next.z - solution.z).abs() <= separator.options.abs_gap {
+            break;
+        }
+        statistics.rounds += 1;
+        statistics.clique_cuts += counts.0;
+        statistics.cover_cuts += counts.1;
+        statistics.gomory_cuts += counts.2;
+        lp = candidate;
+        solution = next;
+    }
+    lp
+}
+
+// Dégradations de l'objectif des deux fils, infinies si le fils est infaisable
+fn strong_branch(node: &Node, var: &str, value: f64, z: f64, is_min: f64) -> (f64, f64) {
+    let degradation = |op: &str, val: f64| {
+        let child = node.child(var, op, val, z, 0.0);
+        match solve_node(&child.to_lp_string()) {
+            Some(solution) => absolute_gap(solution.z, z, is_min),
+            None => f64::INFINITY,
+        }
+    };
+    (
+        degradation("<=", value.floor()),
+        degradation(">=", value.ceil()),
+    )
+}
+
+// Renvoie l'indice dans vars_string de la variable sur laquelle brancher
+#[allow(clippy::too_many_arguments)]
+fn select_branching_variable(
+    node: &Node,
+    vars_string: &[(String, f64)],
+    candidates: &[(usize, f64)],
+    z: f64,
+    is_min: f64,
+    priorities: &HashMap<String, f64>,
+    pseudo_costs: &mut PseudoCosts,
+    options: &BranchAndBoundOptions,
+) -> usize {
+    // Seules les variables de plus haute priorité sont candidates
+    let priority = |index: usize| *priorities.get(&vars_string[index].0).unwrap_or(&0.0);
+    let top_priority = candidates
+        .iter()
+        .map(|(i, _)| priority(*i))
+        .fold(f64::MIN, f64::max);
+    let mut candidates = candidates
+        .iter()
+        .filter(|(i, _)| priority(*i) == top_priority)
+        .copied()
+        .collect::<Vec<_>>();
+    // Les plus fractionnaires d'abord, l'ordre des variables départageant les égalités
+    candidates.sort_by(|a, b| {
+        let fa = a.1.min(1.0 - a.1);
+        let fb = b.1.min(1.0 - b.1);
+        fb.total_cmp(&fa).then(a.0.cmp(&b.0))
+    });
+    let best_by = |scores: &[(usize, f64)]| {
+        scores
             .iter()
-            .any(|(v, o, value)| v == var && o == op && *value == val)
+            .fold(
+                (usize::MAX, f64::MIN),
+                |best, x| if x.1 > best.1 { *x } else { best },
+            )
+            .0
+    };
+    match options.branching {
+        BranchingRule::FirstFractional => candidates.iter().map(|x| x.0).min().unwrap(),
+        BranchingRule::MostFractional => candidates[0].0,
+        BranchingRule::Strong => {
+            let scores = candidates
+                .iter()
+                .take(options.strong_candidates.max(1))
+                .map(|(i, fraction)| {
+                    let (name, value) = &vars_string[*i];
+                    let (down, up) = strong_branch(node, name, *value, z, is_min);
+                    pseudo_costs.update(name, false, *fraction, down);
+                    pseudo_costs.update(name, true, 1.0 - fraction, up);
+                    (*i, product_score(down, up))
+                })
+                .collect::<Vec<_>>();
+            best_by(&scores)
+        }
+        BranchingRule::PseudoCost => {
+            let mut evaluated = 0;
+            let scores = candidates
+                .iter()
+                .map(|(i, fraction)| {
+                    let (name, value) = &vars_string[*i];
+                    // Initialisation par strong branching des pseudo-coûts non fiables
+                    if !pseudo_costs.is_reliable(name, options.reliability)
+                        && evaluated < options.strong_candidates
+                    {
+                        evaluated += 1;
+                        let (down, up) = strong_branch(node, name, *value, z, is_min);
+                        pseudo_costs.update(name, false, *fraction, down);
+                        pseudo_costs.update(name, true, 1.0 - fraction, up);
+                        if down.is_infinite() || up.is_infinite() {
+                            return (*i, product_score(down, up));
+                        }
+                    }
+                    (*i, pseudo_costs.score(name, *fraction))
+                })
+                .collect::<Vec<_>>();
+            best_by(&scores)
+        }
+    }
+}
+
+pub fn branch_and_bound(
+    file: &str,
+    options: &BranchAndBoundOptions,
+) -> Result<BranchAndBoundResult, String> {
+    branch_and_bound_with(file, options, &mut BranchAndBoundCallbacks::default())
+}
+
+// Branch and bound avec des callbacks, le presolve est alors désactivé pour que les
+// callbacks reçoivent les variables du fichier
+pub fn branch_and_bound_with(
+    file: &str,
+    options: &BranchAndBoundOptions,
+    callbacks: &mut BranchAndBoundCallbacks,
+) -> Result<BranchAndBoundResult, String> {
+    let model = parse_lp_model(file)?;
+    if !model.quadratic.is_empty() {
+        return Err("Branch and bound ne traite pas les objectifs quadratiques".to_string());
+    }
+    if !callbacks.is_empty()
+        && (!model.goals.is_empty() || !model.objectives.is_empty() || options.pool_size > 1)
+    {
+        return Err("Les callbacks demandent un seul objectif sans pool de solutions".to_string());
+    }
+    if !model.goals.is_empty() {
+        // Sans déclaration int, les variables du fichier sont entières mais pas les écarts
+        // des buts
+        let mut rewritten = model.clone();
+        if !rewritten.integers.contains(&true) {
+            let deviations = model
+                .goals
+                .iter()
+                .flat_map(|goal| [goal.under, goal.over])
+                .collect::<HashSet<_>>();
+            for (j, integer) in rewritten.integers.iter_mut().enumerate() {
+                *integer = !deviations.contains(&j);
+            }
+        }
+        let lp = rewritten.to_lp_string() + &priority_declarations(file)?;
+        let mut result = branch_and_bound(&lp, options)?;
+        let values = model.values(&result.variables);
+        result.goals = multi_objective::goal_achievements(&model, &values);
+        result.variables = model.names.iter().cloned().zip(values).collect();
+        return Ok(result);
+    }
+    if !model.objectives.is_empty() {
+        let declarations = priority_declarations(file)?;
+        return multi_objective::branch_and_bound(&model, &declarations, options);
+    }
+    if options.pool_size > 1 {
+        return alternatives::solution_pool(&model, &priority_declarations(file)?, options);
     }
+    if !options.presolve || !callbacks.is_empty() {
+        return search(file, options, 0.0, callbacks);
+    }
+    let Declarations {
+        integers,
+        priorities,
+    } = parse_declarations(file)?;
+    let integers = integers.into_iter().collect::<HashSet<_>>();
+    let mut model = model;
+    for (integer, name) in model.integers.iter_mut().zip(&model.names) {
+        *integer = integers.is_empty() || integers.contains(name);
+    }
+    let presolved = presolve_mip(&model)?;
+    let reduced = &presolved.reduced;
+    let duals = vec![0.0; reduced.rows.len()];
+    // Partie constante de l'objectif : variables fixées et substituées par le presolve
+    let (x, _) = presolved.postsolve(&vec![0.0; reduced.names.len()], &duals);
+    let offset = model.objective_value(&x);
+    let mut result = if reduced.integers.contains(&true) {
+        let mut lp = reduced.to_lp_string();
+        let priorities = reduced
+            .names
+            .iter()
+            .filter_map(|name| priorities.get(name).map(|p| format!("{} = {}", name, p)))
+            .collect::<Vec<_>>();
+        if !priorities.is_empty() {
+            lp.push_str(&format!("\npriority {};", priorities.join(", ")));
+        }
+        search(&lp, options, offset, callbacks)?
+    } else {
+        // Plus de variable entière : la relaxation suffit
+        let variables = if reduced.names.is_empty() {
+            vec![]
+        } else {
+            solve_relaxation(&reduced.to_lp_string(), options.solver, options.network)
+                .ok_or(NO_INTEGER_SOLUTION.to_string())?
+                .vars_string
+        };
+        let z = reduced.objective_value(&reduced.values(&variables)) + offset;
+        BranchAndBoundResult {
+            variables,
+            is_min: model.is_min,
+            z,
+            best_bound: z,
+            gap: 0.0,
+            explored_nodes: 1,
+            open_nodes: 0,
+            cuts: CutStatistics::default(),
+            incumbents: vec![],
+            objectives: vec![],
+            goals: vec![],
+            pool: vec![],
+            presolve: None,
+        }
+    };
+    let (x, _) = presolved.postsolve(&reduced.values(&result.variables), &duals);
+    result.variables = model.names.iter().cloned().zip(x).collect();
+    result.presolve = Some(presolved.statistics);
+    Ok(result)
 }
 
-pub fn branch_and_bound(file: &str) -> Result<(Vec<(String, f64)>, f64, f64), String> {
+// Priorités de branchement du fichier, à ajouter à un programme réécrit
+fn priority_declarations(file: &str) -> Result<String, String> {
+    let priorities = parse_declarations(file)?
+        .priorities
+        .iter()
+        .map(|(name, p)| format!("{} = {}", name, p))
+        .collect::<Vec<_>>();
+    Ok(if priorities.is_empty() {
+        String::new()
+    } else {
+        format!("\npriority {};", priorities.join(", "))
+    })
+}
+
+// Recherche arborescente. offset est la partie constante de l'objectif retirée par le
+// presolve, ajoutée aux valeurs affichées et rendues.
+fn search(
+    file: &str,
+    options: &BranchAndBoundOptions,
+    offset: f64,
+    callbacks: &mut BranchAndBoundCallbacks,
+) -> Result<BranchAndBoundResult, String> {
+    let Declarations {
+        integers,
+        priorities,
+    } = parse_declarations(file)?;
+    // Sans déclaration int, toutes les variables sont entières
+    let integers = integers.into_iter().collect::<HashSet<_>>();
+    let is_integer = |name: &str| integers.is_empty() || integers.contains(name);
+    let mut pseudo_costs = PseudoCosts::default();
+    let mut cut_statistics = CutStatistics::default();
+    let mut model = parse_lp_model(file)?;
+    for (integer, name) in model.integers.iter_mut().zip(&model.names) {
+        *integer = is_integer(name);
+    }
+    let separator = Separator::new(&model, &is_integer, options);
+    let base_lp = root_cuts(file, &separator, &mut cut_statistics);
     let mut stack = vec![Node {
-        base_lp: file.to_string(),
+        base_lp,
         constraints: vec![],
+        bound: f64::NAN,
+        branching: None,
+        cuts: vec![],
     }];
 
-    let mut best_solution = None;
+    let mut best_solution: Option<Incumbent> = None;
+    let mut incumbents = vec![];
+    let mut explored_nodes = 0;
+    // Dernière borne envoyée aux observateurs
+    let mut last_bound = f64::NAN;
+    let fractional_candidates = |vars_string: &[(String, f64)]| {
+        vars_string
+            .iter()
+            .enumerate()
+            .filter(|(_, (name, _))| is_integer(name))
+            .filter_map(|(i, (_, v))| fractional_part(*v).map(|f| (i, f)))
+            .collect::<Vec<_>>()
+    };
 
-    while let Some(node) = stack.pop() {
-        let lp_str = node.to_lp_string();
-        let (mut matrix, mut variables, is_min, mut hash_map_vars) = match parse_lp_bigm(&lp_str) {
-            Ok(v) => v,
-            Err(_) => continue,
+    'nodes: while explored_nodes < options.max_nodes
+        && let Some(mut node) = stack.pop()
+    {
+        if let Some((_, is_min, best_z)) = &best_solution
+            && !node.bound.is_nan()
+            && within_gap(*best_z, node.bound, *is_min, options)
+        {
+            continue;
+        }
+        explored_nodes += 1;
+        // La racine n'a pas de borne parente
+        let solver = if node.bound.is_nan() {
+            options.solver
+        } else {
+            Solver::Simplex
         };
-
-        let (vars_string, z) =
-            match solve_system(&mut matrix, &mut variables, &mut hash_map_vars, is_min) {
-                Ok(v) => v,
-                Err(_) => continue,
+        let Some(mut solution) = solve_relaxation(&node.to_lp_string(), solver, options.network)
+        else {
+            continue;
+        };
+        if let Some((var, up, distance)) = &node.branching {
+            pseudo_costs.update(
+                var,
+                *up,
+                *distance,
+                absolute_gap(solution.z, node.bound, solution.is_min),
+            );
+        }
+        let mut candidates = fractional_candidates(&solution.vars_string);
+        if options.node_cuts && !candidates.is_empty() {
+            let new_cuts = separator.separate(&solution).into_cuts();
+            let local_cuts = node.cuts.len();
+            node.cuts.extend(new_cuts);
+            match solve_node(&node.to_lp_string()) {
+                Some(resolved) if local_cuts < node.cuts.len() => {
+                    cut_statistics.node_cuts += node.cuts.len() - local_cuts;
+                    solution = resolved;
+                    candidates = fractional_candidates(&solution.vars_string);
+                }
+                _ => node.cuts.truncate(local_cuts),
+            }
+        }
+        // Contraintes paresseuses à une solution entière, coupes de l'utilisateur à un noeud
+        // fractionnaire, le noeud est résolu à nouveau tant que le callback ajoute des lignes
+        let mut user_rounds = 0;
+        loop {
+            let rows = if candidates.is_empty() {
+                let rounded = solution
+                    .vars_string
+                    .iter()
+                    .map(|(name, v)| (name.clone(), if is_integer(name) { v.round() } else { *v }))
+                    .collect::<Vec<_>>();
+                match callbacks.lazy.as_mut() {
+                    Some(lazy) => violated_rows(&lazy(&rounded), &model, &rounded)?,
+                    None => vec![],
+                }
+            } else {
+                match callbacks.user_cuts.as_mut() {
+                    Some(user_cuts) if user_rounds < options.user_cut_rounds => {
+                        user_rounds += 1;
+                        let cuts = user_cuts(&solution.vars_string);
+                        violated_rows(&cuts, &model, &solution.vars_string)?
+                    }
+                    _ => vec![],
+                }
             };
-        let mut all_integer = true;
-        for (_, v) in &vars_string {
-            if (*v).fract() != 0.0 {
-                all_integer = false;
+            if rows.is_empty() {
                 break;
             }
+            if candidates.is_empty() {
+                cut_statistics.lazy_constraints += rows.len();
+                add_lazy_rows(&mut node, &mut stack, &rows);
+            } else {
+                cut_statistics.user_cuts += rows.len();
+                node.cuts.extend(rows);
+            }
+            let Some(resolved) = solve_node(&node.to_lp_string()) else {
+                continue 'nodes;
+            };
+            solution = resolved;
+            candidates = fractional_candidates(&solution.vars_string);
         }
-
-        if all_integer {
-            let is_better = match &best_solution {
-                None => true,
-                Some((_, _, best_z)) => {
-                    if (is_min + 1.0).abs() < PRECISION {
-                        z < *best_z
-                    } else {
-                        z > *best_z
+        let NodeSolution {
+            mut vars_string,
+            is_min,
+            z,
+            ..
+        } = solution;
+        progress::notify(Progress::Node {
+            explored_nodes,
+            open_nodes: stack.len(),
+            z: z + offset,
+        });
+        if node.bound.is_nan() {
+            if cut_statistics.root_bound_before.is_nan() {
+                cut_statistics.root_bound_before = z;
+            }
+            cut_statistics.root_bound_after = z;
+        }
+        if options.heuristics && !candidates.is_empty() {
+            let values = model.values(&vars_string);
+            let guide = best_solution
+                .as_ref()
+                .map(|(variables, _, best_z)| (model.values(variables), *best_z));
+            let found = primal_heuristics(
+                &model,
+                &node,
+                &values,
+                guide.as_ref().map(|(x, z)| (x.as_slice(), *z)),
+                explored_nodes,
+                options,
+            );
+            for (source, x) in found {
+                let candidate = heuristics::to_solution(&model, &x);
+                if let Some(lazy) = callbacks.lazy.as_mut() {
+                    let rows = violated_rows(&lazy(&candidate), &model, &candidate)?;
+                    if !rows.is_empty() {
+                        cut_statistics.lazy_constraints += rows.len();
+                        add_lazy_rows(&mut node, &mut stack, &rows);
+                        continue;
                     }
                 }
-            };
-            if is_better {
-                best_solution = Some((vars_string, is_min, z));
+                let z = model.objective_value(&model.values(&candidate));
+                update_incumbent(
+                    &mut best_solution,
+                    &mut incumbents,
+                    candidate,
+                    (is_min, z, offset),
+                    source,
+                    explored_nodes,
+                );
             }
+        }
+        if let Some((_, _, best_z)) = &best_solution
+            && within_gap(*best_z, z, is_min, options)
+        {
             continue;
         }
 
-        for (nom, v) in &vars_string {
-            let val = v;
-            if val.fract() != 0.0 {
-                let value_inf = val.floor();
-                let value_sup = val.ceil();
-                if !node.contains_constraint(nom, "<=", value_inf) {
-                    let mut constraints1 = node.constraints.clone();
-                    constraints1.push((nom.clone(), "<=".to_string(), value_inf));
-                    stack.push(Node {
-                        base_lp: node.base_lp.clone(),
-                        constraints: constraints1,
-                    });
-                }
-                if !node.contains_constraint(nom, ">=", value_sup) {
-                    let mut constraints2 = node.constraints.clone();
-                    constraints2.push((nom.clone(), ">=".to_string(), value_sup));
-                    stack.push(Node {
-                        base_lp: node.base_lp.clone(),
-                        constraints: constraints2,
-                    });
+        if candidates.is_empty() {
+            for (name, v) in vars_string.iter_mut() {
+                if is_integer(name) {
+                    *v = v.round();
                 }
+            }
+            update_incumbent(
+                &mut best_solution,
+                &mut incumbents,
+                vars_string,
+                (is_min, z, offset),
+                "lp",
+                explored_nodes,
+            );
+        } else {
+            let index = select_branching_variable(
+                &node,
+                &vars_string,
+                &candidates,
+                z,
+                is_min,
+                &priorities,
+                &mut pseudo_costs,
+                options,
+            );
+            let (nom, val) = &vars_string[index];
+            let fraction = val - val.floor();
+            let value_inf = val.floor();
+            let value_sup = val.ceil();
+            if !node.contains_constraint(nom, "<=", value_inf) {
+                stack.push(node.child(nom, "<=", value_inf, z, fraction));
+            }
+            if !node.contains_constraint(nom, ">=", value_sup) {
+                stack.push(node.child(nom, ">=", value_sup, z, 1.0 - fraction));
+            }
+        }
+
+        // On s'arrête dès que l'incumbent est prouvé optimal à la tolérance près
+        if let Some((_, is_min, best_z)) = &best_solution {
+            let best_bound = best_open_bound(&stack, *best_z, *is_min);
+            if best_bound != last_bound {
+                last_bound = best_bound;
+                progress::notify(Progress::Bound {
+                    best_bound: best_bound + offset,
+                    gap: relative_gap(*best_z + offset, best_bound + offset, *is_min),
+                    explored_nodes,
+                });
+            }
+            if within_gap(*best_z, best_bound, *is_min, options) {
                 break;
             }
         }
     }
 
     match best_solution {
-        Some(sol) => Ok(sol),
-        None => Err("Pas de solution entière".to_string()),
+        Some((variables, is_min, z)) => {
+            let best_bound = best_open_bound(&stack, z, is_min) + offset;
+            let z = z + offset;
+            Ok(BranchAndBoundResult {
+                variables,
+                is_min,
+                z,
+                best_bound,
+                gap: relative_gap(z, best_bound, is_min),
+                explored_nodes,
+                open_nodes: stack.len(),
+                cuts: cut_statistics,
+                incumbents,
+                objectives: vec![],
+                goals: vec![],
+                pool: vec![],
+                presolve: None,
+            })
+        }
+        // Des noeuds restent ouverts : la recherche a été interrompue
+        None if !stack.is_empty() => Err(NODE_LIMIT.to_string()),
+        None => Err(NO_INTEGER_SOLUTION.to_string()),
     }
 }
 
-fn print_system(
-    matrix: &[Vec<f64>],
-    variables: &[Variable],
-    hash_map_vars: &HashMap<String, usize>,
-    show_z_row: bool,
+// Meilleure solution entière connue : (variables, is_min, z)
+type Incumbent = (Vec<(String, f64)>, f64, f64);
+
+// Solution entière rendue par un noeud ou une heuristique, retenue si elle améliore l'incumbent
+fn update_incumbent(
+    best_solution: &mut Option<Incumbent>,
+    incumbents: &mut Vec<IncumbentUpdate>,
+    variables: Vec<(String, f64)>,
+    (is_min, z, offset): (f64, f64, f64),
+    source: &str,
+    explored_nodes: usize,
 ) {
-    // On trie les variables par colonne
-    // let variables = hash_map_vars.keys().zip(variables.iter()).collect::<HashMap<_, _>>();
-    let variables = hash_map_vars
-        .iter()
-        .map(|(x, y)| (x.to_string(), variables[*y]))
-        .collect::<HashMap<_, _>>();
-    for x in variables.iter() {
-        println!("{:?} {:?}", x.1.in_base, x.0);
-    }
-    let mut vars_sorted = BTreeMap::new();
-    for var in variables.iter() {
-        vars_sorted.insert(var.1.column, var.0.clone());
+    if let Some((_, _, best_z)) = best_solution
+        && absolute_gap(*best_z, z, is_min) <= PRECISION
+    {
+        return;
     }
+    progress::notify(Progress::Incumbent {
+        z: z + offset,
+        source: source.to_string(),
+        explored_nodes,
+    });
+    incumbents.push(IncumbentUpdate {
+        source: source.to_string(),
+        z: z + offset,
+        explored_nodes,
+    });
+    *best_solution = Some((variables, is_min, z));
+}
 
-    // Affiche les noms de colonnes
-    print!("{:<8}", "Base");
-    print!("{:<8}", "Coût");
-    print!("{:<8}", "b");
-    for name in vars_sorted.values() {
-        print!("{:<8}", name);
+// Heuristiques primales sur la relaxation d'un noeud fractionnaire. Les arrondis sont
+// lancés à chaque noeud, les heuristiques qui résolvent des LP à la racine et tous
+// les heuristic_frequency noeuds. RINS travaille sur le problème global.
+fn primal_heuristics(
+    model: &LpModel,
+    node: &Node,
+    values: &[f64],
+    incumbent: Option<(&[f64], f64)>,
+    explored_nodes: usize,
+    options: &BranchAndBoundOptions,
+) -> Vec<(&'static str, Vec<f64>)> {
+    let is_root = node.bound.is_nan();
+    let lp = &node.to_lp_string();
+    let mut found = vec![];
+    found.extend(heuristics::simple_rounding(model, values).map(|x| ("simple rounding", x)));
+    found.extend(heuristics::shifting(model, values).map(|x| ("shifting", x)));
+    let periodic = options.heuristic_frequency > 0
+        && explored_nodes.is_multiple_of(options.heuristic_frequency);
+    if !is_root && !periodic {
+        return found;
     }
-    println!();
-
-    // Affiche chaque ligne avec la variable de base
-    let mut base_vars = variables
-        .iter()
-        .filter(|(_, v)| v.in_base)
-        .collect::<Vec<_>>();
-    base_vars.sort_by_key(|(_, v)| v.ligne);
-
-    for (i, ligne) in matrix.iter().enumerate() {
-        let base = base_vars
-            .iter()
-            .find(|v| v.1.ligne == i)
-            .map(|(x, _)| x.as_str())
-            .unwrap_or("-");
-        print!("{:<8}", base);
-        print!("{}    ", variables.get(base).unwrap().cout_original);
-        for val in ligne {
-            print!("{:<8}", val);
-        }
-        println!();
-    }
-
-    // Optionnel : ligne des coefficients de Z
-    if show_z_row {
-        let mut couts_z = vec!["Z".to_string()];
-        couts_z.push("".to_string()); // colonne b
-        for var in vars_sorted.values() {
-            let mut cb = variables
-                .iter()
-                .filter(|v| v.1.in_base)
-                .map(|v| v.1)
-                .collect::<Vec<_>>();
-            let my_var = variables.get(var).unwrap();
-            cb.sort_by(|a, b| a.ligne.cmp(&b.ligne));
-            let cb = cb
-                .iter()
-                .filter(|x| x.in_base)
-                .map(|x| x.cout_original)
-                .collect::<Vec<_>>();
-            let cout = my_var.cout_original;
-            let column: Vec<f64> = matrix.iter().map(|x| x[my_var.column]).collect();
-            let scalar = scalar_product(&cb, &column) - cout;
-            couts_z.push(format!("{}", scalar));
+    match incumbent {
+        Some((guide, z)) => {
+            found.extend(
+                heuristics::dive(model, lp, values, Some(guide), Some(z))
+                    .map(|x| ("guided diving", x)),
+            );
+            found.extend(
+                heuristics::rins(model, &node.base_lp, guide, values, options).map(|x| ("rins", x)),
+            );
         }
-        for val in couts_z {
-            print!("{:<8}", val);
+        None => {
+            found.extend(
+                heuristics::dive(model, lp, values, None, None).map(|x| ("fractional diving", x)),
+            );
         }
-        println!();
     }
+    // La pompe ne sert qu'à trouver une première solution
+    if is_root && incumbent.is_none() && found.is_empty() {
+        found.extend(
+            heuristics::feasibility_pump(model, lp, values).map(|x| ("feasibility pump", x)),
+        );
+    }
+    found
+}
+
+// Meilleure borne parmi les noeuds ouverts, l'incumbent étant lui-même une borne atteinte
+#[inline(always)]
+fn best_open_bound(stack: &[Node], incumbent: f64, is_min: f64) -> f64 {
+    stack
+        .iter()
+        .filter(|node| !node.bound.is_nan())
+        .map(|node| node.bound)
+        .fold(incumbent, |best, bound| {
+            if absolute_gap(best, bound, is_min) > 0.0 {
+                bound
+            } else {
+                best
+            }
+        })
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct Variable {
-    in_base: bool,
-    cout_original: f64,
-    ligne: usize,
-    column: usize,
-    is_slack: bool,
-    is_artificial: bool,
+    pub(crate) in_base: bool,
+    pub(crate) cout_original: f64,
+    pub(crate) ligne: usize,
+    pub(crate) column: usize,
+    pub(crate) is_slack: bool,
+    pub(crate) is_artificial: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(variables: &[(String, f64)], name: &str) -> f64 {
+        variables
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| *v)
+            .unwrap()
+    }
+
+    // Sans déclaration int, x est entière mais pas les écarts du but
+    #[test]
+    fn goal_deviations_stay_continuous() {
+        let options = BranchAndBoundOptions {
+            max_nodes: 30,
+            ..Default::default()
+        };
+        let result = branch_and_bound("goal g: x = 2.5;\nx <= 10;\n", &options).unwrap();
+        assert!((result.z - 0.5).abs() < PRECISION);
+        let x = value(&result.variables, "x");
+        assert!((x - 2.0).abs() < PRECISION || (x - 3.0).abs() < PRECISION);
+        let deviation = value(&result.variables, "g_under") + value(&result.variables, "g_over");
+        assert!((deviation - 0.5).abs() < PRECISION);
+        assert_eq!(result.goals.len(), 1);
+    }
+
+    #[test]
+    fn node_limit_is_not_infeasibility() {
+        let options = BranchAndBoundOptions {
+            max_nodes: 0,
+            presolve: false,
+            ..Default::default()
+        };
+        let error = branch_and_bound("max: x + y;\n2x + 2y <= 3;\nint x, y;\n", &options);
+        assert_eq!(error.unwrap_err(), NODE_LIMIT);
+        let options = BranchAndBoundOptions {
+            presolve: false,
+            ..Default::default()
+        };
+        let error = branch_and_bound("max: x;\n2x = 1;\nint x;\n", &options);
+        assert_eq!(error.unwrap_err(), NO_INTEGER_SOLUTION);
+    }
 }
@@ -0,0 +1,472 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+// Coefficients plus petits ignorés dans les colonnes des etas
+const ZERO: f64 = 1.0e-12;
+
+// Matrice creuse stockée par colonnes (CSC) : les coefficients de la colonne j sont
+// index[start[j]..start[j + 1]] (lignes, croissantes) et values[start[j]..start[j + 1]]
+#[derive(Debug, Clone, Default)]
+pub struct CscMatrix {
+    pub rows: usize,
+    pub start: Vec<usize>,
+    pub index: Vec<usize>,
+    pub values: Vec<f64>,
+}
+
+// Matrice creuse stockée par lignes (CSR), même disposition que CscMatrix
+#[derive(Debug, Clone, Default)]
+pub struct CsrMatrix {
+    pub columns: usize,
+    pub start: Vec<usize>,
+    pub index: Vec<usize>,
+    pub values: Vec<f64>,
+}
+
+impl CscMatrix {
+    // Construit la matrice à partir de ses lignes creuses (colonne, coefficient)
+    pub fn from_rows(rows: &[Vec<(usize, f64)>], columns: usize) -> Self {
+        let mut count = vec![0; columns + 1];
+        for row in rows {
+            for (j, a) in row {
+                if *a != 0.0 {
+                    count[*j + 1] += 1;
+                }
+            }
+        }
+        for j in 0..columns {
+            count[j + 1] += count[j];
+        }
+        let start = count.clone();
+        let mut next = count;
+        let mut index = vec![0; start[columns]];
+        let mut values = vec![0.0; start[columns]];
+        for (i, row) in rows.iter().enumerate() {
+            for (j, a) in row {
+                if *a != 0.0 {
+                    index[next[*j]] = i;
+                    values[next[*j]] = *a;
+                    next[*j] += 1;
+                }
+            }
+        }
+        CscMatrix {
+            rows: rows.len(),
+            start,
+            index,
+            values,
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.start.len() - 1
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.start[j]..self.start[j + 1];
+        self.index[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+
+    // Colonne j sous forme dense
+    pub fn dense_column(&self, j: usize) -> Vec<f64> {
+        let mut column = vec![0.0; self.rows];
+        for (i, a) in self.column(j) {
+            column[i] = a;
+        }
+        column
+    }
+
+    // Produit scalaire de la colonne j avec un vecteur dense indicé par les lignes
+    pub fn dot_column(&self, j: usize, y: &[f64]) -> f64 {
+        self.column(j).map(|(i, a)| a * y[i]).sum()
+    }
+
+    // Multiplie chaque coefficient a_ij par factor(i, j)
+    pub fn scale(&mut self, factor: impl Fn(usize, usize) -> f64) {
+        for j in 0..self.columns() {
+            for k in self.start[j]..self.start[j + 1] {
+                self.values[k] *= factor(self.index[k], j);
+            }
+        }
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix {
+        let mut count = vec![0; self.rows + 1];
+        for i in &self.index {
+            count[i + 1] += 1;
+        }
+        for i in 0..self.rows {
+            count[i + 1] += count[i];
+        }
+        let start = count.clone();
+        let mut next = count;
+        let mut index = vec![0; self.nnz()];
+        let mut values = vec![0.0; self.nnz()];
+        for j in 0..self.columns() {
+            for (i, a) in self.column(j) {
+                index[next[i]] = j;
+                values[next[i]] = a;
+                next[i] += 1;
+            }
+        }
+        CsrMatrix {
+            columns: self.columns(),
+            start,
+            index,
+            values,
+        }
+    }
+}
+
+impl CsrMatrix {
+    pub fn rows(&self) -> usize {
+        self.start.len() - 1
+    }
+
+    pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.start[i]..self.start[i + 1];
+        self.index[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+}
+
+// Inverse de la base sous forme produit : B^-1 = E_k ... E_1, chaque E ne différant de
+// l'identité que par la colonne de sa position pivot
+#[derive(Debug, Clone, Default)]
+pub struct EtaFile {
+    etas: Vec<(usize, Vec<(usize, f64)>)>,
+}
+
+impl EtaFile {
+    pub fn len(&self) -> usize {
+        self.etas.len()
+    }
+
+    // v <- B^-1 v
+    pub fn ftran(&self, v: &mut [f64]) {
+        for (p, eta) in &self.etas {
+            let pivot = v[*p];
+            if pivot == 0.0 {
+                continue;
+            }
+            for (i, e) in eta {
+                if i == p {
+                    v[*i] = e * pivot;
+                } else {
+                    v[*i] += e * pivot;
+                }
+            }
+        }
+    }
+
+    // u^T <- u^T B^-1
+    pub fn btran(&self, u: &mut [f64]) {
+        for (p, eta) in self.etas.iter().rev() {
+            u[*p] = eta.iter().map(|(i, e)| u[*i] * e).sum();
+        }
+    }
+
+    // Ajoute le pivot sur la position p de la colonne alpha = B^-1 a_q
+    pub fn push(&mut self, p: usize, alpha: &[f64]) {
+        let pivot = alpha[p];
+        let eta = alpha
+            .iter()
+            .enumerate()
+            .filter(|(i, a)| *i == p || a.abs() > ZERO)
+            .map(|(i, a)| {
+                if i == p {
+                    (i, 1.0 / pivot)
+                } else {
+                    (i, -a / pivot)
+                }
+            })
+            .collect();
+        self.etas.push((p, eta));
+    }
+}
+
+// Pivot de Cholesky négligeable devant la diagonale de A D A^T : la ligne est dépendante
+const CHOLESKY_TOLERANCE: f64 = 1.0e-14;
+// Pivot mis à la place d'un pivot négligeable, la composante de la solution devient nulle
+const HUGE_PIVOT: f64 = 1.0e128;
+// Une colonne est dense si elle a plus de DENSE_RATIO fois le nombre moyen de coefficients
+// (et au moins MIN_DENSE_COLUMN) : elle formerait une clique dans A D A^T
+const DENSE_RATIO: f64 = 10.0;
+const MIN_DENSE_COLUMN: usize = 40;
+const MAX_DENSE_COLUMNS: usize = 100;
+const REFINEMENT_STEPS: usize = 2;
+
+// Factorisation de Cholesky creuse L L^T = P A_s D A_s^T P^T des équations normales,
+// A_s étant A privée de ses colonnes denses. L'ordre d'élimination (degré minimum) et le motif
+// de L ne dépendent que de A, seules les valeurs sont recalculées pour chaque D.
+// Les colonnes denses U = A_d D^1/2 sont rajoutées par Sherman-Morrison-Woodbury :
+// (M + U U^T)^-1 = M^-1 - M^-1 U (I + U^T M^-1 U)^-1 U^T M^-1.
+pub struct NormalEquations<'a> {
+    matrix: &'a CscMatrix,
+    rows: CsrMatrix,
+    sparse: Vec<bool>,
+    dense: Vec<usize>,
+    // Colonnes u_k = a_k d_k^1/2, w_k = M^-1 u_k et Cholesky de I + U^T W
+    updates: Vec<Vec<f64>>,
+    corrections: Vec<Vec<f64>>,
+    capacitance: Vec<Vec<f64>>,
+    d: Vec<f64>,
+    // Ligne de A éliminée à l'étape k et étape de chaque ligne
+    order: Vec<usize>,
+    step: Vec<usize>,
+    // L par colonnes, la diagonale en tête de chaque colonne
+    factor: CscMatrix,
+    // Coefficients L(k, j), j < k, de chaque ligne k : (colonne j, indice dans factor)
+    row_entries: Vec<Vec<(usize, usize)>>,
+}
+
+impl<'a> NormalEquations<'a> {
+    // used[j] indique les colonnes de A qui entrent dans A D A^T
+    pub fn new(matrix: &'a CscMatrix, used: &[bool]) -> Self {
+        let m = matrix.rows;
+        let count = |j: usize| matrix.start[j + 1] - matrix.start[j];
+        let columns = (1..matrix.columns())
+            .filter(|j| used[*j])
+            .collect::<Vec<_>>();
+        let average =
+            columns.iter().map(|j| count(*j)).sum::<usize>() as f64 / columns.len().max(1) as f64;
+        let mut dense = columns
+            .iter()
+            .copied()
+            .filter(|j| count(*j) >= MIN_DENSE_COLUMN && count(*j) as f64 > DENSE_RATIO * average)
+            .collect::<Vec<_>>();
+        dense.sort_by_key(|j| Reverse(count(*j)));
+        dense.truncate(MAX_DENSE_COLUMNS);
+        let mut sparse = used.to_vec();
+        for j in &dense {
+            sparse[*j] = false;
+        }
+        let mut adjacency: Vec<HashSet<usize>> = vec![HashSet::new(); m];
+        for j in (1..matrix.columns()).filter(|j| sparse[*j]) {
+            let rows = matrix.column(j).map(|(i, _)| i).collect::<Vec<_>>();
+            for i in &rows {
+                adjacency[*i].extend(rows.iter().filter(|k| *k != i));
+            }
+        }
+        // Degré minimum : chaque ligne éliminée relie ses voisines entre elles
+        let mut heap = adjacency
+            .iter()
+            .enumerate()
+            .map(|(i, neighbours)| Reverse((neighbours.len(), i)))
+            .collect::<BinaryHeap<_>>();
+        let mut eliminated = vec![false; m];
+        let mut order = Vec::with_capacity(m);
+        let mut patterns = Vec::with_capacity(m);
+        while let Some(Reverse((degree, i))) = heap.pop() {
+            if eliminated[i] || degree != adjacency[i].len() {
+                continue;
+            }
+            eliminated[i] = true;
+            let neighbours = std::mem::take(&mut adjacency[i])
+                .into_iter()
+                .collect::<Vec<_>>();
+            for u in &neighbours {
+                adjacency[*u].remove(&i);
+                adjacency[*u].extend(neighbours.iter().filter(|v| *v != u));
+                heap.push(Reverse((adjacency[*u].len(), *u)));
+            }
+            order.push(i);
+            patterns.push(neighbours);
+        }
+        let mut step = vec![0; m];
+        for (k, i) in order.iter().enumerate() {
+            step[*i] = k;
+        }
+        let mut start = vec![0];
+        let mut index = vec![];
+        for (k, neighbours) in patterns.iter().enumerate() {
+            let mut column = neighbours.iter().map(|u| step[*u]).collect::<Vec<_>>();
+            column.sort_unstable();
+            index.push(k);
+            index.extend(column);
+            start.push(index.len());
+        }
+        let mut row_entries = vec![vec![]; m];
+        for j in 0..m {
+            for e in start[j] + 1..start[j + 1] {
+                row_entries[index[e]].push((j, e));
+            }
+        }
+        let values = vec![0.0; index.len()];
+        NormalEquations {
+            matrix,
+            rows: matrix.to_csr(),
+            sparse,
+            dense,
+            updates: vec![],
+            corrections: vec![],
+            capacitance: vec![],
+            d: vec![],
+            order,
+            step,
+            factor: CscMatrix {
+                rows: m,
+                start,
+                index,
+                values,
+            },
+            row_entries,
+        }
+    }
+
+    // Factorise A D A^T, d est indicé par colonne de A (0 pour les colonnes ignorées)
+    pub fn factorize(&mut self, d: &[f64]) {
+        let mut work = vec![0.0; self.order.len()];
+        for k in 0..self.order.len() {
+            for (j, a) in self
+                .rows
+                .row(self.order[k])
+                .filter(|(j, _)| self.sparse[*j] && d[*j] != 0.0)
+            {
+                for (l, b) in self.matrix.column(j) {
+                    if self.step[l] >= k {
+                        work[self.step[l]] += a * d[j] * b;
+                    }
+                }
+            }
+            let diagonal = work[k];
+            for (j, e) in &self.row_entries[k] {
+                let lkj = self.factor.values[*e];
+                for f in *e..self.factor.start[j + 1] {
+                    work[self.factor.index[f]] -= lkj * self.factor.values[f];
+                }
+            }
+            let pivot = if work[k] <= CHOLESKY_TOLERANCE * diagonal || diagonal <= 0.0 {
+                HUGE_PIVOT
+            } else {
+                work[k]
+            };
+            let lkk = pivot.sqrt();
+            for e in self.factor.start[k]..self.factor.start[k + 1] {
+                let r = self.factor.index[e];
+                self.factor.values[e] = if r == k { lkk } else { work[r] / lkk };
+                work[r] = 0.0;
+            }
+        }
+        self.updates = self
+            .dense
+            .iter()
+            .map(|j| {
+                let mut u = self.matrix.dense_column(*j);
+                let scale = d[*j].sqrt();
+                u.iter_mut().for_each(|a| *a *= scale);
+                u
+            })
+            .collect();
+        self.corrections = self
+            .updates
+            .iter()
+            .map(|u| {
+                let mut w = u.clone();
+                self.solve_sparse(&mut w);
+                w
+            })
+            .collect();
+        // Cholesky dense de I + U^T W, symétrique définie positive
+        let k = self.dense.len();
+        let mut s = vec![vec![0.0; k]; k];
+        for a in 0..k {
+            for b in 0..=a {
+                let mut v = dot(&self.updates[a], &self.corrections[b]);
+                if a == b {
+                    v += 1.0;
+                }
+                v -= dot(&s[a][..b], &s[b][..b]);
+                s[a][b] = if a == b {
+                    v.max(f64::MIN_POSITIVE).sqrt()
+                } else {
+                    v / s[b][b]
+                };
+            }
+        }
+        self.capacitance = s;
+        self.d = d.to_vec();
+    }
+
+    // Résout A D A^T x = r, r est indicé par les lignes de A. Woodbury perd de la précision
+    // quand D devient très mal conditionné, le résidu est corrigé par raffinement itératif.
+    pub fn solve(&self, r: &mut [f64]) {
+        let rhs = r.to_vec();
+        self.solve_once(r);
+        for _ in 0..REFINEMENT_STEPS {
+            let mut residual = rhs.clone();
+            for j in (1..self.matrix.columns()).filter(|j| self.d[*j] != 0.0) {
+                let t = self.d[j] * self.matrix.dot_column(j, r);
+                for (i, a) in self.matrix.column(j) {
+                    residual[i] -= a * t;
+                }
+            }
+            self.solve_once(&mut residual);
+            for (x, e) in r.iter_mut().zip(&residual) {
+                *x += e;
+            }
+        }
+    }
+
+    fn solve_once(&self, r: &mut [f64]) {
+        self.solve_sparse(r);
+        let k = self.dense.len();
+        if k == 0 {
+            return;
+        }
+        let s = &self.capacitance;
+        let mut t = self.updates.iter().map(|u| dot(u, r)).collect::<Vec<_>>();
+        for a in 0..k {
+            for b in 0..a {
+                t[a] -= s[a][b] * t[b];
+            }
+            t[a] /= s[a][a];
+        }
+        for a in (0..k).rev() {
+            for b in a + 1..k {
+                t[a] -= s[b][a] * t[b];
+            }
+            t[a] /= s[a][a];
+        }
+        for (w, t) in self.corrections.iter().zip(&t) {
+            for (x, w) in r.iter_mut().zip(w) {
+                *x -= t * w;
+            }
+        }
+    }
+
+    // Résout L L^T x = r
+    fn solve_sparse(&self, r: &mut [f64]) {
+        let l = &self.factor;
+        let mut x = self.order.iter().map(|i| r[*i]).collect::<Vec<_>>();
+        for k in 0..x.len() {
+            x[k] /= l.values[l.start[k]];
+            for e in l.start[k] + 1..l.start[k + 1] {
+                x[l.index[e]] -= l.values[e] * x[k];
+            }
+        }
+        for k in (0..x.len()).rev() {
+            let mut s = x[k];
+            for e in l.start[k] + 1..l.start[k + 1] {
+                s -= l.values[e] * x[l.index[e]];
+            }
+            x[k] = s / l.values[l.start[k]];
+        }
+        for (k, i) in self.order.iter().enumerate() {
+            r[*i] = x[k];
+        }
+    }
+}
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
@@ -0,0 +1,406 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const PRECISION: f64 = 1.0e-9;
+const MAX_ITERATIONS: usize = 100_000;
+
+// Problème de transport : offre de chaque source, demande de chaque destination et coût
+// unitaire de chaque couple. Les noms sont facultatifs (1, 2, ... par défaut).
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransportationProblem {
+    pub supply: Vec<f64>,
+    pub demand: Vec<f64>,
+    pub costs: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub destinations: Vec<String>,
+}
+
+// Solution de départ
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InitialSolution {
+    NorthWestCorner,
+    LeastCost,
+    #[default]
+    Vogel,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct TransportationOptions {
+    pub method: InitialSolution,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransportationSolution {
+    // Quantité x_source_destination de chaque couple, comme get_solution
+    pub variables: Vec<(String, f64)>,
+    pub z: f64,
+    // Coût de la solution de départ et nombre d'itérations de MODI
+    pub initial_z: f64,
+    pub iterations: usize,
+    // Problème non équilibré : offre non expédiée et demande non satisfaite
+    pub unused_supply: Vec<f64>,
+    pub unmet_demand: Vec<f64>,
+}
+
+// Lit un problème en JSON ou au format texte compact :
+//   supply: 20 30 25
+//   demand: 10 25 40
+//   costs:
+//   8 6 10
+//   9 12 13
+//   14 9 16
+pub fn parse_transportation(input: &str) -> Result<TransportationProblem, String> {
+    let problem = if input.trim_start().starts_with('{') {
+        serde_json::from_str(input).map_err(|e| format!("JSON invalide : {}", e))?
+    } else {
+        parse_text(input)?
+    };
+    check(&problem)?;
+    Ok(problem)
+}
+
+fn parse_numbers(line: &str) -> Result<Vec<f64>, String> {
+    line.split_whitespace()
+        .map(|a| {
+            a.parse::<f64>()
+                .map_err(|_| format!("Nombre invalide : {}", a))
+        })
+        .collect()
+}
+
+fn parse_text(input: &str) -> Result<TransportationProblem, String> {
+    let mut problem = TransportationProblem {
+        supply: vec![],
+        demand: vec![],
+        costs: vec![],
+        sources: vec![],
+        destinations: vec![],
+    };
+    let mut in_costs = false;
+    for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(rest) = line.strip_prefix("supply:") {
+            problem.supply = parse_numbers(rest)?;
+            in_costs = false;
+        } else if let Some(rest) = line.strip_prefix("demand:") {
+            problem.demand = parse_numbers(rest)?;
+            in_costs = false;
+        } else if let Some(rest) = line.strip_prefix("costs:") {
+            in_costs = true;
+            if !rest.trim().is_empty() {
+                problem.costs.push(parse_numbers(rest)?);
+            }
+        } else if in_costs {
+            problem.costs.push(parse_numbers(line)?);
+        } else {
+            return Err(format!("Ligne inattendue : {}", line));
+        }
+    }
+    Ok(problem)
+}
+
+fn check(problem: &TransportationProblem) -> Result<(), String> {
+    let (m, n) = (problem.supply.len(), problem.demand.len());
+    if m == 0 || n == 0 {
+        return Err("Il faut au moins une source et une destination".to_string());
+    }
+    if problem.costs.len() != m || problem.costs.iter().any(|row| row.len() != n) {
+        return Err(format!(
+            "La matrice des coûts doit être de taille {} x {}",
+            m, n
+        ));
+    }
+    if problem
+        .supply
+        .iter()
+        .chain(&problem.demand)
+        .any(|a| !a.is_finite() || *a < 0.0)
+    {
+        return Err("Les offres et les demandes doivent être positives".to_string());
+    }
+    if !problem.sources.is_empty() && problem.sources.len() != m
+        || !problem.destinations.is_empty() && problem.destinations.len() != n
+    {
+        return Err("Il faut un nom par source et par destination".to_string());
+    }
+    Ok(())
+}
+
+// Tableau de transport équilibré : une destination (ou une source) fictive de coût nul
+// absorbe l'écart entre l'offre et la demande. Les cases de base forment un arbre couvrant
+// du graphe biparti sources - destinations.
+struct Tableau {
+    supply: Vec<f64>,
+    demand: Vec<f64>,
+    costs: Vec<Vec<f64>>,
+    flow: Vec<Vec<f64>>,
+    basic: Vec<Vec<bool>>,
+}
+
+impl Tableau {
+    fn new(problem: &TransportationProblem) -> Self {
+        let mut supply = problem.supply.clone();
+        let mut demand = problem.demand.clone();
+        let mut costs = problem.costs.clone();
+        let excess = supply.iter().sum::<f64>() - demand.iter().sum::<f64>();
+        if excess > PRECISION {
+            demand.push(excess);
+            for row in costs.iter_mut() {
+                row.push(0.0);
+            }
+        } else if excess < -PRECISION {
+            supply.push(-excess);
+            costs.push(vec![0.0; demand.len()]);
+        }
+        let (m, n) = (supply.len(), demand.len());
+        Tableau {
+            supply,
+            demand,
+            costs,
+            flow: vec![vec![0.0; n]; m],
+            basic: vec![vec![false; n]; m],
+        }
+    }
+
+    fn cost(&self) -> f64 {
+        self.flow
+            .iter()
+            .zip(&self.costs)
+            .flat_map(|(flow, costs)| flow.iter().zip(costs))
+            .map(|(x, c)| x * c)
+            .sum()
+    }
+
+    // Remplit la base case par case. Chaque allocation raye une ligne ou une colonne, jamais
+    // les deux sauf à la dernière : quand les deux s'épuisent ensemble, la colonne reste avec
+    // une demande nulle et recevra une case de base à 0 (dégénérescence), ce qui donne
+    // toujours m + n - 1 cases de base.
+    fn initial_solution(&mut self, method: InitialSolution) {
+        let (m, n) = (self.supply.len(), self.demand.len());
+        let mut supply = self.supply.clone();
+        let mut demand = self.demand.clone();
+        let mut active_rows = vec![true; m];
+        let mut active_columns = vec![true; n];
+        let (mut rows, mut columns) = (m, n);
+        loop {
+            let (i, j) = match method {
+                InitialSolution::NorthWestCorner => (
+                    (0..m).find(|i| active_rows[*i]).unwrap(),
+                    (0..n).find(|j| active_columns[*j]).unwrap(),
+                ),
+                InitialSolution::LeastCost => self.least_cost(&active_rows, &active_columns),
+                InitialSolution::Vogel => self.vogel(&active_rows, &active_columns),
+            };
+            let quantity = supply[i].min(demand[j]);
+            self.flow[i][j] = quantity;
+            self.basic[i][j] = true;
+            supply[i] -= quantity;
+            demand[j] -= quantity;
+            if rows == 1 && columns == 1 {
+                break;
+            }
+            if supply[i] <= demand[j] && rows > 1 || columns == 1 {
+                active_rows[i] = false;
+                rows -= 1;
+            } else {
+                active_columns[j] = false;
+                columns -= 1;
+            }
+        }
+    }
+
+    // Case active de plus petit coût parmi cells
+    fn cheapest(&self, cells: impl Iterator<Item = (usize, usize)>) -> Option<(usize, usize)> {
+        cells.min_by(|a, b| self.costs[a.0][a.1].total_cmp(&self.costs[b.0][b.1]))
+    }
+
+    fn least_cost(&self, active_rows: &[bool], active_columns: &[bool]) -> (usize, usize) {
+        let cells = (0..active_rows.len())
+            .filter(|i| active_rows[*i])
+            .flat_map(|i| {
+                (0..active_columns.len())
+                    .filter(|j| active_columns[*j])
+                    .map(move |j| (i, j))
+            });
+        self.cheapest(cells).unwrap()
+    }
+
+    // Approximation de Vogel : la ligne ou la colonne dont l'écart entre les deux plus petits
+    // coûts est le plus grand reçoit son allocation la moins chère
+    fn vogel(&self, active_rows: &[bool], active_columns: &[bool]) -> (usize, usize) {
+        let penalty = |costs: &mut dyn Iterator<Item = f64>| {
+            let (mut first, mut second) = (f64::INFINITY, f64::INFINITY);
+            for c in costs {
+                if c < first {
+                    second = first;
+                    first = c;
+                } else if c < second {
+                    second = c;
+                }
+            }
+            if second.is_finite() {
+                second - first
+            } else {
+                first
+            }
+        };
+        let rows = (0..active_rows.len()).filter(|i| active_rows[*i]);
+        let columns = (0..active_columns.len()).filter(|j| active_columns[*j]);
+        let mut best: Option<(f64, Vec<(usize, usize)>)> = None;
+        for i in rows.clone() {
+            let cells = columns.clone().map(|j| (i, j)).collect::<Vec<_>>();
+            let p = penalty(&mut cells.iter().map(|(i, j)| self.costs[*i][*j]));
+            if best.as_ref().is_none_or(|(max, _)| p > *max) {
+                best = Some((p, cells));
+            }
+        }
+        for j in columns {
+            let cells = rows.clone().map(|i| (i, j)).collect::<Vec<_>>();
+            let p = penalty(&mut cells.iter().map(|(i, j)| self.costs[*i][*j]));
+            if best.as_ref().is_none_or(|(max, _)| p > *max) {
+                best = Some((p, cells));
+            }
+        }
+        let (_, cells) = best.unwrap();
+        self.cheapest(cells.into_iter()).unwrap()
+    }
+
+    // Voisins de chaque noeud de l'arbre de base : sources 0..m, destinations m..m+n
+    fn tree(&self) -> Vec<Vec<usize>> {
+        let (m, n) = (self.supply.len(), self.demand.len());
+        let mut adjacency = vec![vec![]; m + n];
+        for i in 0..m {
+            for j in (0..n).filter(|j| self.basic[i][*j]) {
+                adjacency[i].push(m + j);
+                adjacency[m + j].push(i);
+            }
+        }
+        adjacency
+    }
+
+    // Potentiels u_i + v_j = c_ij sur les cases de base, u_0 = 0
+    fn potentials(&self, adjacency: &[Vec<usize>]) -> (Vec<f64>, Vec<f64>) {
+        let m = self.supply.len();
+        let mut potential = vec![f64::NAN; adjacency.len()];
+        potential[0] = 0.0;
+        let mut queue = VecDeque::from([0]);
+        while let Some(a) = queue.pop_front() {
+            for b in &adjacency[a] {
+                if potential[*b].is_nan() {
+                    let (i, j) = if a < m { (a, b - m) } else { (*b, a - m) };
+                    potential[*b] = self.costs[i][j] - potential[a];
+                    queue.push_back(*b);
+                }
+            }
+        }
+        let v = potential.split_off(m);
+        (potential, v)
+    }
+
+    // Chemin de l'arbre de la destination j à la source i, sous forme de cases
+    fn path(&self, adjacency: &[Vec<usize>], i: usize, j: usize) -> Vec<(usize, usize)> {
+        let m = self.supply.len();
+        let mut previous = vec![usize::MAX; adjacency.len()];
+        previous[m + j] = m + j;
+        let mut queue = VecDeque::from([m + j]);
+        while let Some(a) = queue.pop_front() {
+            if a == i {
+                break;
+            }
+            for b in &adjacency[a] {
+                if previous[*b] == usize::MAX {
+                    previous[*b] = a;
+                    queue.push_back(*b);
+                }
+            }
+        }
+        let mut cells = vec![];
+        let mut a = i;
+        while a != m + j {
+            let b = previous[a];
+            cells.push(if a < m { (a, b - m) } else { (b, a - m) });
+            a = b;
+        }
+        // De la destination j vers la source i
+        cells.reverse();
+        cells
+    }
+
+    // Méthode MODI (u-v) : la case de coût réduit c_ij - u_i - v_j le plus négatif entre dans
+    // la base, le flot circule le long du cycle qu'elle forme avec l'arbre et la case -
+    // de plus petit flot en sort. Une case à 0 reste de base quand plusieurs s'annulent.
+    fn modi(&mut self) -> Result<usize, String> {
+        let (m, n) = (self.supply.len(), self.demand.len());
+        for iteration in 0..MAX_ITERATIONS {
+            let adjacency = self.tree();
+            let (u, v) = self.potentials(&adjacency);
+            let entering = (0..m)
+                .flat_map(|i| (0..n).map(move |j| (i, j)))
+                .filter(|(i, j)| !self.basic[*i][*j])
+                .map(|(i, j)| ((i, j), self.costs[i][j] - u[i] - v[j]))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some(((i, j), _)) = entering.filter(|(_, d)| *d < -PRECISION) else {
+                return Ok(iteration);
+            };
+            // Cycle : (i, j) +, puis alternativement - et + le long du chemin de j à i
+            let path = self.path(&adjacency, i, j);
+            let (leaving, theta) = path
+                .iter()
+                .step_by(2)
+                .map(|(a, b)| ((*a, *b), self.flow[*a][*b]))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            for (k, (a, b)) in path.iter().enumerate() {
+                if k % 2 == 0 {
+                    self.flow[*a][*b] -= theta;
+                } else {
+                    self.flow[*a][*b] += theta;
+                }
+            }
+            self.flow[i][j] = theta;
+            self.basic[i][j] = true;
+            self.flow[leaving.0][leaving.1] = 0.0;
+            self.basic[leaving.0][leaving.1] = false;
+        }
+        Err("MODI ne converge pas".to_string())
+    }
+}
+
+pub fn solve_transportation(
+    problem: &TransportationProblem,
+    options: &TransportationOptions,
+) -> Result<TransportationSolution, String> {
+    check(problem)?;
+    let mut tableau = Tableau::new(problem);
+    tableau.initial_solution(options.method);
+    let initial_z = tableau.cost();
+    let iterations = tableau.modi()?;
+    let (m, n) = (problem.supply.len(), problem.demand.len());
+    let name =
+        |names: &[String], k: usize| names.get(k).cloned().unwrap_or_else(|| (k + 1).to_string());
+    let mut variables = Vec::with_capacity(m * n);
+    for i in 0..m {
+        for j in 0..n {
+            let source = name(&problem.sources, i);
+            let destination = name(&problem.destinations, j);
+            variables.push((format!("x_{}_{}", source, destination), tableau.flow[i][j]));
+        }
+    }
+    let unused_supply = (0..m)
+        .map(|i| problem.supply[i] - tableau.flow[i].iter().take(n).sum::<f64>())
+        .collect();
+    let unmet_demand = (0..n)
+        .map(|j| problem.demand[j] - tableau.flow.iter().take(m).map(|row| row[j]).sum::<f64>())
+        .collect();
+    Ok(TransportationSolution {
+        variables,
+        z: tableau.cost(),
+        initial_z,
+        iterations,
+        unused_supply,
+        unmet_demand,
+    })
+}
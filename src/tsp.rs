@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Sense, format_constraint};
+use crate::simplexef64::{
+    self, BranchAndBoundCallbacks, BranchAndBoundOptions, CallbackRow, PRECISION,
+};
+
+// Voyageur de commerce symétrique : distance entre chaque couple de villes
+#[derive(Debug, Clone, Deserialize)]
+pub struct TspProblem {
+    pub distances: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TspSolution {
+    // Villes dans l'ordre de la tournée, qui part de la ville 0
+    pub tour: Vec<usize>,
+    pub length: f64,
+    pub explored_nodes: usize,
+    pub lazy_constraints: usize,
+    pub user_cuts: usize,
+}
+
+// Lit un problème en JSON ou sous forme de matrice des distances, une ligne par ville :
+//   0 3 4
+//   3 0 5
+//   4 5 0
+pub fn parse_tsp(input: &str) -> Result<TspProblem, String> {
+    let problem = if input.trim_start().starts_with('{') {
+        serde_json::from_str(input).map_err(|e| format!("JSON invalide : {}", e))?
+    } else {
+        TspProblem {
+            distances: input
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(|line| {
+                    line.split_whitespace()
+                        .map(|a| {
+                            a.parse::<f64>()
+                                .map_err(|_| format!("Nombre invalide : {}", a))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<_, _>>()?,
+        }
+    };
+    check(&problem)?;
+    Ok(problem)
+}
+
+fn check(problem: &TspProblem) -> Result<(), String> {
+    let n = problem.distances.len();
+    if n < 3 {
+        return Err("Il faut au moins trois villes".to_string());
+    }
+    if problem.distances.iter().any(|row| row.len() != n) {
+        return Err("La matrice des distances doit être carrée".to_string());
+    }
+    for i in 0..n {
+        for j in 0..i {
+            if (problem.distances[i][j] - problem.distances[j][i]).abs() > PRECISION {
+                return Err(format!("Les distances {} {} ne sont pas symétriques", i, j));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn edge(i: usize, j: usize) -> String {
+    format!("x_{}_{}", i.min(j), i.max(j))
+}
+
+// Extrémités de l'arête x_i_j
+fn ends(name: &str) -> Option<(usize, usize)> {
+    let (i, j) = name.strip_prefix("x_")?.split_once('_')?;
+    Some((i.parse().ok()?, j.parse().ok()?))
+}
+
+// Voisins de chaque ville par les arêtes de valeur supérieure à threshold
+fn adjacency(n: usize, variables: &[(String, f64)], threshold: f64) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![vec![]; n];
+    for (i, j) in variables
+        .iter()
+        .filter(|(_, v)| *v > threshold)
+        .filter_map(|(name, _)| ends(name))
+    {
+        adjacency[i].push(j);
+        adjacency[j].push(i);
+    }
+    adjacency
+}
+
+// Composantes connexes du graphe des arêtes de valeur supérieure à threshold
+fn components(n: usize, variables: &[(String, f64)], threshold: f64) -> Vec<Vec<usize>> {
+    let adjacency = adjacency(n, variables, threshold);
+    let mut seen = vec![false; n];
+    let mut components = vec![];
+    for start in 0..n {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut component = vec![start];
+        let mut k = 0;
+        while k < component.len() {
+            for next in &adjacency[component[k]] {
+                if !seen[*next] {
+                    seen[*next] = true;
+                    component.push(*next);
+                }
+            }
+            k += 1;
+        }
+        components.push(component);
+    }
+    components
+}
+
+// Contraintes d'élimination des sous-tours sum_{i, j dans S} x_ij <= |S| - 1 des
+// composantes S qui ne contiennent pas toutes les villes
+fn subtour_rows(n: usize, variables: &[(String, f64)], threshold: f64) -> Vec<CallbackRow> {
+    components(n, variables, threshold)
+        .into_iter()
+        .filter(|component| component.len() < n)
+        .map(|component| CallbackRow {
+            coeffs: component
+                .iter()
+                .enumerate()
+                .flat_map(|(k, i)| component[k + 1..].iter().map(|j| (edge(*i, *j), 1.0)))
+                .collect(),
+            sense: Sense::Leq,
+            rhs: component.len() as f64 - 1.0,
+        })
+        .collect()
+}
+
+// Modèle à deux arêtes par ville, sans les contraintes de sous-tours qui sont en nombre
+// exponentiel : elles sont ajoutées par le callback des contraintes paresseuses quand une
+// solution entière forme plusieurs cycles, et par celui des coupes quand le support d'une
+// relaxation n'est pas connexe
+pub fn solve_tsp(problem: &TspProblem) -> Result<TspSolution, String> {
+    let n = problem.distances.len();
+    let pairs = (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .collect::<Vec<_>>();
+    let mut lp = format!(
+        "min: {};\n",
+        pairs
+            .iter()
+            .map(|(i, j)| format!("{:+} {}", problem.distances[*i][*j], edge(*i, *j)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    for i in 0..n {
+        let coeffs = (0..n)
+            .filter(|j| *j != i)
+            .map(|j| (edge(i, j), 1.0))
+            .collect::<Vec<_>>();
+        lp.push_str(&format_constraint(&coeffs, "=", 2.0));
+        lp.push('\n');
+    }
+    for (i, j) in &pairs {
+        lp.push_str(&format_constraint(&[(edge(*i, *j), 1.0)], "<=", 1.0));
+        lp.push('\n');
+    }
+    let integers = pairs
+        .iter()
+        .map(|(i, j)| edge(*i, *j))
+        .collect::<Vec<_>>()
+        .join(", ");
+    lp.push_str(&format!("int {};\n", integers));
+
+    let mut callbacks = BranchAndBoundCallbacks {
+        lazy: Some(Box::new(|variables| subtour_rows(n, variables, 0.5))),
+        user_cuts: Some(Box::new(|variables| subtour_rows(n, variables, PRECISION))),
+    };
+    let result =
+        simplexef64::branch_and_bound_with(&lp, &BranchAndBoundOptions::default(), &mut callbacks)?;
+    let next = adjacency(n, &result.variables, 0.5);
+    let mut tour = vec![0];
+    while tour.len() < n {
+        let last = tour[tour.len() - 1];
+        match next[last].iter().find(|j| !tour.contains(j)) {
+            Some(j) => tour.push(*j),
+            None => return Err("La solution ne forme pas une tournée".to_string()),
+        }
+    }
+    Ok(TspSolution {
+        tour,
+        length: result.z,
+        explored_nodes: result.explored_nodes,
+        lazy_constraints: result.cuts.lazy_constraints,
+        user_cuts: result.cuts.user_cuts,
+    })
+}